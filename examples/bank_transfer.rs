@@ -0,0 +1,218 @@
+#![deny(missing_docs, warnings, clippy::all, clippy::pedantic)]
+
+//! This example embeds the transaction state machine of the library in a model of a bank
+//! transfer, and checks that two-phase commit conserves money.
+//!
+//! # Transfer
+//!
+//! Two participants hold an account each; the transfer debits the first account and credits the
+//! second one. Each participant keeps its write pending until it learns the decision of the
+//! coordinator, and the participant holding the debited account votes against commit if the
+//! balance is insufficient.
+//!
+//! Run it with `cargo run --example bank_transfer`.
+
+use homework::transaction::{State as TransactionState, Transaction};
+
+use std::io;
+
+use stateright::{Checker, Model, Property};
+
+/// The initial balances of the accounts.
+const INITIAL_BALANCES: [i64; 2] = [100, 50];
+
+/// The amounts of the transfers; the larger one exceeds the balance of the debited account.
+const AMOUNTS: [i64; 2] = [30, 150];
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Bank {
+    /// The coordinator transaction.
+    coordinator: Transaction,
+
+    /// The accounts held by the participants.
+    accounts: Vec<Account>,
+}
+
+/// [`Account`] is an account held by a participant.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Account {
+    /// The participant transaction.
+    transaction: Transaction,
+
+    /// The committed balance.
+    balance: i64,
+
+    /// The pending write of the transfer.
+    delta: i64,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum BankAction {
+    /// The coordinator asks the participants to prepare.
+    RequestPrepare,
+
+    /// The participant validates its pending write, and votes.
+    Vote(usize),
+
+    /// The coordinator commits the transfer.
+    Commit,
+
+    /// The coordinator rolls back the transfer.
+    Rollback,
+
+    /// The participant applies the decision of the coordinator.
+    Apply(usize),
+}
+
+/// [`BankTransferModel`] implements state transitions of a bank transfer.
+struct BankTransferModel;
+
+impl BankTransferModel {
+    fn vote(last_state: &Bank, index: usize) -> Bank {
+        let mut state = last_state.clone();
+        let account = &mut state.accounts[index];
+        if account.balance + account.delta >= 0 && account.transaction.prepare() {
+            state.coordinator.report_prepared(index);
+        } else {
+            account.transaction.rollback();
+            state.coordinator.rollback();
+        }
+        state
+    }
+
+    fn apply(last_state: &Bank, index: usize) -> Option<Bank> {
+        let mut state = last_state.clone();
+        let account = &mut state.accounts[index];
+        match state.coordinator.state() {
+            TransactionState::Committed if account.transaction.commit() => {
+                account.balance += account.delta;
+            }
+            TransactionState::RolledBack if !account.transaction.state().is_terminal() => {
+                account.transaction.rollback();
+            }
+            _ => return None,
+        }
+        Some(state)
+    }
+}
+
+impl Model for BankTransferModel {
+    type State = Bank;
+    type Action = BankAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        AMOUNTS
+            .iter()
+            .map(|amount| {
+                let mut coordinator = Transaction::default();
+                coordinator.start();
+                let accounts = INITIAL_BALANCES
+                    .iter()
+                    .zip([-amount, *amount])
+                    .enumerate()
+                    .map(|(index, (balance, delta))| {
+                        coordinator.add_participant(index);
+                        let mut transaction = Transaction::default();
+                        transaction.start();
+                        Account {
+                            transaction,
+                            balance: *balance,
+                            delta,
+                        }
+                    })
+                    .collect();
+                Bank {
+                    coordinator,
+                    accounts,
+                }
+            })
+            .collect()
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        match state.coordinator.state() {
+            TransactionState::Active => {
+                actions.push(BankAction::RequestPrepare);
+                actions.push(BankAction::Rollback);
+            }
+            TransactionState::Prepared => {
+                for (index, account) in state.accounts.iter().enumerate() {
+                    if account.transaction.state() == TransactionState::Active {
+                        actions.push(BankAction::Vote(index));
+                    }
+                }
+                if state.coordinator.is_all_prepared() {
+                    actions.push(BankAction::Commit);
+                }
+                actions.push(BankAction::Rollback);
+            }
+            TransactionState::Committed | TransactionState::RolledBack => {
+                for (index, account) in state.accounts.iter().enumerate() {
+                    if !account.transaction.state().is_terminal() {
+                        actions.push(BankAction::Apply(index));
+                    }
+                }
+            }
+            TransactionState::Inactive | TransactionState::PreCommitted => (),
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            BankAction::RequestPrepare => {
+                let mut state = last_state.clone();
+                state.coordinator.prepare().then_some(state)
+            }
+            BankAction::Vote(index) => Some(Self::vote(last_state, index)),
+            BankAction::Commit => {
+                let mut state = last_state.clone();
+                state.coordinator.commit().then_some(state)
+            }
+            BankAction::Rollback => {
+                let mut state = last_state.clone();
+                state.coordinator.rollback().then_some(state)
+            }
+            BankAction::Apply(index) => Self::apply(last_state, index),
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("conservation of money", |_, state| {
+                // The balances add up to the initial total once the committed writes that have
+                // not yet been applied are taken into account.
+                let committed = state.coordinator.state() == TransactionState::Committed;
+                let total: i64 = state
+                    .accounts
+                    .iter()
+                    .map(|account| {
+                        if committed && account.transaction.state() != TransactionState::Committed {
+                            account.balance + account.delta
+                        } else {
+                            account.balance
+                        }
+                    })
+                    .sum();
+                total == INITIAL_BALANCES.iter().sum::<i64>()
+            }),
+            Property::<Self>::always("no overdraft", |_, state| {
+                state.accounts.iter().all(|account| account.balance >= 0)
+            }),
+            Property::<Self>::sometimes("transfer", |_, state| {
+                state
+                    .accounts
+                    .iter()
+                    .all(|account| account.transaction.state() == TransactionState::Committed)
+            }),
+        ]
+    }
+}
+
+fn main() {
+    println!("Model-check the bank transfer.");
+    BankTransferModel
+        .checker()
+        .spawn_dfs()
+        .report(&mut io::stdout())
+        .assert_properties();
+}
@@ -0,0 +1,110 @@
+#![deny(missing_docs, warnings, clippy::all, clippy::pedantic)]
+
+//! The [`main`] function reports on several protocols, or maintains the check cache.
+
+use homework::check_cache;
+use homework::group_commit_model::GroupCommitModel;
+use homework::linear_commit_model::LinearCommitModel;
+use homework::message_complexity::{self, MessageCount};
+use homework::paxos_commit_model::PaxosCommitModel;
+use homework::percolator_model::PercolatorModel;
+use homework::state_graph::StateGraph;
+use homework::three_phase_commit_model::ThreePhaseCommitModel;
+use homework::transaction_model::TransactionModel;
+
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::hash::Hash;
+use std::io;
+
+use stateright::Model;
+
+fn main() {
+    match env::args().nth(1).as_deref() {
+        Some("compare") => {
+            println!("Compare the state-space sizes of the commit protocols.");
+            report_state_space(&TransactionModel::new(3, false), "2PC");
+            report_state_space(&ThreePhaseCommitModel::new(3), "3PC");
+            report_state_space(&PaxosCommitModel::new(2, 1), "Paxos Commit");
+            report_state_space(&PercolatorModel::new(2), "Percolator");
+        }
+        Some("messages") => {
+            report_message_complexity(&TransactionModel::new(3, false), "2PC");
+            report_message_complexity(&TransactionModel::new(3, true), "2PC presumed commit");
+            report_message_complexity(
+                &TransactionModel::new(2, false).one_phase(true),
+                "2PC one-phase",
+            );
+            report_message_complexity(
+                &TransactionModel::new(3, false).last_agent(true),
+                "2PC last agent",
+            );
+            report_message_complexity(&LinearCommitModel::new(3), "Linear 2PC");
+            report_message_complexity(&ThreePhaseCommitModel::new(3), "3PC");
+        }
+        Some("forces") => report_log_forces(&GroupCommitModel::new(3)),
+        Some("clear-cache") => match fs::remove_file(check_cache::CACHE_FILE) {
+            Ok(()) => println!("Removed {}.", check_cache::CACHE_FILE),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                println!("No cached result.");
+            }
+            Err(error) => eprintln!("Failed to remove {}: {error}.", check_cache::CACHE_FILE),
+        },
+        _ => print_usage(),
+    }
+}
+
+/// Prints the usage.
+fn print_usage() {
+    println!("USAGE:");
+    println!("  cargo run --bin transaction-admin compare");
+    println!("  cargo run --bin transaction-admin messages");
+    println!("  cargo run --bin transaction-admin forces");
+    println!("  cargo run --bin transaction-admin clear-cache");
+}
+
+/// Reports the number of unique reachable states of the protocol model.
+///
+/// Unlike the model checker, the exploration does not stop when every property has a discovery.
+fn report_state_space<M: Model>(model: &M, protocol: &str)
+where
+    M::Action: Debug,
+    M::State: Hash,
+{
+    let graph = StateGraph::build(model, usize::MAX);
+    println!("{protocol}: {} unique states.", graph.states.len());
+}
+
+/// Reports the number of messages per committed transaction of the protocol.
+fn report_message_complexity<M: MessageCount>(model: &M, protocol: &str)
+where
+    M::State: Hash,
+{
+    let expected = model.expected_messages();
+    match message_complexity::analyze(model) {
+        Some(complexity) => println!(
+            "{protocol}: best {}, worst {} messages per committed transaction (textbook: {expected}){}.",
+            complexity.best,
+            complexity.worst,
+            if complexity.best == expected && complexity.worst == expected {
+                ""
+            } else {
+                ", MISMATCH"
+            }
+        ),
+        None => println!("{protocol}: no transaction is committed."),
+    }
+}
+
+/// Reports the number of log forces saved by group commit.
+fn report_log_forces(model: &GroupCommitModel) {
+    let num_transactions = model.num_transactions();
+    match model.min_log_forces() {
+        Some(forces) => println!(
+            "Group commit: {forces} log forces for {num_transactions} decisions instead of {num_transactions} (saving {}).",
+            num_transactions - forces
+        ),
+        None => println!("Group commit: no transaction decision is released."),
+    }
+}
@@ -0,0 +1,133 @@
+#![deny(missing_docs, warnings, clippy::all, clippy::pedantic)]
+
+//! The [`main`] function executes two-phase commit over local sockets, optionally after checking
+//! the model of the same configuration.
+
+use homework::cli::parse_node_ids;
+use homework::driver::{self, FaultPlan};
+use homework::trace::Trace;
+use homework::transaction_model::{Action, TransactionModel};
+
+use std::env;
+use std::io;
+use std::num::NonZeroUsize;
+use std::process::ExitCode;
+use std::thread::available_parallelism;
+
+use stateright::{Checker, Model};
+
+/// The number of nodes, including the coordinator.
+const NUM_NODES: usize = 3;
+
+/// The exit code of a model that violates a property, or of an execution that is not a trace of
+/// the model.
+const EXIT_VIOLATION: u8 = 1;
+
+/// The exit code of a driver that fails to run.
+const EXIT_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    let sub_command = args.next();
+    let mut num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+    let mut fault_plan = FaultPlan::default();
+    let mut presumed_commit = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--presumed-commit" => presumed_commit = true,
+            "--vote-no" => fault_plan.vote_no = parse_node_ids(args.next()).unwrap_or_default(),
+            "--drop-prepare" => {
+                fault_plan.drop_prepare = parse_node_ids(args.next()).unwrap_or_default();
+            }
+            "--threads" => {
+                num_cpus = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(num_cpus);
+            }
+            _ => (),
+        }
+    }
+
+    match sub_command.as_deref() {
+        Some("run") => match run_driver(&fault_plan) {
+            Some(_) => ExitCode::SUCCESS,
+            None => ExitCode::from(EXIT_ERROR),
+        },
+        Some("verify-and-run") => verify_and_run(&fault_plan, presumed_commit, num_cpus),
+        _ => {
+            print_usage();
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Prints the usage.
+fn print_usage() {
+    println!("USAGE:");
+    println!("  cargo run --bin transaction-driver run [OPTIONS]");
+    println!("  cargo run --bin transaction-driver verify-and-run [OPTIONS]");
+    println!();
+    println!("OPTIONS:");
+    println!("  --presumed-commit      Check the presumed-commit variant of 2PC before running");
+    println!("  --vote-no N1,N2,...    Let the participants vote against commit");
+    println!("  --drop-prepare N1,N2,...");
+    println!("                         Drop the first prepare request to the participants");
+    println!("  --threads N            Model-check with N threads (default: all the CPUs)");
+}
+
+/// Runs the driver, and prints the execution log.
+fn run_driver(fault_plan: &FaultPlan) -> Option<Vec<Action>> {
+    println!("Run the driver over local sockets.");
+    match driver::run(NUM_NODES, fault_plan) {
+        Ok(log) => {
+            for action in &log {
+                println!("- {action:?}");
+            }
+            Some(log)
+        }
+        Err(error) => {
+            eprintln!("Failed to run the driver: {error}.");
+            None
+        }
+    }
+}
+
+/// Model-checks two-phase commit, executes the driver with the same configuration and fault plan,
+/// and validates that the execution log is a trace of the model.
+fn verify_and_run(fault_plan: &FaultPlan, presumed_commit: bool, num_cpus: usize) -> ExitCode {
+    let model = || TransactionModel::new(NUM_NODES, presumed_commit);
+
+    println!("Model-check the transaction implementation.");
+    let checker = model()
+        .checker()
+        .threads(num_cpus)
+        .spawn_dfs()
+        .report(&mut io::stdout());
+    let counterexamples = checker
+        .discoveries()
+        .into_keys()
+        .filter(|name| checker.discovery_classification(name) == "counterexample")
+        .collect::<Vec<_>>();
+    if !counterexamples.is_empty() {
+        println!("The model violates {counterexamples:?}; the driver is not run.");
+        return ExitCode::from(EXIT_VIOLATION);
+    }
+
+    let Some(log) = run_driver(fault_plan) else {
+        return ExitCode::from(EXIT_ERROR);
+    };
+    match Trace::replay(&model(), log) {
+        Ok(trace) => {
+            println!(
+                "The execution is a trace of the model with {} steps.",
+                trace.steps.len() - 1
+            );
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("The execution is not a trace of the model: {error}.");
+            ExitCode::from(EXIT_VIOLATION)
+        }
+    }
+}
@@ -0,0 +1,1822 @@
+#![deny(missing_docs, warnings, clippy::all, clippy::pedantic)]
+
+//! The [`main`] function either checks the transaction model, or spawns a web server for the user
+//! to explore the state space.
+
+use homework::blacklist_model::BlacklistModel;
+use homework::byzantine_coordinator_model::ByzantineCoordinatorModel;
+use homework::check_cache;
+use homework::check_report::CheckReport;
+use homework::cli::{parse_duration, parse_node_ids, parse_number, parse_value};
+use homework::concurrent_transaction_model::ConcurrentTransactionModel;
+use homework::coordinator_policy;
+use homework::coverage::{self, Coverage, CoverageReport};
+use homework::dot_export;
+use homework::group_commit_model::GroupCommitModel;
+use homework::html_export;
+use homework::invariant::Invariant;
+use homework::linear_commit_model::LinearCommitModel;
+use homework::lock_model::{LockModel, PreemptionPolicy};
+use homework::model_config::{FaultType, ModelConfig};
+use homework::participant_behavior;
+use homework::paxos_commit_model::PaxosCommitModel;
+use homework::percolator_model::PercolatorModel;
+use homework::raft_coordinator_model::RaftCoordinatorModel;
+use homework::saga_model::SagaModel;
+use homework::scenario::Scenario;
+use homework::shrink;
+use homework::simulation;
+use homework::state_graph::StateGraph;
+use homework::three_phase_commit_model::ThreePhaseCommitModel;
+use homework::trace::Trace;
+use homework::transaction_model::TransactionModel;
+use homework::workload;
+
+use std::any::type_name;
+use std::env;
+use std::fmt::{Debug, Display};
+use std::fs;
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+use std::iter;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::process::{self, ExitCode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, available_parallelism};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::info;
+use stateright::{Checker, Expectation, Model, Property};
+
+/// Options shared by all the models.
+struct Options {
+    /// The address of the web server for exploring the state space, or the output file path.
+    argument: Option<String>,
+
+    /// The number of threads for model-checking.
+    num_cpus: usize,
+
+    /// Reuse the cached check result if the model and the code have not changed.
+    cached: bool,
+
+    /// The number of states after which the check stops, which it may overshoot, or the maximum
+    /// number of states to export.
+    max_states: Option<usize>,
+
+    /// The maximum resident memory of the check in MiB.
+    max_memory: Option<usize>,
+
+    /// The maximum wall-clock time of the check.
+    timeout: Option<Duration>,
+
+    /// The name of the order in which the model checker explores the state space.
+    search: Option<String>,
+
+    /// The maximum number of actions from the initial state, or `None` if the exploration is
+    /// unbounded.
+    max_depth: Option<usize>,
+
+    /// The name of the format of the check report.
+    format: Option<String>,
+
+    /// The directory to save the counterexamples to as trace files.
+    traces: Option<String>,
+
+    /// The seed of the random simulation, or `None` to seed it from the clock.
+    seed: Option<u64>,
+
+    /// The maximum number of steps of a simulated run.
+    steps: usize,
+
+    /// The number of simulated runs.
+    runs: usize,
+
+    /// Do not print the progress of the check.
+    quiet: bool,
+}
+
+impl Options {
+    /// Returns the default options, using all the available threads.
+    fn new() -> Options {
+        Options {
+            argument: None,
+            num_cpus: available_parallelism().map_or(1, NonZeroUsize::get),
+            cached: false,
+            max_states: None,
+            max_memory: None,
+            timeout: None,
+            search: None,
+            max_depth: None,
+            format: None,
+            traces: None,
+            seed: None,
+            steps: 100,
+            runs: 1000,
+            quiet: false,
+        }
+    }
+
+    /// Parses the option `arg`, taking its value from `args`, and returns `false` if it is not
+    /// an option shared by all the models, or why its value is invalid.
+    fn parse(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, String> {
+        match arg {
+            "--cached" => self.cached = true,
+            "--quiet" => self.quiet = true,
+            "--threads" => {
+                self.num_cpus = parse_number(args.next())?;
+                if self.num_cpus == 0 {
+                    return Err("at least 1 thread is needed".to_string());
+                }
+            }
+            "--max-states" => self.max_states = Some(parse_number(args.next())?),
+            "--max-memory" => self.max_memory = Some(parse_number(args.next())?),
+            "--timeout" => self.timeout = Some(parse_duration(args.next())?),
+            "--search" => self.search = Some(parse_value(args.next())?),
+            "--max-depth" => self.max_depth = Some(parse_number(args.next())?),
+            "--format" => self.format = Some(parse_value(args.next())?),
+            "--traces" => self.traces = Some(parse_value(args.next())?),
+            "--seed" => {
+                let seed = parse_value(args.next())?;
+                self.seed = Some(
+                    seed.trim()
+                        .parse()
+                        .map_err(|_| format!("{seed} is not a non-negative number"))?,
+                );
+            }
+            "--steps" => self.steps = parse_number(args.next())?,
+            "--runs" => self.runs = parse_number(args.next())?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// The exit code of a check in which a property fails.
+const EXIT_PROPERTY_FAILURE: u8 = 1;
+
+/// The exit code of invalid options, or of a file that cannot be loaded.
+const EXIT_ERROR: u8 = 2;
+
+/// The exit code of a check that stops at its memory limit or timeout before any property fails.
+const EXIT_INCOMPLETE: u8 = 3;
+
+/// The exit code of a check that is interrupted by Ctrl-C.
+const EXIT_INTERRUPTED: u8 = 130;
+
+/// [`Search`] is the order in which the model checker explores the state space.
+#[derive(Clone, Copy, Hash)]
+enum Search {
+    /// Breadth-first search, which discovers the shortest counterexamples.
+    Bfs,
+
+    /// Depth-first search, which needs less memory.
+    Dfs,
+}
+
+/// [`Format`] is the format of the check report.
+#[derive(Clone, Copy, Hash, PartialEq)]
+enum Format {
+    /// The human-readable report of the model checker.
+    Text,
+
+    /// The JSON document of a [`CheckReport`].
+    Json,
+
+    /// The `JUnit` XML document of a [`CheckReport`], with a test case for each property.
+    Junit,
+}
+
+impl Format {
+    /// Returns the [`Format`] with the name.
+    fn by_name(name: &str) -> Option<Format> {
+        match name {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "junit" => Some(Format::Junit),
+            _ => None,
+        }
+    }
+}
+
+impl Search {
+    /// Returns the [`Search`] with the name.
+    fn by_name(name: &str) -> Option<Search> {
+        match name {
+            "bfs" => Some(Search::Bfs),
+            "dfs" => Some(Search::Dfs),
+            _ => None,
+        }
+    }
+}
+
+/// Options selecting the variants of the models.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Default)]
+struct ModelOptions {
+    /// Use the presumed-commit variant of two-phase commit.
+    presumed_commit: bool,
+
+    /// Allow nodes to be gracefully shut down.
+    graceful_shutdown: bool,
+
+    /// Check the decision latency of a fault-free system.
+    decision_latency: bool,
+
+    /// Report the action variants and the combinations of transaction states that are reached.
+    coverage: bool,
+
+    /// The number of times a participant may defer its vote.
+    retry_budget: Option<usize>,
+
+    /// The number of yes-votes after which the coordinator commits.
+    quorum: Option<usize>,
+
+    /// The number of replicas of each participant.
+    replicas: Option<usize>,
+
+    /// The parents of the participant nodes in the commit tree.
+    parents: Option<Vec<usize>>,
+
+    /// The participant nodes that only read data.
+    read_only_participants: Vec<usize>,
+
+    /// A coordinator with a single participant delegates the decision to it.
+    one_phase: bool,
+
+    /// The coordinator delegates the decision to its last participant after preparing the others.
+    last_agent: bool,
+
+    /// Participants of a failed coordinator query their peers for the outcome.
+    cooperative_termination: bool,
+
+    /// A participant is elected to take over the transaction of a failed coordinator.
+    coordinator_election: bool,
+
+    /// Prepared participants of a failed coordinator may decide heuristically.
+    heuristics: bool,
+
+    /// A hot-standby replica of the coordinator takes over when the coordinator fails.
+    backup_coordinator: bool,
+
+    /// Messages are delivered asynchronously after they are sent.
+    asynchronous_network: bool,
+
+    /// The maximum number of delivered messages that the network duplicates.
+    max_duplicates: usize,
+
+    /// The maximum number of directed links of the asynchronous network that fail.
+    max_link_failures: usize,
+
+    /// Nodes may checkpoint the transaction, and truncate their logs.
+    checkpointing: bool,
+
+    /// The participant node that sends arbitrary protocol messages.
+    byzantine_participant: Option<usize>,
+
+    /// Timeouts fire only by explicit timer expirations of waiting nodes.
+    timers: bool,
+
+    /// Steps that do not change the state are pruned for checking liveness properties.
+    fairness: bool,
+
+    /// The participant node that may die permanently along with the coordinator.
+    dead_participant: Option<usize>,
+
+    /// The number of nodes, the coordinator, the crash budget, the faults and the properties,
+    /// set by the flags over the configuration file.
+    config: ModelConfig,
+
+    /// The path of the configuration file.
+    config_path: Option<String>,
+
+    /// The path of the fault-injection scenario file.
+    scenario: Option<String>,
+
+    /// The path of the file of extra invariants.
+    invariants: Option<String>,
+
+    /// The name of the workload of the transaction.
+    workload: Option<String>,
+
+    /// The behaviors of the participant nodes, each as `N=NAME`.
+    participant_behaviors: Vec<String>,
+
+    /// The name of the decision policy of the coordinator.
+    coordinator_policy: Option<String>,
+
+    /// The number of ticks of the simulated clock after which a timeout fires.
+    timeout_ticks: Option<usize>,
+
+    /// The maximum number of ticks of the simulated clock.
+    max_ticks: usize,
+
+    /// Participants gossip the decisions of a Byzantine coordinator.
+    gossip: bool,
+
+    /// The number of failed prepare attempts that blacklists a participant.
+    blacklist_threshold: usize,
+
+    /// Use wait-die instead of wound-wait for locks.
+    wait_die: bool,
+
+    /// Add a read-only transaction to the lock model.
+    read_only: bool,
+
+    /// The number of concurrent distributed transactions.
+    transactions: usize,
+}
+
+impl ModelOptions {
+    /// Parses the option `arg`, taking its value from `args`, and returns `false` if it is not
+    /// an option of the models, or why its value is invalid.
+    fn parse(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool, String> {
+        match arg {
+            "--graceful-shutdown" => self.graceful_shutdown = true,
+            "--presumed-commit" => self.presumed_commit = true,
+            "--replacement" => self.config.faults.push(FaultType::Replacement),
+            "--decision-latency" => self.decision_latency = true,
+            "--coverage" => self.coverage = true,
+            "--one-phase" => self.one_phase = true,
+            "--last-agent" => self.last_agent = true,
+            "--cooperative-termination" => self.cooperative_termination = true,
+            "--coordinator-election" => self.coordinator_election = true,
+            "--heuristics" => self.heuristics = true,
+            "--backup-coordinator" => self.backup_coordinator = true,
+            "--asynchronous-network" => self.asynchronous_network = true,
+            "--checkpointing" => self.checkpointing = true,
+            "--torn-writes" => self.config.faults.push(FaultType::TornWrites),
+            "--timers" => self.timers = true,
+            "--coordinator-amnesia" => self.config.faults.push(FaultType::CoordinatorAmnesia),
+            "--log-write-failures" => self.config.faults.push(FaultType::LogWriteFailures),
+            "--fail-stop" => self.config.faults.push(FaultType::FailStop),
+            "--fairness" => self.fairness = true,
+            "--workload" => self.workload = Some(parse_value(args.next())?),
+            "--coordinator-policy" => self.coordinator_policy = Some(parse_value(args.next())?),
+            "--participant-behavior" => self.participant_behaviors.push(parse_value(args.next())?),
+            "--scenario" => self.scenario = Some(parse_value(args.next())?),
+            "--invariants" => self.invariants = Some(parse_value(args.next())?),
+            "--config" => self.config_path = Some(parse_value(args.next())?),
+            "--property" => self.config.properties.push(parse_value(args.next())?),
+            "--retry-budget" => self.retry_budget = Some(parse_number(args.next())?),
+            "--replicas" => self.replicas = Some(parse_number(args.next())?),
+            "--duplicates" => self.max_duplicates = parse_number(args.next())?,
+            "--link-failures" => self.max_link_failures = parse_number(args.next())?,
+            "--byzantine-participant" => {
+                self.byzantine_participant = Some(parse_number(args.next())?);
+            }
+            "--dead-participant" => self.dead_participant = Some(parse_number(args.next())?),
+            "--crashes" => self.config.crash_budget = Some(parse_number(args.next())?),
+            "--quorum" => self.quorum = Some(parse_number(args.next())?),
+            "--nodes" => self.config.nodes = Some(parse_number(args.next())?),
+            "--parents" => self.parents = Some(parse_node_ids(args.next())?),
+            "--coordinator" => self.config.coordinator = Some(parse_number(args.next())?),
+            "--read-only-participants" => {
+                self.read_only_participants = parse_node_ids(args.next())?;
+            }
+            "--timeout-ticks" => self.timeout_ticks = Some(parse_number(args.next())?),
+            "--max-ticks" => self.max_ticks = parse_number(args.next())?,
+            "--blacklist-threshold" => self.blacklist_threshold = parse_number(args.next())?,
+            "--transactions" => self.transactions = parse_number(args.next())?,
+            "--gossip" => self.gossip = true,
+            "--wait-die" => self.wait_die = true,
+            "--read-only" => self.read_only = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+fn main() -> ExitCode {
+    env_logger::init();
+    let mut args = env::args().skip(1);
+    let sub_command = args.next();
+    let mut model_name = None;
+    let mut options = Options::new();
+    let mut model_options = ModelOptions {
+        max_ticks: 3,
+        blacklist_threshold: 2,
+        transactions: 2,
+        ..ModelOptions::default()
+    };
+    // The flags after `--versus` configure the second model of `diff` on top of the first.
+    let mut first_model_options = None;
+    while let Some(arg) = args.next() {
+        let parsed = model_options
+            .parse(&arg, &mut args)
+            .and_then(|parsed| Ok(parsed || options.parse(&arg, &mut args)?));
+        match parsed {
+            Ok(true) => {}
+            Ok(false) => match arg.as_str() {
+                "--versus" => first_model_options = Some(model_options.clone()),
+                "2pc" | "3pc" | "paxos-commit" | "raft" | "linear" | "saga" | "byzantine"
+                | "blacklist" | "group-commit" | "locks" | "concurrent" | "percolator" => {
+                    model_name = Some(arg);
+                }
+                _ => options.argument = Some(arg),
+            },
+            Err(error) => {
+                println!("Invalid {arg}: {error}.");
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+    }
+
+    if sub_command.as_deref() == Some("diff") {
+        if model_name.as_deref().is_some_and(|name| name != "2pc") {
+            println!("Only configurations of the 2pc model can be compared.");
+            return ExitCode::from(EXIT_ERROR);
+        }
+        let Some(first_model_options) = first_model_options else {
+            println!("The second configuration is set by the flags after --versus.");
+            return ExitCode::from(EXIT_ERROR);
+        };
+        return diff_models(&first_model_options, &model_options, &options);
+    }
+
+    run_model(
+        model_name.as_deref(),
+        sub_command.as_deref(),
+        options,
+        model_options,
+    )
+}
+
+/// Loads the configuration file, if any, under the values set by the flags.
+fn load_config(model_options: ModelOptions) -> Option<ModelOptions> {
+    let Some(path) = model_options.config_path.as_deref() else {
+        return Some(model_options);
+    };
+    let config = load_file(path, ModelConfig::load)?;
+    Some(ModelOptions {
+        config: config.merge(model_options.config),
+        ..model_options
+    })
+}
+
+/// Runs the sub-command on the model selected by its name.
+fn run_model(
+    model_name: Option<&str>,
+    sub_command: Option<&str>,
+    options: Options,
+    model_options: ModelOptions,
+) -> ExitCode {
+    let Some(model_options) = load_config(model_options) else {
+        return ExitCode::from(EXIT_ERROR);
+    };
+    if !model_options.config.properties.is_empty() && model_name.is_some_and(|name| name != "2pc") {
+        println!("Properties can only be selected for the 2pc model.");
+        return ExitCode::from(EXIT_ERROR);
+    }
+    if model_options.coverage && model_name.is_some_and(|name| name != "2pc") {
+        println!("Coverage can only be reported for the 2pc model.");
+        return ExitCode::from(EXIT_ERROR);
+    }
+    if options.max_depth.is_some() && model_name.is_some_and(|name| name != "2pc") {
+        println!("The depth can only be bounded for the 2pc model.");
+        return ExitCode::from(EXIT_ERROR);
+    }
+    match model_name {
+        Some("locks") => {
+            let policy = if model_options.wait_die {
+                PreemptionPolicy::WaitDie
+            } else {
+                PreemptionPolicy::WoundWait
+            };
+            run(
+                LockModel::new(3, 2, policy)
+                    .read_only_transactions(usize::from(model_options.read_only)),
+                sub_command,
+                options,
+            )
+        }
+        Some("paxos-commit") => run(PaxosCommitModel::new(2, 1), sub_command, options),
+        Some("raft") => run(RaftCoordinatorModel::new(2, 3), sub_command, options),
+        Some("linear") => run(LinearCommitModel::new(3), sub_command, options),
+        Some("saga") => run(SagaModel::new(3), sub_command, options),
+        Some("percolator") => run(PercolatorModel::new(3), sub_command, options),
+        Some("byzantine") => run(
+            ByzantineCoordinatorModel::new(3, model_options.gossip),
+            sub_command,
+            options,
+        ),
+        Some("blacklist") => run(
+            BlacklistModel::new(2, 3, model_options.blacklist_threshold),
+            sub_command,
+            options,
+        ),
+        Some("3pc") => run(ThreePhaseCommitModel::new(3), sub_command, options),
+        Some("group-commit") => run(GroupCommitModel::new(3), sub_command, options),
+        Some("concurrent") => run(
+            ConcurrentTransactionModel::with_transactions(model_options.transactions, 3),
+            sub_command,
+            options,
+        ),
+        _ if sub_command == Some("check-all") => check_all(&model_options, &options),
+        _ if sub_command == Some("bench") => {
+            bench(&model_options, &options);
+            ExitCode::SUCCESS
+        }
+        _ => {
+            let Some(mut model) = transaction_model(&model_options) else {
+                return ExitCode::from(EXIT_ERROR);
+            };
+            if let Some(max_depth) = options.max_depth {
+                model = model.max_depth(max_depth);
+            }
+            if model_options.decision_latency {
+                println!(
+                    "Decision latency bound: {} transitions.",
+                    model.decision_latency_bound()
+                );
+            }
+            if sub_command == Some("properties") {
+                print_properties(&model, TransactionModel::property_description);
+                return ExitCode::SUCCESS;
+            }
+            if sub_command == Some("export-dot") {
+                // The states are labeled by the transaction states of the nodes.
+                export_dot(&model, options, |state| {
+                    let node_states: Vec<String> = model
+                        .node_states(state)
+                        .iter()
+                        .enumerate()
+                        .map(|(node_id, node_state)| format!("{node_id}: {node_state:?}"))
+                        .collect();
+                    node_states.join("\n")
+                });
+                return ExitCode::SUCCESS;
+            }
+            let coverage = (model_options.coverage && sub_command == Some("check"))
+                .then(|| coverage::analyze(&model));
+            let exit_code = run(model, sub_command, options);
+            if let Some(coverage) = coverage {
+                print_coverage(&coverage);
+            }
+            exit_code
+        }
+    }
+}
+
+/// Times the exhaustive checks of the 2pc model with 2 up to the configured number of nodes, and
+/// with the thread counts in powers of two up to the number of threads of the options.
+fn bench(model_options: &ModelOptions, options: &Options) {
+    let Some(max_nodes) = num_nodes(model_options) else {
+        return;
+    };
+    // A commit tree has a fixed number of nodes.
+    let min_nodes = if model_options.parents.is_some() {
+        max_nodes
+    } else {
+        2
+    };
+    let thread_counts: Vec<usize> = iter::successors(Some(1), |threads| Some(threads * 2))
+        .take_while(|threads| *threads < options.num_cpus)
+        .chain(iter::once(options.num_cpus))
+        .collect();
+    println!("Benchmark the exhaustive checks of the 2pc model.");
+    println!(
+        "{:>5} {:>7} {:>12} {:>12} {:>10} {:>12}",
+        "nodes", "threads", "states", "unique", "msec", "states/sec"
+    );
+    for num_nodes in min_nodes..=max_nodes {
+        let model_options = ModelOptions {
+            config: ModelConfig {
+                nodes: Some(num_nodes),
+                ..model_options.config.clone()
+            },
+            ..model_options.clone()
+        };
+        for &threads in &thread_counts {
+            let Some(mut model) = transaction_model(&model_options) else {
+                return;
+            };
+            if let Some(max_depth) = options.max_depth {
+                model = model.max_depth(max_depth);
+            }
+            let start = Instant::now();
+            let checker = model.checker().threads(threads).spawn_dfs().join();
+            let msec = start.elapsed().as_millis();
+            let states = checker.state_count();
+            println!(
+                "{num_nodes:>5} {threads:>7} {states:>12} {:>12} {msec:>10} {:>12}",
+                checker.unique_state_count(),
+                u128::try_from(states).unwrap_or(u128::MAX) * 1000 / msec.max(1)
+            );
+        }
+    }
+}
+
+/// [`CheckSummary`] is the result of checking the model of a scenario or configuration file.
+struct CheckSummary {
+    /// The number of states that were checked.
+    states: usize,
+
+    /// The number of unique states that were checked.
+    unique: usize,
+
+    /// The time of the check in milliseconds.
+    msec: u128,
+
+    /// The names of the checked properties.
+    properties: Vec<&'static str>,
+
+    /// The names of the failed properties.
+    failed: Vec<&'static str>,
+}
+
+/// Checks the 2pc model of each scenario and configuration file in the directory, and prints a
+/// summary table of the checks.
+fn check_all(model_options: &ModelOptions, options: &Options) -> ExitCode {
+    let dir = options.argument.as_deref().unwrap_or("scenarios");
+    let search = options.search.as_deref().unwrap_or("dfs");
+    let Some(search) = Search::by_name(search) else {
+        println!("Unknown search: {search}.");
+        return ExitCode::from(EXIT_ERROR);
+    };
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "json" || extension == "toml")
+            })
+            .collect(),
+        Err(error) => {
+            println!("Failed to read {dir}: {error}.");
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    if paths.is_empty() {
+        println!("No scenario or configuration file in {dir}.");
+        return ExitCode::from(EXIT_ERROR);
+    }
+    paths.sort();
+
+    println!("Model-check the scenarios and configurations in {dir}.");
+    let mut summaries = Vec::new();
+    for path in paths {
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let mut file_options = model_options.clone();
+        let file = Some(path.to_string_lossy().into_owned());
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            file_options.scenario = file;
+        } else {
+            file_options.config_path = file;
+        }
+        println!("Check {name}.");
+        let summary = load_config(file_options)
+            .and_then(|file_options| transaction_model(&file_options))
+            .map(|model| check_summary(model, options, search));
+        summaries.push((name, summary));
+    }
+
+    let width = summaries
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    println!();
+    println!(
+        "{:width$} {:>12} {:>12} {:>10}  result",
+        "file", "states", "unique", "msec"
+    );
+    for (name, summary) in &summaries {
+        match summary {
+            Some(summary) if summary.failed.is_empty() => println!(
+                "{name:width$} {:>12} {:>12} {:>10}  ok",
+                summary.states, summary.unique, summary.msec
+            ),
+            Some(summary) => println!(
+                "{name:width$} {:>12} {:>12} {:>10}  failed: {}",
+                summary.states,
+                summary.unique,
+                summary.msec,
+                summary.failed.join(", ")
+            ),
+            None => println!("{name:width$} {:>12} {:>12} {:>10}  not loaded", "", "", ""),
+        }
+    }
+    let summaries: Vec<_> = summaries.into_iter().map(|(_, summary)| summary).collect();
+    if summaries
+        .iter()
+        .flatten()
+        .any(|summary| !summary.failed.is_empty())
+    {
+        ExitCode::from(EXIT_PROPERTY_FAILURE)
+    } else if summaries.iter().any(Option::is_none) {
+        ExitCode::from(EXIT_ERROR)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Checks the model in the search order, bounded by the options, and summarizes the result.
+fn check_summary(mut model: TransactionModel, options: &Options, search: Search) -> CheckSummary {
+    if let Some(max_depth) = options.max_depth {
+        model = model.max_depth(max_depth);
+    }
+    let mut builder = model.checker().threads(options.num_cpus);
+    if let Some(max_states) = options.max_states {
+        builder = builder.target_state_count(max_states);
+    }
+    let start = Instant::now();
+    match search {
+        Search::Bfs => summarize(&builder.spawn_bfs().join(), start),
+        Search::Dfs => summarize(&builder.spawn_dfs().join(), start),
+    }
+}
+
+/// Summarizes the result of the checker that was started at `start`.
+fn summarize<M: Model>(checker: &impl Checker<M>, start: Instant) -> CheckSummary {
+    CheckSummary {
+        states: checker.state_count(),
+        unique: checker.unique_state_count(),
+        msec: start.elapsed().as_millis(),
+        properties: checker
+            .model()
+            .properties()
+            .iter()
+            .map(|property| property.name)
+            .collect(),
+        failed: failed_properties(checker),
+    }
+}
+
+/// Checks the 2pc model of the two configurations, and reports the properties whose results
+/// differ along with the state counts of both.
+fn diff_models(first: &ModelOptions, second: &ModelOptions, options: &Options) -> ExitCode {
+    let search = options.search.as_deref().unwrap_or("dfs");
+    let Some(search) = Search::by_name(search) else {
+        println!("Unknown search: {search}.");
+        return ExitCode::from(EXIT_ERROR);
+    };
+    let mut summaries = Vec::new();
+    for (name, model_options) in [("first", first), ("second", second)] {
+        println!("Check the {name} configuration.");
+        let Some(model) =
+            load_config(model_options.clone()).and_then(|options| transaction_model(&options))
+        else {
+            return ExitCode::from(EXIT_ERROR);
+        };
+        summaries.push(check_summary(model, options, search));
+    }
+    let (first, second) = (&summaries[0], &summaries[1]);
+
+    println!();
+    println!("{:8} {:>12} {:>12}", "", "first", "second");
+    println!("{:8} {:>12} {:>12}", "states", first.states, second.states);
+    println!("{:8} {:>12} {:>12}", "unique", first.unique, second.unique);
+    println!("{:8} {:>12} {:>12}", "msec", first.msec, second.msec);
+    let result = |summary: &CheckSummary, name: &str| {
+        if !summary.properties.contains(&name) {
+            "absent"
+        } else if summary.failed.contains(&name) {
+            "failed"
+        } else {
+            "ok"
+        }
+    };
+    let mut names: Vec<&str> = first
+        .properties
+        .iter()
+        .chain(&second.properties)
+        .copied()
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    let differences: Vec<_> = names
+        .into_iter()
+        .map(|name| (name, result(first, name), result(second, name)))
+        .filter(|(_, first, second)| first != second)
+        .collect();
+    if differences.is_empty() {
+        println!("No property result differs.");
+    } else {
+        let width = differences
+            .iter()
+            .map(|(name, _, _)| name.len())
+            .max()
+            .unwrap_or_default();
+        println!("Properties with different results:");
+        for (name, first, second) in differences {
+            println!("  {name:width$}  {first:>6} -> {second}");
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Prints the action variants and the combinations of transaction states that are reached.
+fn print_coverage(coverage: &CoverageReport) {
+    println!("Coverage of {} states:", coverage.states);
+    println!("Actions:");
+    for action in &coverage.actions {
+        if action.steps == 0 {
+            println!("  {}: never taken", action.variant);
+        } else {
+            println!(
+                "  {}: {} steps, {} changing the state",
+                action.variant, action.steps, action.state_changes
+            );
+        }
+    }
+    println!("Transaction states of the nodes:");
+    for (states, count) in &coverage.node_states {
+        println!("  {states:?}: {count} states");
+    }
+    let unreached = coverage.unreached_states();
+    if !unreached.is_empty() {
+        println!("Never reached by any node: {unreached:?}");
+    }
+}
+
+/// Prints the name, the expectation and the description, if any, of each property of the model.
+fn print_properties<M: Model>(model: &M, description: fn(&str) -> Option<&'static str>) {
+    let properties = model.properties();
+    let width = properties
+        .iter()
+        .map(|property| property.name.len())
+        .max()
+        .unwrap_or_default();
+    for property in properties {
+        let expectation = match property.expectation {
+            Expectation::Always => "always",
+            Expectation::Eventually => "eventually",
+            Expectation::Sometimes => "sometimes",
+        };
+        let name = property.name;
+        match description(name) {
+            Some(description) => println!("{name:width$}  {expectation:10}  {description}"),
+            None => println!("{name:width$}  {expectation}"),
+        }
+    }
+}
+
+/// Returns the number of nodes of the [`TransactionModel`] configured by the options.
+fn num_nodes(model_options: &ModelOptions) -> Option<usize> {
+    match (&model_options.parents, model_options.config.nodes) {
+        (Some(parents), Some(num_nodes)) if parents.len() + 1 != num_nodes => {
+            println!("The commit tree has {} nodes.", parents.len() + 1);
+            None
+        }
+        (Some(parents), _) => Some(parents.len() + 1),
+        (None, Some(num_nodes)) if num_nodes < 2 => {
+            println!("At least 2 nodes are needed.");
+            None
+        }
+        (None, num_nodes) => Some(num_nodes.unwrap_or(3)),
+    }
+}
+
+/// Builds the [`TransactionModel`] configured by the options.
+#[allow(clippy::too_many_lines)]
+fn transaction_model(model_options: &ModelOptions) -> Option<TransactionModel> {
+    let num_nodes = num_nodes(model_options)?;
+    let config = &model_options.config;
+    let mut builder = TransactionModel::builder()
+        .presumed_commit(model_options.presumed_commit)
+        .one_phase(model_options.one_phase)
+        .last_agent(model_options.last_agent)
+        .read_only_participants(model_options.read_only_participants.clone());
+    builder = match &model_options.parents {
+        Some(parents) => builder.commit_tree(
+            iter::once(None)
+                .chain(parents.iter().copied().map(Some))
+                .collect(),
+        ),
+        None => builder.nodes(num_nodes),
+    };
+    if let Some(coordinator) = config.coordinator {
+        builder = builder.coordinator(coordinator);
+    }
+    if let Some(crash_budget) = config.crash_budget {
+        builder = builder.crash_budget(crash_budget);
+    }
+    for fault in &config.faults {
+        builder = builder.fault(*fault, true);
+    }
+    let mut model = match builder.build() {
+        Ok(model) => model,
+        Err(error) => {
+            println!("Invalid model: {error}.");
+            return None;
+        }
+    };
+    model = model
+        .graceful_shutdown(model_options.graceful_shutdown)
+        .decision_latency(model_options.decision_latency)
+        .cooperative_termination(model_options.cooperative_termination)
+        .coordinator_election(model_options.coordinator_election)
+        .heuristics(model_options.heuristics)
+        .backup_coordinator(model_options.backup_coordinator)
+        .asynchronous_network(model_options.asynchronous_network)
+        .message_duplication(model_options.max_duplicates)
+        .link_failures(model_options.max_link_failures)
+        .checkpointing(model_options.checkpointing)
+        .timers(model_options.timers)
+        .fairness(model_options.fairness);
+    if let Some(workload) = model_options.workload.as_deref() {
+        let Some(workload) = workload::by_name(workload) else {
+            println!("Unknown workload: {workload}.");
+            return None;
+        };
+        model = model.workload(workload);
+    }
+    if let Some(policy) = model_options.coordinator_policy.as_deref() {
+        let Some(policy) = coordinator_policy::by_name(policy) else {
+            println!("Unknown coordinator policy: {policy}.");
+            return None;
+        };
+        model = model.coordinator_policy(policy);
+    }
+    for behavior in &model_options.participant_behaviors {
+        let Some((node_id, name)) = behavior
+            .split_once('=')
+            .and_then(|(node_id, name)| Some((node_id.parse::<usize>().ok()?, name)))
+        else {
+            println!("Invalid participant behavior: {behavior}; expected N=NAME.");
+            return None;
+        };
+        let Some(behavior) = participant_behavior::by_name(name) else {
+            println!("Unknown participant behavior: {name}.");
+            return None;
+        };
+        model = model.participant_behavior(node_id, behavior);
+    }
+    if let Some(byzantine) = model_options.byzantine_participant {
+        model = model.byzantine_participant(byzantine);
+    }
+    if let Some(path) = model_options.scenario.as_deref() {
+        let scenario = load_file(path, |json| Scenario::load(json, num_nodes))?;
+        println!("Scenario: {}.", scenario.name);
+        model = model.scenario(&scenario);
+    }
+    if let Some(path) = model_options.invariants.as_deref() {
+        let invariants = load_file(path, |json| Invariant::load(json, num_nodes))?;
+        model = model.invariants(invariants);
+    }
+    if let Some(dead) = model_options.dead_participant {
+        model = model.dead_participant(dead);
+    }
+    if let Some(replicas) = model_options.replicas {
+        model = model.replicated_participants(replicas);
+    }
+    if let Some(quorum) = model_options.quorum {
+        model = model.quorum(quorum);
+    }
+    if let Some(retry_budget) = model_options.retry_budget {
+        model = model.flow_control(retry_budget);
+    }
+    if let Some(timeout_ticks) = model_options.timeout_ticks {
+        model = model.simulated_clock(timeout_ticks, model_options.max_ticks);
+    }
+    // A selected property that the model does not have is left out silently.
+    if !config.properties.is_empty() {
+        model = model.select_properties(config.properties.clone());
+    }
+    let properties = model.properties();
+    if let Some(name) = config
+        .properties
+        .iter()
+        .find(|name| properties.iter().all(|property| property.name != *name))
+    {
+        println!("Unknown property: {name}.");
+        return None;
+    }
+    Some(model)
+}
+
+/// Loads the file, or prints why it cannot be loaded.
+fn load_file<T, E: Display>(path: &str, load: impl FnOnce(&str) -> Result<T, E>) -> Option<T> {
+    match fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| load(&json).map_err(|error| error.to_string()))
+    {
+        Ok(value) => Some(value),
+        Err(error) => {
+            println!("Failed to load {path}: {error}.");
+            None
+        }
+    }
+}
+
+/// Runs the sub-command on the model.
+fn run<M>(model: M, sub_command: Option<&str>, options: Options) -> ExitCode
+where
+    M: 'static + Hash + Model + Send + Sync,
+    M::Action: Clone + Debug + PartialEq + Send + Sync,
+    M::State: Debug + Hash + PartialEq + Send + Sync,
+{
+    match sub_command {
+        Some("check") => return run_check(model, &options),
+        Some("explore") => {
+            let address = options
+                .argument
+                .unwrap_or_else(|| "localhost:3000".to_string());
+            println!("Explore the state space for the transaction model on {address}.");
+            model.checker().threads(options.num_cpus).serve(address);
+        }
+        Some("export-html") => {
+            let path = options
+                .argument
+                .unwrap_or_else(|| "state-space.html".to_string());
+            let graph = StateGraph::build(&model, options.max_states.unwrap_or(10_000));
+            println!(
+                "Export {} states{} to {path}.",
+                graph.states.len(),
+                if graph.truncated { " (truncated)" } else { "" }
+            );
+            if let Err(error) = fs::write(&path, html_export::render(&model, &graph)) {
+                eprintln!("Failed to write {path}: {error}.");
+            }
+        }
+        Some("export-dot") => export_dot(&model, options, |state| format!("{state:?}")),
+        Some("export-trace") => export_trace(model, options),
+        Some("validate-trace") => validate_trace(&model, options),
+        Some("replay") => replay_trace(&model, options),
+        Some("simulate") => simulate(&model, &options),
+        Some("properties") => print_properties(&model, |_| None),
+        Some("step") => step(&model),
+        _ => {
+            print_usage();
+            return ExitCode::from(EXIT_ERROR);
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// Model-checks the model as configured by the options, and returns [`EXIT_PROPERTY_FAILURE`] if
+/// a property fails.
+fn run_check<M>(model: M, options: &Options) -> ExitCode
+where
+    M: 'static + Hash + Model + Send + Sync,
+    M::Action: Clone + Debug + PartialEq + Send + Sync,
+    M::State: Debug + Hash + Send + Sync,
+{
+    let search = options.search.as_deref().unwrap_or("dfs");
+    let Some(search) = Search::by_name(search) else {
+        println!("Unknown search: {search}.");
+        return ExitCode::from(EXIT_ERROR);
+    };
+    let format = options.format.as_deref().unwrap_or("text");
+    let Some(format) = Format::by_name(format) else {
+        println!("Unknown format: {format}.");
+        return ExitCode::from(EXIT_ERROR);
+    };
+    if format == Format::Text {
+        println!("Model-check the transaction implementation.");
+    }
+    // The trace files and the results of bounded checks are not cached.
+    let failed = if options.cached
+        && options.traces.is_none()
+        && options.max_states.is_none()
+        && options.max_memory.is_none()
+        && options.timeout.is_none()
+    {
+        check_cached(model, options, search, format)
+    } else {
+        check(model, options, search, format, &mut io::stdout())
+    };
+    if failed {
+        ExitCode::from(EXIT_PROPERTY_FAILURE)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Model-checks the model in the search order, reports the result in the format, and returns
+/// `true` if a property fails.
+fn check<M>(model: M, options: &Options, search: Search, format: Format, w: &mut impl Write) -> bool
+where
+    M: 'static + Model + Send + Sync,
+    M::Action: Clone + Debug + PartialEq + Send + Sync,
+    M::State: Debug + Hash + Send + Sync,
+{
+    info!(
+        "Checking {} {}, threads={}.",
+        type_name::<M>(),
+        match search {
+            Search::Bfs => "breadth-first",
+            Search::Dfs => "depth-first",
+        },
+        options.num_cpus
+    );
+    handle_interrupt();
+    let mut builder = model.checker().threads(options.num_cpus);
+    if let Some(max_states) = options.max_states {
+        builder = builder.target_state_count(max_states);
+    }
+    match search {
+        Search::Bfs => report_check(builder.spawn_bfs(), options, format, w),
+        Search::Dfs => report_check(builder.spawn_dfs(), options, format, w),
+    }
+}
+
+/// Reports the result of the checker in the format, saves the counterexamples if requested, and
+/// returns `true` if a property fails.
+fn report_check<M>(
+    checker: impl Checker<M>,
+    options: &Options,
+    format: Format,
+    w: &mut impl Write,
+) -> bool
+where
+    M: Model,
+    M::Action: Clone + Debug + PartialEq,
+    M::State: Debug,
+{
+    let start = Instant::now();
+    // The checker of a stopped check is still running; what it has found so far is reported
+    // before the process exits.
+    let (checker, stop) = join_with_progress(checker, options);
+    let outcome = match stop {
+        None => "Done",
+        Some(Stop::Interrupted) => "Interrupted",
+        Some(Stop::MemoryLimit) => "Stopped at the memory limit",
+        Some(Stop::Timeout) => "Stopped at the timeout",
+    };
+    let bounded = options
+        .max_states
+        .is_some_and(|max_states| checker.state_count() >= max_states);
+    info!(
+        "{outcome}: {} states, {} unique, in {} msec.",
+        checker.state_count(),
+        checker.unique_state_count(),
+        start.elapsed().as_millis()
+    );
+    for (name, path) in checker.discoveries() {
+        info!(
+            "Discovered \"{name}\" {} of {} actions.",
+            checker.discovery_classification(name),
+            path.into_actions().len()
+        );
+    }
+    if format != Format::Text {
+        if let Some(dir) = &options.traces {
+            save_traces(&checker, Path::new(dir), &mut io::sink());
+        }
+        let mut report = CheckReport::from_checker(&checker);
+        report.max_depth = options.max_depth;
+        report.interrupted = stop.is_some() || bounded;
+        if format == Format::Json {
+            let _ = writeln!(w, "{}", report.to_json());
+        } else {
+            let _ = write!(w, "{}", report.to_junit());
+        }
+        let failed = has_failure(&checker);
+        exit_if_stopped(stop, failed, w);
+        return failed;
+    }
+    let _ = writeln!(
+        w,
+        "{outcome}. states={}, unique={}, sec={}",
+        checker.state_count(),
+        checker.unique_state_count(),
+        start.elapsed().as_secs()
+    );
+    let mut discoveries: Vec<_> = checker.discoveries().into_iter().collect();
+    discoveries.sort_by_key(|(name, _)| *name);
+    for (name, path) in discoveries {
+        let classification = checker.discovery_classification(name);
+        let _ = write!(w, "Discovered \"{name}\" {classification} {path}");
+    }
+    report_shrunk(&checker, w);
+    if let Some(dir) = &options.traces {
+        save_traces(&checker, Path::new(dir), w);
+    }
+    if let Some(max_depth) = options.max_depth {
+        let _ = writeln!(
+            w,
+            "Bounded verification: only the executions of at most {max_depth} actions were \
+             explored, and the liveness properties were not checked."
+        );
+    }
+    if let Some(max_states) = options.max_states.filter(|_| bounded) {
+        // The checker stops only after its threads finish their batches of states.
+        let _ = writeln!(
+            w,
+            "Bounded verification: the check stopped after {} states at the target of \
+             {max_states}, and the properties without a discovery may still fail.",
+            checker.state_count()
+        );
+    }
+    if stop.is_some() {
+        let deepest = checker
+            .discoveries()
+            .into_values()
+            .map(|path| path.into_actions().len())
+            .max();
+        if let Some(deepest) = deepest {
+            let _ = writeln!(w, "Deepest discovered path: {deepest} actions.");
+        }
+        let _ = writeln!(
+            w,
+            "Partial verification: the state space was not fully explored, and the properties \
+             without a discovery may still fail."
+        );
+    }
+    let failed = has_failure(&checker);
+    exit_if_stopped(stop, failed, w);
+    failed
+}
+
+/// Returns `true` if an always- or eventually-property has a counterexample, or a
+/// sometimes-property has no example.
+fn has_failure<M: Model>(checker: &impl Checker<M>) -> bool {
+    !failed_properties(checker).is_empty()
+}
+
+/// Returns the names of the always- and eventually-properties with a counterexample, and of the
+/// sometimes-properties without an example.
+fn failed_properties<M: Model>(checker: &impl Checker<M>) -> Vec<&'static str> {
+    checker
+        .model()
+        .properties()
+        .iter()
+        .filter(|property| {
+            let sometimes = matches!(property.expectation, Expectation::Sometimes);
+            checker.discovery(property.name).is_some() != sometimes
+        })
+        .map(|property| property.name)
+        .collect()
+}
+
+/// [`Stop`] is the reason why a check stops before the checker has finished.
+#[derive(Clone, Copy)]
+enum Stop {
+    /// Ctrl-C was pressed.
+    Interrupted,
+
+    /// The check exceeded its memory limit.
+    MemoryLimit,
+
+    /// The check ran out of its time.
+    Timeout,
+}
+
+/// Set once the check is interrupted by Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Lets Ctrl-C interrupt the check, so that the partial result is reported; a second Ctrl-C
+/// exits at once.
+fn handle_interrupt() {
+    let _ = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            process::exit(EXIT_INTERRUPTED.into());
+        }
+    });
+}
+
+/// Exits without waiting for the checker if the check was stopped.
+fn exit_if_stopped(stop: Option<Stop>, failed: bool, w: &mut impl Write) {
+    if let Some(stop) = stop {
+        let _ = w.flush();
+        process::exit(
+            match stop {
+                Stop::Interrupted => EXIT_INTERRUPTED,
+                Stop::MemoryLimit | Stop::Timeout if failed => EXIT_PROPERTY_FAILURE,
+                Stop::MemoryLimit | Stop::Timeout => EXIT_INCOMPLETE,
+            }
+            .into(),
+        );
+    }
+}
+
+/// Returns the resident memory of the process in bytes, or `None` if it cannot be measured.
+fn resident_memory() -> Option<usize> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?;
+    Some(kib.trim().parse::<usize>().ok()? * 1024)
+}
+
+/// Waits for the checker to finish, printing its progress to the standard error every second
+/// unless `quiet` is set, or returns why the check stopped before.
+fn join_with_progress<M: Model, C: Checker<M>>(checker: C, options: &Options) -> (C, Option<Stop>) {
+    let start = Instant::now();
+    let mut next_report = start;
+    let max_memory = options
+        .max_memory
+        .map(|mib| mib.saturating_mul(1024 * 1024));
+    if max_memory.is_some() && resident_memory().is_none() {
+        eprintln!("The memory use cannot be measured on this platform.");
+    }
+    // The checker never reports itself done once it reaches the target state count, but its
+    // threads stop, so that it can be joined.
+    let is_done = |checker: &C| {
+        checker.is_done()
+            || options
+                .max_states
+                .is_some_and(|max_states| checker.state_count() >= max_states)
+    };
+    while !is_done(&checker) {
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            return (checker, Some(Stop::Interrupted));
+        }
+        if max_memory
+            .is_some_and(|max_memory| resident_memory().is_some_and(|memory| memory > max_memory))
+        {
+            return (checker, Some(Stop::MemoryLimit));
+        }
+        let now = Instant::now();
+        if options
+            .timeout
+            .is_some_and(|timeout| now.duration_since(start) >= timeout)
+        {
+            return (checker, Some(Stop::Timeout));
+        }
+        if !options.quiet && now >= next_report {
+            let states = checker.state_count();
+            let msec = now.duration_since(start).as_millis();
+            eprintln!(
+                "Checking. states={states}, unique={}, sec={}, states/sec={}",
+                checker.unique_state_count(),
+                msec / 1000,
+                u128::try_from(states).unwrap_or(u128::MAX) * 1000 / msec.max(1)
+            );
+            next_report += Duration::from_secs(1);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    (checker.join(), None)
+}
+
+/// Saves each counterexample as a trace file in the directory.
+fn save_traces<M>(checker: &impl Checker<M>, dir: &Path, w: &mut impl Write)
+where
+    M: Model,
+    M::Action: Debug,
+    M::State: Debug,
+{
+    let mut discoveries: Vec<_> = checker.discoveries().into_iter().collect();
+    discoveries.sort_by_key(|(name, _)| *name);
+    for (name, path) in discoveries {
+        if checker.discovery_classification(name) != "counterexample" {
+            continue;
+        }
+        match Trace::from_path::<M>(path).save(dir, name) {
+            Ok(file) => {
+                let _ = writeln!(w, "Saved \"{name}\" counterexample to {}.", file.display());
+            }
+            Err(error) => eprintln!("Failed to save the \"{name}\" counterexample: {error}."),
+        }
+    }
+}
+
+/// Reports the discoveries that can be shortened with as many actions removed as possible.
+fn report_shrunk<M>(checker: &impl Checker<M>, w: &mut impl Write)
+where
+    M: Model,
+    M::Action: Clone + Debug + PartialEq,
+{
+    let mut discoveries: Vec<_> = checker.discoveries().into_iter().collect();
+    discoveries.sort_by_key(|(name, _)| *name);
+    for (name, path) in discoveries {
+        let length = checker
+            .discovery(name)
+            .map_or(0, |path| path.into_actions().len());
+        let actions = shrink::shrink(checker.model(), &checker.model().property(name), path);
+        if actions.len() < length {
+            let _ = writeln!(
+                w,
+                "Shrunk \"{name}\" {} from {length} to {} actions:",
+                checker.discovery_classification(name),
+                actions.len()
+            );
+            for action in actions {
+                let _ = writeln!(w, "- {action:?}");
+            }
+        }
+    }
+}
+
+/// Prints the usage.
+#[allow(clippy::too_many_lines)]
+fn print_usage() {
+    println!("USAGE:");
+    println!("  cargo run check [MODEL] [OPTIONS]");
+    println!("  cargo run check-all [DIR] [OPTIONS]");
+    println!("  cargo run diff [2pc] [OPTIONS] --versus [OPTIONS]");
+    println!("  cargo run explore [MODEL] [host:port] [OPTIONS]");
+    println!("  cargo run export-html [MODEL] [output.html] [OPTIONS]");
+    println!("  cargo run export-dot [MODEL] [output.dot] [OPTIONS]");
+    println!("  cargo run export-trace [MODEL] [trace.json] [OPTIONS]");
+    println!("  cargo run validate-trace [MODEL] [trace.json] [OPTIONS]");
+    println!("  cargo run replay [MODEL] [trace.json] [OPTIONS]");
+    println!("  cargo run simulate [MODEL] [OPTIONS]");
+    println!("  cargo run properties [MODEL] [OPTIONS]");
+    println!("  cargo run step [MODEL] [OPTIONS]");
+    println!("  cargo run bench [2pc] [--nodes N] [OPTIONS]");
+    println!();
+    println!("MODELS:");
+    println!("  2pc                    Two-phase commit (default)");
+    println!("  3pc                    Three-phase commit with fail-stop nodes");
+    println!("  paxos-commit           Paxos Commit tolerating one acceptor failure");
+    println!("  raft                   2PC with a coordinator replicated to two standbys");
+    println!("  linear                 Linear 2PC forwarding prepare along a chain");
+    println!("  saga                   Saga of local steps and compensating actions");
+    println!("  byzantine              Participants of an equivocating coordinator");
+    println!("  blacklist              Coordinator blacklisting unresponsive participants");
+    println!("  group-commit           Coordinator forcing several decisions at once");
+    println!("  locks                  Concurrent transactions competing for locks");
+    println!("  concurrent             Concurrent 2PC transactions sharing the nodes");
+    println!("  percolator             Percolator commit deciding on the primary lock");
+    println!();
+    println!("OPTIONS:");
+    println!("  --config FILE          Read the nodes, the coordinator, the crash budget, the");
+    println!("                         faults and the properties from a TOML file; the flags");
+    println!("                         override its values");
+    println!("  --presumed-commit      Use the presumed-commit variant of 2PC");
+    println!("  --graceful-shutdown    Allow nodes to be gracefully shut down");
+    println!("  --replacement          Allow participants to fail permanently and be replaced");
+    println!("  --decision-latency     Check the decision latency of a fault-free system");
+    println!("  --coverage             Report the actions and the transaction states reached");
+    println!("  --one-phase            Let a single participant decide without the voting phase");
+    println!("  --last-agent           Delegate the decision to the last participant");
+    println!("  --retry-budget N       Let overloaded participants defer their votes N times");
+    println!("  --replicas N           Back each participant by N replicas");
+    println!("  --quorum N             Commit once N participants have voted for commit");
+    println!("  --nodes N              Check N nodes including the coordinator (default: 3)");
+    println!("  --parents P1,P2,...    Form a commit tree with the parent of each participant");
+    println!("  --coordinator N        Let the node N coordinate the transaction (default: 0)");
+    println!("  --workload NAME        Check the data of a workload: transfer, append, counter");
+    println!("  --coordinator-policy NAME");
+    println!("                         Let the coordinator decide as permissive (default), strict");
+    println!("                         or impatient");
+    println!("  --participant-behavior N=NAME");
+    println!("                         Let the participant N behave as cooperative, no-voter,");
+    println!("                         slow or unrecoverable; may be repeated");
+    println!("  --scenario FILE        Inject only the faults of the JSON scenario file");
+    println!("  --invariants FILE      Check the invariants of the JSON file as well");
+    println!("  --property NAME        Check only the property; may be repeated");
+    println!("  --cooperative-termination");
+    println!("                         Let participants of a failed coordinator query their peers");
+    println!("  --coordinator-election");
+    println!("                         Elect a participant to take over from a failed coordinator");
+    println!("  --backup-coordinator   Let a hot-standby coordinator take over a failed one");
+    println!("  --heuristics           Let in-doubt participants decide heuristically");
+    println!("  --asynchronous-network");
+    println!("                         Deliver messages in a later step than they are sent");
+    println!("  --duplicates N         Let the asynchronous network duplicate N messages");
+    println!("  --link-failures N      Let N directed links of the asynchronous network fail");
+    println!("  --crashes N            Let the nodes crash at most N times in total");
+    println!("  --checkpointing        Let the nodes checkpoint the transaction and truncate logs");
+    println!("  --torn-writes          Lose the log records that are not synced when nodes crash");
+    println!("  --coordinator-amnesia  Let the coordinator lose its log before it has decided");
+    println!("  --log-write-failures   Let the logs of the participants fill up");
+    println!("  --fail-stop            Keep crashed nodes down instead of restarting them");
+    println!("  --fairness             Prune steps that do not change the state, so that");
+    println!("                         liveness is checked; use along with --crashes");
+    println!("  --dead-participant N   Let the participant N die along with the coordinator");
+    println!("  --byzantine-participant N");
+    println!("                         Let the participant N send forged votes and commits");
+    println!("  --read-only-participants N1,N2,...");
+    println!("                         Let the participants reply read-only to prepare requests");
+    println!("  --timeout-ticks N      Fire timeouts only after N ticks of a simulated clock");
+    println!("  --max-ticks N          Advance the simulated clock at most N ticks (default: 3)");
+    println!("  --timers               Fire timeouts only when the timers of waiting nodes expire");
+    println!("  --gossip               Let participants detect an equivocating coordinator");
+    println!("  --blacklist-threshold N");
+    println!("                         Blacklist after N failed prepare attempts (default: 2)");
+    println!("  --wait-die             Use wait-die instead of wound-wait for locks");
+    println!("  --read-only            Add a read-only transaction to the lock model");
+    println!("  --transactions N       Run N concurrent transactions (default: 2)");
+    println!(
+        "  --versus               Configure the second model of diff with the flags after it,"
+    );
+    println!("                         on top of the flags before it");
+    println!("  --search bfs|dfs       Search breadth-first for the shortest counterexamples,");
+    println!("                         or depth-first (default)");
+    println!("  --max-depth N          Explore the executions of at most N actions, and skip the");
+    println!("                         liveness properties; only for the 2pc model");
+    println!("  --format text|json|junit");
+    println!("                         Print the check report as text (default), as JSON with");
+    println!("                         the state counts, the property results, and the traces,");
+    println!("                         or as JUnit XML with a test case for each property");
+    println!("  --traces DIR           Save the counterexamples to DIR as trace files");
+    println!("  --seed S               Seed the random simulation (default: the clock)");
+    println!("  --steps N              Simulate runs of at most N steps (default: 100)");
+    println!("  --runs N               Simulate N runs (default: 1000)");
+    println!("  --quiet                Do not print the progress of the check to stderr");
+    println!("  --threads N            Check with N threads (default: all available)");
+    println!("  --cached               Reuse the check result of an unchanged model");
+    println!("  --max-states N         Stop the check after about N states, as it finishes the");
+    println!("                         states in progress, or export at most N states");
+    println!("                         (export default: 10000)");
+    println!("  --max-memory MIB       Stop the check once it uses more than MIB MiB of memory");
+    println!("  --timeout DURATION     Stop the check after DURATION, such as 90s, 10m or 2h");
+}
+
+/// Exports the reachable state graph of the model as a Graphviz digraph, labeling each state with
+/// `label`.
+fn export_dot<M>(model: &M, options: Options, label: impl Fn(&M::State) -> String)
+where
+    M: Model,
+    M::Action: Debug,
+    M::State: Hash,
+{
+    let path = options
+        .argument
+        .unwrap_or_else(|| "state-space.dot".to_string());
+    let graph = StateGraph::build(model, options.max_states.unwrap_or(10_000));
+    println!(
+        "Export {} states{} to {path}.",
+        graph.states.len(),
+        if graph.truncated { " (truncated)" } else { "" }
+    );
+    if let Err(error) = fs::write(&path, dot_export::render(&graph, label)) {
+        eprintln!("Failed to write {path}: {error}.");
+    }
+}
+
+/// Exports the discovery of the first property with a discovery as a trace file.
+fn export_trace<M>(model: M, options: Options)
+where
+    M: 'static + Model + Send + Sync,
+    M::Action: Debug + Send + Sync,
+    M::State: Debug + Hash + Send + Sync,
+{
+    let path = options.argument.unwrap_or_else(|| "trace.json".to_string());
+    let checker = model.checker().threads(options.num_cpus).spawn_bfs().join();
+    let mut discoveries: Vec<_> = checker.discoveries().into_iter().collect();
+    discoveries.sort_by_key(|(name, _)| *name);
+    let Some((name, discovery)) = discoveries.into_iter().next() else {
+        println!("No property has a discovery.");
+        return;
+    };
+    println!("Export the \"{name}\" discovery to {path}.");
+    if let Err(error) = fs::write(&path, Trace::from_path::<M>(discovery).to_json()) {
+        eprintln!("Failed to write {path}: {error}.");
+    }
+}
+
+/// Simulates random runs of the model, and reports the violations with the seeds that reproduce
+/// them.
+fn simulate<M: Model>(model: &M, options: &Options)
+where
+    M::Action: Clone + Debug,
+    M::State: PartialEq,
+{
+    let seed = options.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+    });
+    println!(
+        "Simulate {} runs of at most {} steps from seed {seed}.",
+        options.runs, options.steps
+    );
+    let start = Instant::now();
+    let report = simulation::simulate(model, seed, options.steps, options.runs);
+    println!(
+        "Done. runs={}, steps={}, sec={}",
+        report.runs,
+        report.steps,
+        start.elapsed().as_secs()
+    );
+    if report.violations.is_empty() {
+        println!("No property was violated.");
+    }
+    for violation in report.violations {
+        println!(
+            "Violated \"{}\" in {} steps; reproduce with --seed {} --runs 1:",
+            violation.property,
+            violation.actions.len(),
+            violation.seed
+        );
+        for action in violation.actions {
+            println!("- {action:?}");
+        }
+    }
+}
+
+/// Replays the actions of the trace file on the model, printing each state along with the
+/// properties that it violates or satisfies.
+fn replay_trace<M: Model>(model: &M, options: Options)
+where
+    M::Action: Debug,
+    M::State: Debug,
+{
+    let path = options.argument.unwrap_or_else(|| "trace.json".to_string());
+    let result = fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| Trace::load(&json).map_err(|error| error.to_string()))
+        .and_then(|trace| {
+            let states = trace
+                .replay_actions(model)
+                .map_err(|error| error.to_string())?;
+            Ok((trace, states))
+        });
+    let (trace, states) = match result {
+        Ok(replayed) => replayed,
+        Err(error) => {
+            eprintln!("Failed to replay {path}: {error}.");
+            return;
+        }
+    };
+    println!("Replay {} actions of {path}.", states.len() - 1);
+    let properties = model.properties();
+    let mut satisfied = vec![false; properties.len()];
+    for (step, (trace_step, state)) in trace.steps.iter().zip(&states).enumerate() {
+        let action = trace_step.action.as_deref().unwrap_or("initial state");
+        println!("Step {step}: {action}");
+        println!("{state:?}");
+        evaluate_properties(model, &properties, state, &mut satisfied);
+    }
+    report_unsatisfied(&properties, &satisfied);
+}
+
+/// Prints the properties that the state violates or is an example of, and marks the
+/// eventually-properties that hold in it as satisfied.
+fn evaluate_properties<M: Model>(
+    model: &M,
+    properties: &[Property<M>],
+    state: &M::State,
+    satisfied: &mut [bool],
+) {
+    let mut violated = Vec::new();
+    let mut examples = Vec::new();
+    for (index, property) in properties.iter().enumerate() {
+        let holds = (property.condition)(model, state);
+        match property.expectation {
+            Expectation::Always if !holds => violated.push(property.name),
+            Expectation::Sometimes if holds => examples.push(property.name),
+            Expectation::Eventually if holds => satisfied[index] = true,
+            _ => {}
+        }
+    }
+    if !violated.is_empty() {
+        println!("Violated: {}.", violated.join(", "));
+    }
+    if !examples.is_empty() {
+        println!("Example of: {}.", examples.join(", "));
+    }
+}
+
+/// Prints the eventually-properties that have not been satisfied.
+fn report_unsatisfied<M: Model>(properties: &[Property<M>], satisfied: &[bool]) {
+    let unsatisfied: Vec<_> = properties
+        .iter()
+        .zip(satisfied)
+        .filter(|(property, satisfied)| {
+            matches!(property.expectation, Expectation::Eventually) && !**satisfied
+        })
+        .map(|(property, _)| property.name)
+        .collect();
+    if !unsatisfied.is_empty() {
+        println!("Never satisfied: {}.", unsatisfied.join(", "));
+    }
+}
+
+/// Steps through the model from an initial state: lists the enabled actions, takes the one that
+/// is picked by its index from the standard input, and evaluates the properties on the resulting
+/// state.
+fn step<M: Model>(model: &M)
+where
+    M::Action: Debug,
+    M::State: Debug + PartialEq,
+{
+    let properties = model.properties();
+    let init_states: Vec<M::State> = model
+        .init_states()
+        .into_iter()
+        .filter(|state| model.within_boundary(state))
+        .collect();
+    if init_states.len() > 1 {
+        for (index, state) in init_states.iter().enumerate() {
+            println!("[{index}] {state:?}");
+        }
+    }
+    let mut lines = io::stdin().lock().lines();
+    let mut prompt = |question: &str| {
+        print!("{question}: ");
+        let _ = io::stdout().flush();
+        lines.next().and_then(Result::ok)
+    };
+    let state = match init_states.len() {
+        0 => {
+            println!("No initial state.");
+            return;
+        }
+        1 => init_states.into_iter().next(),
+        _ => prompt("Pick an initial state")
+            .and_then(|line| line.trim().parse::<usize>().ok())
+            .and_then(|index| init_states.into_iter().nth(index)),
+    };
+    let Some(state) = state else {
+        return;
+    };
+
+    let mut satisfied = vec![false; properties.len()];
+    println!("Step 0: initial state");
+    println!("{state:?}");
+    evaluate_properties(model, &properties, &state, &mut satisfied);
+    // The states taken so far, along with the eventually-properties satisfied up to each of them.
+    let mut path = vec![(state, satisfied)];
+    while let Some((state, satisfied)) = path.last() {
+        let steps: Vec<(M::Action, M::State)> = model
+            .next_steps(state)
+            .into_iter()
+            .filter(|(_, next_state)| model.within_boundary(next_state))
+            .collect();
+        if steps.is_empty() {
+            println!("No action is enabled.");
+            report_unsatisfied(&properties, satisfied);
+        }
+        for (index, (action, next_state)) in steps.iter().enumerate() {
+            let unchanged = if next_state == state {
+                " (unchanged)"
+            } else {
+                ""
+            };
+            println!("[{index}] {action:?}{unchanged}");
+        }
+        let Some(line) = prompt("Pick an action, b to go back, or q to quit") else {
+            println!();
+            return;
+        };
+        match line.trim() {
+            "q" => return,
+            "b" if path.len() > 1 => {
+                path.pop();
+                println!("Back to step {}.", path.len() - 1);
+            }
+            "b" => println!("This is the initial state."),
+            choice => match choice
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| steps.into_iter().nth(index))
+            {
+                Some((action, next_state)) => {
+                    let mut satisfied = satisfied.clone();
+                    println!("Step {}: {action:?}", path.len());
+                    println!("{next_state:?}");
+                    evaluate_properties(model, &properties, &next_state, &mut satisfied);
+                    path.push((next_state, satisfied));
+                }
+                None => println!("Unknown choice: {choice}."),
+            },
+        }
+    }
+}
+
+/// Validates the trace file by replaying it on the model.
+fn validate_trace<M: Model>(model: &M, options: Options)
+where
+    M::Action: Debug,
+    M::State: Debug,
+{
+    let path = options.argument.unwrap_or_else(|| "trace.json".to_string());
+    let result = fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|json| Trace::load(&json).map_err(|error| error.to_string()))
+        .and_then(|trace| trace.validate(model).map_err(|error| error.to_string()));
+    match result {
+        Ok(()) => println!("The trace in {path} is reproduced by the model."),
+        Err(error) => eprintln!("Failed to validate {path}: {error}."),
+    }
+}
+
+/// Checks the model unless the result for the same model and code is found in the cache, and
+/// returns `true` if a property fails.
+fn check_cached<M>(model: M, options: &Options, search: Search, format: Format) -> bool
+where
+    M: 'static + Hash + Model + Send + Sync,
+    M::Action: Clone + Debug + PartialEq + Send + Sync,
+    M::State: Debug + Hash + Send + Sync,
+{
+    let cache_file = Path::new(check_cache::CACHE_FILE);
+    let fingerprint = check_cache::fingerprint(&(&model, search, format));
+    if let Some((report, failed)) = check_cache::lookup(cache_file, fingerprint) {
+        info!("Reusing the cached result {fingerprint:016x}.");
+        if format == Format::Text {
+            println!("Cached result {fingerprint:016x}.");
+        }
+        print!("{report}");
+        return failed;
+    }
+
+    let mut tee = check_cache::Tee::new(io::stdout());
+    let failed = check(model, options, search, format, &mut tee);
+    let _ = tee.flush();
+    if let Err(error) = check_cache::store(cache_file, fingerprint, &tee.output(), failed) {
+        eprintln!("Failed to cache the result: {error}.");
+    }
+    failed
+}
@@ -0,0 +1,268 @@
+//! This is a model of a coordinator that blacklists unresponsive participants.
+//!
+//! # Transactions
+//!
+//! The coordinator runs a number of distributed transactions one after another. Each transaction
+//! involves all the participants that have not been blacklisted, and the coordinator asks them to
+//! prepare for commit as soon as the transaction starts. A participant votes for or against
+//! commit unless it crashes; a crashed participant recovers before the next transaction starts.
+//!
+//! # Blacklisting
+//!
+//! The coordinator rolls back the transaction when it times out waiting for the vote of a crashed
+//! participant, and counts the timeout as a failed prepare attempt of the participant. Once the
+//! number of failed prepare attempts of a participant reaches the threshold, the participant is
+//! excluded from the subsequent transactions. A vote against commit is not a failure.
+
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// [`BlacklistSystem`] is the state of the coordinator and its participants.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BlacklistSystem {
+    /// The number of transactions that have been started.
+    num_started: usize,
+
+    /// The current transaction of the coordinator; the participants are the participant nodes.
+    coordinator: Transaction,
+
+    /// The participant nodes.
+    participants: Vec<Participant>,
+}
+
+/// [`Participant`] is a participant node.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Participant {
+    /// The current transaction of the participant.
+    transaction: Transaction,
+
+    /// The participant has crashed during the current transaction.
+    crashed: bool,
+
+    /// The number of failed prepare attempts counted by the coordinator.
+    failures: usize,
+
+    /// The coordinator has excluded the participant from future transactions.
+    blacklisted: bool,
+
+    /// The number of transactions in which the participant never voted.
+    ///
+    /// It is not visible to the coordinator, and is only used to check the properties.
+    silent: usize,
+}
+
+/// [`BlacklistAction`] is a state transition of [`BlacklistSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BlacklistAction {
+    /// The coordinator starts a new transaction, and asks the participants to prepare.
+    Begin,
+
+    /// The participant votes for commit.
+    VoteYes(usize),
+
+    /// The participant votes against commit.
+    VoteNo(usize),
+
+    /// The participant crashes.
+    Crash(usize),
+
+    /// The coordinator times out waiting for the vote of the participant.
+    Timeout(usize),
+
+    /// The coordinator commits the transaction.
+    Commit,
+}
+
+/// [`BlacklistModel`] implements state transitions of a coordinator blacklisting participants.
+#[derive(Hash)]
+pub struct BlacklistModel {
+    /// The number of participant nodes.
+    num_participants: usize,
+
+    /// The number of transactions.
+    num_transactions: usize,
+
+    /// The number of failed prepare attempts that blacklists a participant.
+    threshold: usize,
+}
+
+impl BlacklistModel {
+    /// Creates a new [`BlacklistModel`].
+    #[must_use]
+    pub fn new(
+        num_participants: usize,
+        num_transactions: usize,
+        threshold: usize,
+    ) -> BlacklistModel {
+        BlacklistModel {
+            num_participants,
+            num_transactions,
+            threshold,
+        }
+    }
+
+    fn begin(last_state: &BlacklistSystem) -> BlacklistSystem {
+        let mut state = last_state.clone();
+        state.num_started += 1;
+        state.coordinator = Transaction::default();
+        state.coordinator.start();
+        for (node_id, participant) in state.participants.iter_mut().enumerate() {
+            // A crashed participant recovers, and a blacklisted participant is not involved.
+            participant.crashed = false;
+            participant.transaction = Transaction::default();
+            if !participant.blacklisted {
+                participant.transaction.start();
+                state.coordinator.add_participant(node_id);
+            }
+        }
+        state.coordinator.prepare();
+        state
+    }
+
+    fn crash(last_state: &BlacklistSystem, node_id: usize) -> BlacklistSystem {
+        let mut state = last_state.clone();
+        let participant = &mut state.participants[node_id];
+        participant.crashed = true;
+        if participant.transaction.state() == TransactionState::Active {
+            // The participant will never vote in the current transaction.
+            participant.silent += 1;
+        }
+        state
+    }
+
+    fn timeout(&self, last_state: &BlacklistSystem, node_id: usize) -> Option<BlacklistSystem> {
+        let mut state = last_state.clone();
+        if !state.coordinator.rollback() {
+            return None;
+        }
+        let participant = &mut state.participants[node_id];
+        participant.failures += 1;
+        if participant.failures >= self.threshold {
+            participant.blacklisted = true;
+        }
+        Some(state)
+    }
+}
+
+impl Model for BlacklistModel {
+    type State = BlacklistSystem;
+    type Action = BlacklistAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![BlacklistSystem {
+            num_started: 0,
+            coordinator: Transaction::default(),
+            participants: vec![
+                Participant {
+                    transaction: Transaction::default(),
+                    crashed: false,
+                    failures: 0,
+                    blacklisted: false,
+                    silent: 0,
+                };
+                self.num_participants
+            ],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        match state.coordinator.state() {
+            TransactionState::Prepared if state.coordinator.is_all_prepared() => {
+                actions.push(BlacklistAction::Commit);
+            }
+            TransactionState::Prepared => {
+                state
+                    .participants
+                    .iter()
+                    .enumerate()
+                    .filter(|(node_id, _)| {
+                        state.coordinator.has_participant(*node_id)
+                            && !state.coordinator.is_prepared(*node_id)
+                    })
+                    .for_each(|(node_id, participant)| {
+                        if participant.crashed {
+                            actions.push(BlacklistAction::Timeout(node_id));
+                        } else if participant.transaction.state() == TransactionState::Active {
+                            actions.push(BlacklistAction::VoteYes(node_id));
+                            actions.push(BlacklistAction::VoteNo(node_id));
+                            actions.push(BlacklistAction::Crash(node_id));
+                        }
+                    });
+            }
+            _ if state.num_started < self.num_transactions => actions.push(BlacklistAction::Begin),
+            _ => (),
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            BlacklistAction::Begin => Some(Self::begin(last_state)),
+            BlacklistAction::VoteYes(node_id) => {
+                let mut state = last_state.clone();
+                state.participants[node_id].transaction.prepare();
+                state.coordinator.report_prepared(node_id).then_some(state)
+            }
+            BlacklistAction::VoteNo(node_id) => {
+                let mut state = last_state.clone();
+                state.participants[node_id].transaction.rollback();
+                state.coordinator.rollback().then_some(state)
+            }
+            BlacklistAction::Crash(node_id) => Some(Self::crash(last_state, node_id)),
+            BlacklistAction::Timeout(node_id) => self.timeout(last_state, node_id),
+            BlacklistAction::Commit => {
+                let mut state = last_state.clone();
+                state.coordinator.commit().then_some(state)
+            }
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("no blacklisting of voters", |model, state| {
+                // A participant is blacklisted only if it failed to vote in as many transactions
+                // as the threshold.
+                state.participants.iter().all(|participant| {
+                    !participant.blacklisted || participant.silent >= model.threshold
+                })
+            }),
+            Property::<Self>::always("blacklisted participants excluded", |_, state| {
+                // A blacklisted participant takes no part in the transactions after the one in
+                // which it was blacklisted.
+                state.participants.iter().all(|participant| {
+                    !participant.blacklisted
+                        || participant.transaction.state() == TransactionState::Inactive
+                        || state.coordinator.state().is_terminal()
+                })
+            }),
+            Property::<Self>::sometimes("blacklisted", |_, state| {
+                state
+                    .participants
+                    .iter()
+                    .any(|participant| participant.blacklisted)
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{BlacklistModel, Model};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn blacklist() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = BlacklistModel::new(2, 3, 2)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
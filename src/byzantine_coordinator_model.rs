@@ -0,0 +1,230 @@
+//! This is an experimental model of participants detecting an equivocating coordinator.
+//!
+//! # Equivocation
+//!
+//! All the participants have voted for commit, and wait for the decision of a Byzantine
+//! coordinator that may send commit to some participants and rollback to others.
+//!
+//! # Gossip
+//!
+//! In the gossip-enabled variant, a participant that has received a decision forwards it to its
+//! peers, and applies the decision only after it has heard the decisions of all of its peers; a
+//! participant that hears a conflicting decision has detected the equivocation, and never applies
+//! any decision. Without gossip, a participant applies the decision as soon as it receives it.
+
+use stateright::{Model, Property};
+
+/// [`ByzantineSystem`] is the state of the participants of an equivocating coordinator.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ByzantineSystem {
+    /// The participants.
+    participants: Vec<Participant>,
+}
+
+impl ByzantineSystem {
+    /// Returns `true` if two participants have applied different decisions.
+    fn is_diverged(&self) -> bool {
+        let mut applied = self
+            .participants
+            .iter()
+            .filter(|participant| participant.applied)
+            .filter_map(|participant| participant.received);
+        applied
+            .next()
+            .is_some_and(|decision| applied.any(|other_decision| other_decision != decision))
+    }
+
+    /// Returns `true` if a participant has detected the equivocation.
+    fn is_detected(&self) -> bool {
+        self.participants.iter().any(Participant::is_conflicting)
+    }
+}
+
+/// [`Participant`] is a participant waiting for the decision.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Participant {
+    /// The decision received from the coordinator; `true` for commit.
+    received: Option<bool>,
+
+    /// The decisions heard from the peers.
+    heard: Vec<Option<bool>>,
+
+    /// The participant has irreversibly applied the received decision.
+    applied: bool,
+}
+
+impl Participant {
+    /// Returns `true` if the participant knows two different decisions.
+    fn is_conflicting(&self) -> bool {
+        let mut decisions = self.received.iter().chain(self.heard.iter().flatten());
+        decisions
+            .next()
+            .is_some_and(|decision| decisions.any(|other_decision| other_decision != decision))
+    }
+}
+
+/// [`ByzantineAction`] is a state transition of [`ByzantineSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ByzantineAction {
+    /// The coordinator sends commit to the participant.
+    SendCommit(usize),
+
+    /// The coordinator sends rollback to the participant.
+    SendRollback(usize),
+
+    /// The first participant forwards its received decision to the second participant.
+    Gossip(usize, usize),
+
+    /// The participant applies the received decision.
+    Apply(usize),
+}
+
+/// [`ByzantineCoordinatorModel`] implements state transitions of participants of an equivocating
+/// coordinator.
+#[derive(Hash)]
+pub struct ByzantineCoordinatorModel {
+    /// The number of participants.
+    num_participants: usize,
+
+    /// The participants gossip the received decisions.
+    gossip: bool,
+}
+
+impl ByzantineCoordinatorModel {
+    /// Creates a new [`ByzantineCoordinatorModel`].
+    #[must_use]
+    pub fn new(num_participants: usize, gossip: bool) -> ByzantineCoordinatorModel {
+        ByzantineCoordinatorModel {
+            num_participants,
+            gossip,
+        }
+    }
+
+    /// Returns `true` if the participant may apply the received decision.
+    fn can_apply(&self, participant_id: usize, participant: &Participant) -> bool {
+        if participant.received.is_none() || participant.applied {
+            return false;
+        }
+        !self.gossip
+            || (!participant.is_conflicting()
+                && participant
+                    .heard
+                    .iter()
+                    .enumerate()
+                    .all(|(peer_id, heard)| peer_id == participant_id || heard.is_some()))
+    }
+
+    fn send(last_state: &ByzantineSystem, participant_id: usize, commit: bool) -> ByzantineSystem {
+        let mut state = last_state.clone();
+        state.participants[participant_id].received = Some(commit);
+        state
+    }
+
+    fn gossip(last_state: &ByzantineSystem, from: usize, to: usize) -> Option<ByzantineSystem> {
+        let mut state = last_state.clone();
+        state.participants[to].heard[from] = Some(last_state.participants[from].received?);
+        Some(state)
+    }
+}
+
+impl Model for ByzantineCoordinatorModel {
+    type State = ByzantineSystem;
+    type Action = ByzantineAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![ByzantineSystem {
+            participants: vec![
+                Participant {
+                    received: None,
+                    heard: vec![None; self.num_participants],
+                    applied: false,
+                };
+                self.num_participants
+            ],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for (participant_id, participant) in state.participants.iter().enumerate() {
+            if participant.received.is_none() {
+                actions.push(ByzantineAction::SendCommit(participant_id));
+                actions.push(ByzantineAction::SendRollback(participant_id));
+            } else if self.gossip {
+                for (peer_id, peer) in state.participants.iter().enumerate() {
+                    if peer_id != participant_id && peer.heard[participant_id].is_none() {
+                        actions.push(ByzantineAction::Gossip(participant_id, peer_id));
+                    }
+                }
+            }
+            if self.can_apply(participant_id, participant) {
+                actions.push(ByzantineAction::Apply(participant_id));
+            }
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            ByzantineAction::SendCommit(participant_id) => {
+                Some(Self::send(last_state, participant_id, true))
+            }
+            ByzantineAction::SendRollback(participant_id) => {
+                Some(Self::send(last_state, participant_id, false))
+            }
+            ByzantineAction::Gossip(from, to) => Self::gossip(last_state, from, to),
+            ByzantineAction::Apply(participant_id) => {
+                let mut state = last_state.clone();
+                state.participants[participant_id].applied = true;
+                Some(state)
+            }
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        let mut properties = vec![Property::<Self>::sometimes("detection", |_, state| {
+            state.is_detected()
+        })];
+        if self.gossip {
+            properties.push(Property::<Self>::always(
+                "detection precedes divergence",
+                |_, state| {
+                    // Participants never apply different decisions before the equivocation is
+                    // detected.
+                    !state.is_diverged() || state.is_detected()
+                },
+            ));
+        }
+        properties
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{ByzantineCoordinatorModel, Model};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn gossip() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = ByzantineCoordinatorModel::new(3, true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn no_gossip() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = ByzantineCoordinatorModel::new(3, false)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("detection");
+    }
+}
@@ -0,0 +1,144 @@
+//! This is a cache of model-checking results.
+//!
+//! A result is identified by a fingerprint of the model configuration and the code version; the
+//! code version is represented by the contents of the running executable, so that any code change
+//! invalidates the cached results.
+//!
+//! # Format
+//!
+//! Each entry in the cache file starts with a `# <fingerprint>` line followed by the lines of the
+//! check report; the header line ends with ` failed` if a property failed in the check.
+
+use std::any::type_name;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The path to the cache file.
+pub const CACHE_FILE: &str = ".check-cache";
+
+/// Returns the fingerprint of the model configuration and the code version.
+pub fn fingerprint<M: Hash>(model: &M) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    type_name::<M>().hash(&mut hasher);
+    model.hash(&mut hasher);
+    env::current_exe()
+        .and_then(fs::read)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns the cached check report for the fingerprint, and whether a property failed in it.
+#[must_use]
+pub fn lookup(path: &Path, fingerprint: u64) -> Option<(String, bool)> {
+    let contents = fs::read_to_string(path).ok()?;
+    let header = format!("# {fingerprint:016x}");
+    let mut lines = contents.lines().skip_while(|line| {
+        line.strip_prefix(&header)
+            .is_none_or(|status| !matches!(status, "" | " failed"))
+    });
+    let failed = lines.next()?.ends_with(" failed");
+    let report: Vec<&str> = lines.take_while(|line| !line.starts_with("# ")).collect();
+    Some((report.join("\n") + "\n", failed))
+}
+
+/// Appends the check report for the fingerprint to the cache file, along with whether a property
+/// failed in the check.
+///
+/// # Errors
+///
+/// Returns an error if the cache file cannot be written.
+pub fn store(path: &Path, fingerprint: u64, report: &str, failed: bool) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(
+        file,
+        "# {fingerprint:016x}{}",
+        if failed { " failed" } else { "" }
+    )?;
+    report
+        .lines()
+        .filter(|line| !line.starts_with("Checking."))
+        .try_for_each(|line| writeln!(file, "{line}"))
+}
+
+/// [`Tee`] writes to the underlying writer while keeping a copy of the output.
+pub struct Tee<W: Write> {
+    /// The underlying writer.
+    inner: W,
+
+    /// The copy of the output.
+    output: Vec<u8>,
+}
+
+impl<W: Write> Tee<W> {
+    /// Creates a new [`Tee`].
+    pub fn new(inner: W) -> Tee<W> {
+        Tee {
+            inner,
+            output: Vec::new(),
+        }
+    }
+
+    /// Returns the copy of the output.
+    pub fn output(&self) -> String {
+        String::from_utf8_lossy(&self.output).into_owned()
+    }
+}
+
+impl<W: Write> Write for Tee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.output.extend_from_slice(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fingerprint, lookup, store};
+
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn store_and_lookup() {
+        let path = env::temp_dir().join(format!("check-cache-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let first = fingerprint(&1_usize);
+        let second = fingerprint(&2_usize);
+        assert_ne!(first, second);
+        assert!(lookup(&path, first).is_none());
+
+        store(&path, first, "Checking. states=1\nDone. states=3\n", false).unwrap();
+        store(
+            &path,
+            second,
+            "Done. states=5\nDiscovered \"ACID\"\n- Crash(0)\n",
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            lookup(&path, first).unwrap(),
+            ("Done. states=3\n".to_string(), false)
+        );
+        assert_eq!(
+            lookup(&path, second).unwrap(),
+            (
+                "Done. states=5\nDiscovered \"ACID\"\n- Crash(0)\n".to_string(),
+                true
+            )
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+//! This is a machine-readable report of a model-checking result.
+//!
+//! # Format
+//!
+//! A report is a JSON document with the name of the model type, the number of generated and
+//! unique states, the maximum depth if the exploration was bounded, whether the check was
+//! interrupted, and the result of each property. A property passes if it has no counterexample;
+//! a sometimes-property passes if it has an example. The discovery of a property, if any, is a
+//! [`Trace`] that `validate-trace` accepts.
+//!
+//! The report can also be rendered as `JUnit` XML for continuous integration: each property is a
+//! test case of a test suite named after the model, and a property that does not pass fails with
+//! its counterexample, if any, as the message body.
+
+use std::any::type_name;
+use std::fmt::{Debug, Write};
+
+use serde::{Deserialize, Serialize};
+use stateright::{Checker, Expectation, Model};
+
+use super::trace::Trace;
+
+/// [`CheckReport`] is the result of model-checking a model.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CheckReport {
+    /// The name of the model type.
+    pub model: String,
+
+    /// The number of generated states, including the repeated ones.
+    pub states: usize,
+
+    /// The number of unique states.
+    pub unique_states: usize,
+
+    /// The maximum number of actions from the initial state, or `None` if the exploration was
+    /// unbounded.
+    pub max_depth: Option<usize>,
+
+    /// The check was interrupted, or stopped at a limit, before the state space was fully
+    /// explored, so that a property without a discovery may still fail.
+    pub interrupted: bool,
+
+    /// The results of the properties in the order of the model.
+    pub properties: Vec<PropertyReport>,
+}
+
+/// [`PropertyReport`] is the result of checking a property.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PropertyReport {
+    /// The name of the property.
+    pub name: String,
+
+    /// The expectation of the property: `always`, `eventually` or `sometimes`.
+    pub expectation: String,
+
+    /// The property has no counterexample, or has an example if it is a sometimes-property.
+    pub passed: bool,
+
+    /// The counterexample or the example of the property, if any.
+    pub discovery: Option<Trace>,
+}
+
+impl CheckReport {
+    /// Creates a [`CheckReport`] of the result of the checker that has finished.
+    #[must_use]
+    pub fn from_checker<M: Model>(checker: &impl Checker<M>) -> CheckReport
+    where
+        M::Action: Debug,
+        M::State: Debug,
+    {
+        let properties = checker
+            .model()
+            .properties()
+            .into_iter()
+            .map(|property| {
+                let discovery = checker.discovery(property.name);
+                let sometimes = matches!(property.expectation, Expectation::Sometimes);
+                PropertyReport {
+                    name: property.name.to_string(),
+                    expectation: match property.expectation {
+                        Expectation::Always => "always",
+                        Expectation::Eventually => "eventually",
+                        Expectation::Sometimes => "sometimes",
+                    }
+                    .to_string(),
+                    passed: discovery.is_some() == sometimes,
+                    discovery: discovery.map(Trace::from_path::<M>),
+                }
+            })
+            .collect();
+        CheckReport {
+            model: type_name::<M>().to_string(),
+            states: checker.state_count(),
+            unique_states: checker.unique_state_count(),
+            max_depth: None,
+            interrupted: false,
+            properties,
+        }
+    }
+
+    /// Returns the JSON document of the report.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Returns the `JUnit` XML document of the report.
+    #[must_use]
+    pub fn to_junit(&self) -> String {
+        let model = xml_escape(&self.model);
+        let tests = self.properties.len();
+        let failures = self
+            .properties
+            .iter()
+            .filter(|property| !property.passed)
+            .count();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites tests=\"{tests}\" failures=\"{failures}\">"
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{model}\" tests=\"{tests}\" failures=\"{failures}\">"
+        );
+        let _ = writeln!(xml, "    <properties>");
+        let _ = writeln!(
+            xml,
+            "      <property name=\"states\" value=\"{}\"/>",
+            self.states
+        );
+        let _ = writeln!(
+            xml,
+            "      <property name=\"unique_states\" value=\"{}\"/>",
+            self.unique_states
+        );
+        if let Some(max_depth) = self.max_depth {
+            let _ = writeln!(
+                xml,
+                "      <property name=\"max_depth\" value=\"{max_depth}\"/>"
+            );
+        }
+        if self.interrupted {
+            let _ = writeln!(xml, "      <property name=\"interrupted\" value=\"true\"/>");
+        }
+        let _ = writeln!(xml, "    </properties>");
+        for property in &self.properties {
+            let name = xml_escape(&property.name);
+            if property.passed {
+                let _ = writeln!(xml, "    <testcase name=\"{name}\" classname=\"{model}\"/>");
+                continue;
+            }
+            let _ = writeln!(xml, "    <testcase name=\"{name}\" classname=\"{model}\">");
+            match &property.discovery {
+                Some(trace) => {
+                    let _ = writeln!(
+                        xml,
+                        "      <failure message=\"counterexample of {} actions\" type=\"{}\">",
+                        trace.steps.len().saturating_sub(1),
+                        property.expectation
+                    );
+                    for step in &trace.steps {
+                        let action = step.action.as_deref().unwrap_or("Init");
+                        let _ =
+                            writeln!(xml, "{}", xml_escape(&format!("{action}: {}", step.state)));
+                    }
+                    let _ = writeln!(xml, "      </failure>");
+                }
+                None => {
+                    let _ = writeln!(
+                        xml,
+                        "      <failure message=\"no example\" type=\"{}\"/>",
+                        property.expectation
+                    );
+                }
+            }
+            let _ = writeln!(xml, "    </testcase>");
+        }
+        let _ = writeln!(xml, "  </testsuite>");
+        let _ = writeln!(xml, "</testsuites>");
+        xml
+    }
+}
+
+/// Escapes the text for an XML attribute value or element content.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::{xml_escape, CheckReport};
+    use crate::transaction_model::TransactionModel;
+
+    use stateright::{Checker, Model};
+
+    #[test]
+    fn from_checker() {
+        let checker = TransactionModel::new(3, false)
+            .heuristics(true)
+            .max_depth(9)
+            .checker()
+            .spawn_bfs()
+            .join();
+        let report = CheckReport::from_checker(&checker);
+        assert_eq!(report.states, checker.state_count());
+        assert_eq!(report.unique_states, checker.unique_state_count());
+        assert!(!report.interrupted);
+
+        let mixed_outcome = report
+            .properties
+            .iter()
+            .find(|property| property.name == "no heuristic-mixed outcome")
+            .unwrap();
+        assert_eq!(mixed_outcome.expectation, "always");
+        assert!(!mixed_outcome.passed);
+        let discovery = mixed_outcome.discovery.as_ref().unwrap();
+        assert_eq!(
+            discovery.steps.len(),
+            checker
+                .discovery("no heuristic-mixed outcome")
+                .unwrap()
+                .into_actions()
+                .len()
+                + 1
+        );
+        assert!(discovery.validate(checker.model()).is_ok());
+
+        let global_rollback = report
+            .properties
+            .iter()
+            .find(|property| property.name == "global rollback")
+            .unwrap();
+        assert_eq!(global_rollback.expectation, "sometimes");
+        assert!(global_rollback.passed);
+
+        assert_eq!(
+            serde_json::from_str::<CheckReport>(&report.to_json()).unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    fn junit() {
+        let checker = TransactionModel::new(3, false)
+            .heuristics(true)
+            .max_depth(9)
+            .checker()
+            .spawn_bfs()
+            .join();
+        let report = CheckReport::from_checker(&checker);
+        let junit = report.to_junit();
+        let failures = report
+            .properties
+            .iter()
+            .filter(|property| !property.passed)
+            .count();
+        assert!(failures > 0);
+        assert!(junit.contains(&format!("failures=\"{failures}\"")));
+        assert!(junit.contains("<testcase name=\"no heuristic-mixed outcome\""));
+        assert!(junit.contains("<testcase name=\"global rollback\" classname"));
+        assert!(junit.contains("Fail(0): System {"));
+        assert_eq!(junit.matches("<failure ").count(), failures);
+        assert_eq!(
+            xml_escape("<a href=\"&\">'"),
+            "&lt;a href=&quot;&amp;&quot;&gt;&apos;"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! This is a set of helpers shared by the command-line tools.
+
+use std::time::Duration;
+
+/// Returns the value of an option.
+///
+/// # Errors
+///
+/// Returns why the value is missing.
+pub fn parse_value(value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| "a value is expected".to_string())
+}
+
+/// Parses a comma-separated list of node identifiers.
+///
+/// # Errors
+///
+/// Returns why the value is missing or is not a list of node identifiers.
+pub fn parse_node_ids(value: Option<String>) -> Result<Vec<usize>, String> {
+    parse_value(value)?
+        .split(',')
+        .map(|node_id| parse_number(Some(node_id.to_string())))
+        .collect()
+}
+
+/// Parses a non-negative number.
+///
+/// # Errors
+///
+/// Returns why the value is missing or is not a non-negative number.
+pub fn parse_number(value: Option<String>) -> Result<usize, String> {
+    let value = value.ok_or_else(|| "a number is expected".to_string())?;
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{value} is not a non-negative number"))
+}
+
+/// Parses a duration of seconds, minutes or hours, such as `90s`, `10m` or `2h`; a number without
+/// a unit is in seconds.
+///
+/// # Errors
+///
+/// Returns why the value is missing or is not a duration.
+pub fn parse_duration(value: Option<String>) -> Result<Duration, String> {
+    let value = value.ok_or_else(|| "a duration is expected".to_string())?;
+    let invalid = || format!("{value} is not a duration such as 90s, 10m or 2h");
+    let trimmed = value.trim();
+    let (number, seconds) = match trimmed.char_indices().last() {
+        Some((index, 's')) => (&trimmed[..index], 1),
+        Some((index, 'm')) => (&trimmed[..index], 60),
+        Some((index, 'h')) => (&trimmed[..index], 60 * 60),
+        _ => (trimmed, 1),
+    };
+    let number: u64 = number.trim().parse().map_err(|_| invalid())?;
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_duration, parse_node_ids, parse_number};
+
+    use std::time::Duration;
+
+    #[test]
+    fn duration() {
+        let parse = |value: &str| parse_duration(Some(value.to_string()));
+        assert_eq!(parse("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse("10m"), Ok(Duration::from_mins(10)));
+        assert_eq!(parse(" 2h "), Ok(Duration::from_hours(2)));
+        assert!(parse("m").is_err());
+        assert!(parse("1d").is_err());
+        assert!(parse("10x").is_err());
+        assert!(parse_duration(None).is_err());
+    }
+
+    #[test]
+    fn number() {
+        let parse = |value: &str| parse_number(Some(value.to_string()));
+        assert_eq!(parse(" 42 "), Ok(42));
+        assert!(parse("-1").is_err());
+        assert!(parse("abc").is_err());
+        assert!(parse_number(None).is_err());
+    }
+
+    #[test]
+    fn node_ids() {
+        let parse = |value: &str| parse_node_ids(Some(value.to_string()));
+        assert_eq!(parse("1, 2"), Ok(vec![1, 2]));
+        assert!(parse("1,x").is_err());
+        assert!(parse_node_ids(None).is_err());
+    }
+}
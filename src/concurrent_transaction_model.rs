@@ -0,0 +1,186 @@
+//! This is a model of concurrent distributed transactions sharing the same nodes.
+//!
+//! # Transactions
+//!
+//! Each transaction runs two-phase commit as in [`TransactionModel`] with its own coordinator and
+//! participants, and the steps of the transactions interleave arbitrarily.
+//!
+//! # Crashes
+//!
+//! The transactions share the nodes: a node crash hits all the transactions on the node at once,
+//! so that each of them recovers from the same crash independently.
+//!
+//! # Atomicity
+//!
+//! Atomicity is checked per transaction: the participants of each transaction agree on its
+//! outcome whatever the outcomes of the other transactions are.
+
+use super::transaction::State as TransactionState;
+use super::transaction_model::{Action, System, TransactionModel};
+
+use stateright::{Model, Property};
+
+/// [`ConcurrentSystem`] is the state of all the transactions.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConcurrentSystem {
+    /// The state of the nodes for each transaction.
+    transactions: Vec<System>,
+}
+
+/// [`ConcurrentAction`] is a state transition of [`ConcurrentSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ConcurrentAction {
+    /// A step of the transaction.
+    Step(usize, Action),
+
+    /// Crashes the node in all the transactions.
+    Crash(usize),
+}
+
+/// [`ConcurrentTransactionModel`] implements state transitions of concurrent transactions.
+#[derive(Hash)]
+pub struct ConcurrentTransactionModel {
+    /// The model of each transaction.
+    models: Vec<TransactionModel>,
+}
+
+impl ConcurrentTransactionModel {
+    /// Creates a new [`ConcurrentTransactionModel`] of the transactions over the same nodes.
+    #[must_use]
+    pub fn new(models: Vec<TransactionModel>) -> ConcurrentTransactionModel {
+        ConcurrentTransactionModel { models }
+    }
+
+    /// Creates a new [`ConcurrentTransactionModel`] of two-phase commit transactions over
+    /// `num_nodes` nodes, the transaction `i` being coordinated by the node `i % num_nodes`.
+    #[must_use]
+    pub fn with_transactions(num_transactions: usize, num_nodes: usize) -> Self {
+        Self::new(
+            (0..num_transactions)
+                .map(|index| TransactionModel::new(num_nodes, false).coordinator(index % num_nodes))
+                .collect(),
+        )
+    }
+
+    /// Returns the number of concurrent transactions.
+    #[must_use]
+    pub fn num_transactions(&self) -> usize {
+        self.models.len()
+    }
+
+    fn step(
+        &self,
+        last_state: &ConcurrentSystem,
+        index: usize,
+        action: Action,
+    ) -> Option<ConcurrentSystem> {
+        let next_transaction_state =
+            self.models[index].next_state(&last_state.transactions[index], action)?;
+        let mut state = last_state.clone();
+        state.transactions[index] = next_transaction_state;
+        Some(state)
+    }
+
+    fn crash(&self, last_state: &ConcurrentSystem, node_id: usize) -> ConcurrentSystem {
+        // A transaction in which the node cannot crash, e.g., because it has been shut down, is
+        // left unchanged.
+        ConcurrentSystem {
+            transactions: self
+                .models
+                .iter()
+                .zip(&last_state.transactions)
+                .map(|(model, state)| {
+                    model
+                        .next_state(state, Action::Crash(node_id))
+                        .unwrap_or_else(|| state.clone())
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Model for ConcurrentTransactionModel {
+    type State = ConcurrentSystem;
+    type Action = ConcurrentAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![ConcurrentSystem {
+            transactions: self
+                .models
+                .iter()
+                .filter_map(|model| model.init_states().into_iter().next())
+                .collect(),
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        let mut crashes = Vec::new();
+        for (index, (model, transaction)) in self.models.iter().zip(&state.transactions).enumerate()
+        {
+            let mut transaction_actions = Vec::new();
+            model.actions(transaction, &mut transaction_actions);
+            for action in transaction_actions {
+                match action {
+                    Action::Crash(node_id) => {
+                        if !crashes.contains(&node_id) {
+                            crashes.push(node_id);
+                        }
+                    }
+                    action => actions.push(ConcurrentAction::Step(index, action)),
+                }
+            }
+        }
+        actions.extend(crashes.into_iter().map(ConcurrentAction::Crash));
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            ConcurrentAction::Step(index, action) => self.step(last_state, index, action),
+            ConcurrentAction::Crash(node_id) => Some(self.crash(last_state, node_id)),
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("ACID of each transaction", |model, state| {
+                model
+                    .models
+                    .iter()
+                    .zip(&state.transactions)
+                    .all(|(model, state)| model.is_acid(state))
+            }),
+            Property::<Self>::sometimes("independent outcomes", |model, state| {
+                // A transaction commits while another one rolls back.
+                let outcomes = model
+                    .models
+                    .iter()
+                    .zip(&state.transactions)
+                    .map(|(model, state)| model.coordinator_state(state))
+                    .collect::<Vec<_>>();
+                outcomes.contains(&TransactionState::Committed)
+                    && outcomes.contains(&TransactionState::RolledBack)
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{ConcurrentTransactionModel, Model};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn concurrent_transactions() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = ConcurrentTransactionModel::with_transactions(2, 2)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
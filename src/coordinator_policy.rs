@@ -0,0 +1,150 @@
+//! This is a set of decision policies of the coordinator of the two-phase commit model.
+//!
+//! # Policies
+//!
+//! A policy decides when the coordinator starts the voting phase, whether it may roll back the
+//! transaction although the participants have voted for commit, and whether it gives up on the
+//! participants that have not voted yet; the model uses [`Permissive`] unless told otherwise,
+//! which explores every decision that the protocol allows.
+//!
+//! A coordinator that rolls back despite unanimous yes votes is still correct, but it makes the
+//! commit depend on the policy as well as on the participants.
+
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+/// [`CoordinatorPolicy`] decides how the coordinator drives the protocol.
+///
+/// The [`Debug`] representation of a policy identifies it along with its parameters, and is
+/// hashed into the fingerprint of the model.
+pub trait CoordinatorPolicy: Debug + Send + Sync {
+    /// Returns the name of the policy.
+    fn name(&self) -> &'static str;
+
+    /// Returns `true` if the coordinator may start the voting phase once `joined` of the
+    /// `participants` have joined the transaction.
+    fn starts_voting(&self, joined: usize, participants: usize) -> bool {
+        let _ = (joined, participants);
+        true
+    }
+
+    /// Returns `true` if the coordinator may roll back the transaction after the participants have
+    /// voted for commit.
+    fn may_abort_despite_yes_votes(&self) -> bool {
+        true
+    }
+
+    /// Returns `true` if the coordinator may roll back the transaction once `acked` of its
+    /// `participants` have voted for commit, while it is waiting for the others.
+    fn may_abort_on_partial_acks(&self, acked: usize, participants: usize) -> bool {
+        let _ = (acked, participants);
+        false
+    }
+}
+
+impl Hash for dyn CoordinatorPolicy {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        format!("{self:?}").hash(state);
+    }
+}
+
+/// Returns the coordinator policy of the name.
+#[must_use]
+pub fn by_name(name: &str) -> Option<Box<dyn CoordinatorPolicy>> {
+    match name {
+        "permissive" => Some(Box::new(Permissive)),
+        "strict" => Some(Box::new(Strict)),
+        "impatient" => Some(Box::new(Impatient)),
+        _ => None,
+    }
+}
+
+/// [`Permissive`] starts voting at any time, may roll back despite unanimous yes votes, and keeps
+/// requesting the missing votes.
+#[derive(Debug)]
+pub struct Permissive;
+
+impl CoordinatorPolicy for Permissive {
+    fn name(&self) -> &'static str {
+        "permissive"
+    }
+}
+
+/// [`Strict`] starts voting only once all the participants have joined, and commits whenever they
+/// have voted for commit.
+#[derive(Debug)]
+pub struct Strict;
+
+impl CoordinatorPolicy for Strict {
+    fn name(&self) -> &'static str {
+        "strict"
+    }
+
+    fn starts_voting(&self, joined: usize, participants: usize) -> bool {
+        joined == participants
+    }
+
+    fn may_abort_despite_yes_votes(&self) -> bool {
+        false
+    }
+}
+
+/// [`Impatient`] commits whenever the participants have voted for commit, but rolls back as soon
+/// as some of them have voted while the others have not.
+#[derive(Debug)]
+pub struct Impatient;
+
+impl CoordinatorPolicy for Impatient {
+    fn name(&self) -> &'static str {
+        "impatient"
+    }
+
+    fn may_abort_despite_yes_votes(&self) -> bool {
+        false
+    }
+
+    fn may_abort_on_partial_acks(&self, acked: usize, participants: usize) -> bool {
+        acked > 0 && acked < participants
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CoordinatorPolicy;
+
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    /// Rolls back once `patience` participants have voted while the others have not.
+    #[derive(Debug)]
+    struct Patient {
+        patience: usize,
+    }
+
+    impl CoordinatorPolicy for Patient {
+        fn name(&self) -> &'static str {
+            "patient"
+        }
+
+        fn may_abort_on_partial_acks(&self, acked: usize, participants: usize) -> bool {
+            acked >= self.patience && acked < participants
+        }
+    }
+
+    #[test]
+    fn hash_parameters() {
+        let hash = |policy: Box<dyn CoordinatorPolicy>| {
+            let mut hasher = DefaultHasher::new();
+            policy.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_ne!(
+            hash(Box::new(Patient { patience: 1 })),
+            hash(Box::new(Patient { patience: 2 }))
+        );
+        assert_eq!(
+            hash(Box::new(Patient { patience: 1 })),
+            hash(Box::new(Patient { patience: 1 }))
+        );
+    }
+}
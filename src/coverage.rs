@@ -0,0 +1,191 @@
+//! This is a coverage analysis of the reachable state space of a model.
+//!
+//! Every reachable state is visited once, and every step from it is counted by the variant of its
+//! action, along with the steps that change the state; a variant that is never taken, or never
+//! changes the state, is a dead path of the model. The combinations of the transaction states of
+//! the nodes are counted as well, so that a transaction state that no node ever reaches stands out.
+
+use super::state_graph::fingerprint;
+use super::transaction::State as TransactionState;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use stateright::Model;
+
+/// All the transaction states in the order of the protocol.
+const TRANSACTION_STATES: [TransactionState; 6] = [
+    TransactionState::Inactive,
+    TransactionState::Active,
+    TransactionState::Prepared,
+    TransactionState::PreCommitted,
+    TransactionState::Committed,
+    TransactionState::RolledBack,
+];
+
+/// [`Coverage`] is implemented by models whose coverage is analyzed.
+pub trait Coverage: Model {
+    /// Returns the names of all the variants of the actions.
+    fn action_variants(&self) -> &'static [&'static str];
+
+    /// Returns the transaction states of the nodes.
+    fn node_states(&self, state: &Self::State) -> Vec<TransactionState>;
+}
+
+/// [`CoverageReport`] is the coverage of the reachable state space of a model.
+#[derive(Debug, Eq, PartialEq)]
+pub struct CoverageReport {
+    /// The number of reachable states.
+    pub states: usize,
+
+    /// The coverage of each variant of the actions, in the order of the model; a variant that the
+    /// model does not list is appended.
+    pub actions: Vec<ActionCoverage>,
+
+    /// The reached combinations of the transaction states of the nodes, and the number of states
+    /// in which they are reached, in the order in which they are first reached.
+    pub node_states: Vec<(Vec<TransactionState>, usize)>,
+}
+
+/// [`ActionCoverage`] is the number of steps taken by a variant of the actions.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ActionCoverage {
+    /// The name of the variant.
+    pub variant: String,
+
+    /// The number of steps taken.
+    pub steps: usize,
+
+    /// The number of steps that changed the state.
+    pub state_changes: usize,
+}
+
+impl CoverageReport {
+    /// Returns the transaction states that no node reaches.
+    #[must_use]
+    pub fn unreached_states(&self) -> Vec<TransactionState> {
+        TRANSACTION_STATES
+            .into_iter()
+            .filter(|state| {
+                self.node_states
+                    .iter()
+                    .all(|(states, _)| !states.contains(state))
+            })
+            .collect()
+    }
+}
+
+/// Analyzes the coverage of the reachable state space of the model.
+pub fn analyze<M: Coverage>(model: &M) -> CoverageReport
+where
+    M::Action: Debug,
+    M::State: Hash,
+{
+    let mut report = CoverageReport {
+        states: 0,
+        actions: model
+            .action_variants()
+            .iter()
+            .map(|variant| ActionCoverage {
+                variant: (*variant).to_string(),
+                steps: 0,
+                state_changes: 0,
+            })
+            .collect(),
+        node_states: Vec::new(),
+    };
+    let mut combinations: HashMap<Vec<TransactionState>, usize> = HashMap::new();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<M::State> = model
+        .init_states()
+        .into_iter()
+        .filter(|state| model.within_boundary(state) && visited.insert(fingerprint(state)))
+        .collect();
+    while let Some(state) = queue.pop_front() {
+        report.states += 1;
+        let node_states = model.node_states(&state);
+        if let Some(index) = combinations.get(&node_states) {
+            report.node_states[*index].1 += 1;
+        } else {
+            combinations.insert(node_states.clone(), report.node_states.len());
+            report.node_states.push((node_states, 1));
+        }
+
+        let key = fingerprint(&state);
+        for (action, next_state) in model.next_steps(&state) {
+            if !model.within_boundary(&next_state) {
+                continue;
+            }
+            let action = format!("{action:?}");
+            let variant = action
+                .split(|c: char| !c.is_alphanumeric())
+                .next()
+                .unwrap_or_default();
+            let index = report
+                .actions
+                .iter()
+                .position(|coverage| coverage.variant == variant)
+                .unwrap_or_else(|| {
+                    report.actions.push(ActionCoverage {
+                        variant: variant.to_string(),
+                        steps: 0,
+                        state_changes: 0,
+                    });
+                    report.actions.len() - 1
+                });
+            report.actions[index].steps += 1;
+            let next_key = fingerprint(&next_state);
+            if next_key != key {
+                report.actions[index].state_changes += 1;
+            }
+            if visited.insert(next_key) {
+                queue.push_back(next_state);
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::{analyze, Coverage};
+    use crate::transaction::State;
+    use crate::transaction_model::TransactionModel;
+
+    use stateright::{Checker, Model};
+
+    #[test]
+    fn analyze_coverage() {
+        let model = TransactionModel::new(2, false).crash_budget(0);
+        let report = analyze(&model);
+        assert_eq!(
+            report.states,
+            TransactionModel::new(2, false)
+                .crash_budget(0)
+                .checker()
+                .spawn_bfs()
+                .join()
+                .unique_state_count()
+        );
+
+        // Every variant that is taken is listed by the model.
+        assert_eq!(report.actions.len(), model.action_variants().len());
+        let coverage = |variant| {
+            report
+                .actions
+                .iter()
+                .find(|coverage| coverage.variant == variant)
+                .unwrap()
+        };
+        assert!(coverage("Commit").state_changes > 0);
+        assert_eq!(coverage("Crash").steps, 0);
+
+        assert_eq!(report.node_states[0], (vec![State::Inactive; 2], 1));
+        assert!(report
+            .node_states
+            .iter()
+            .any(|(states, _)| states == &vec![State::Committed; 2]));
+        assert!(report.unreached_states().contains(&State::PreCommitted));
+    }
+}
@@ -0,0 +1,77 @@
+//! This is an exporter of the state graph as a Graphviz digraph.
+//!
+//! Each state of the [`StateGraph`] is a node labeled by the caller, and each step is an edge
+//! labeled by its action; the initial states are drawn with a double border. The digraph can be
+//! rendered with `dot -Tsvg`, which is practical only for small model configurations.
+
+use super::state_graph::StateGraph;
+
+use std::fmt::Write;
+
+use stateright::Model;
+
+/// Renders the state graph as a Graphviz digraph, labeling each state with `label`.
+pub fn render<M: Model>(graph: &StateGraph<M>, label: impl Fn(&M::State) -> String) -> String {
+    let mut dot = String::from("digraph {\n");
+    if graph.truncated {
+        dot.push_str("    // Truncated: not all the reachable states are included.\n");
+    }
+    for (index, state) in graph.states.iter().enumerate() {
+        let _ = write!(dot, "    s{index} [label={}", dot_string(&label(state)));
+        if graph.init_states.contains(&index) {
+            dot.push_str(", peripheries=2");
+        }
+        dot.push_str("];\n");
+    }
+    for (index, edges) in graph.edges.iter().enumerate() {
+        for (action, next_index) in edges {
+            let _ = writeln!(
+                dot,
+                "    s{index} -> s{next_index} [label={}];",
+                dot_string(action)
+            );
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Encodes the string as a quoted DOT string, with a line break for each newline.
+fn dot_string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{dot_string, render};
+    use crate::lock_model::{LockModel, PreemptionPolicy};
+    use crate::state_graph::StateGraph;
+
+    #[test]
+    fn escape() {
+        assert_eq!(dot_string("a\"b\\c\nd"), "\"a\\\"b\\\\c\\nd\"");
+    }
+
+    #[test]
+    fn digraph() {
+        let model = LockModel::new(2, 2, PreemptionPolicy::WaitDie);
+        let graph = StateGraph::build(&model, 100);
+        let dot = render(&graph, |_| "state".to_string());
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("s0 [label=\"state\", peripheries=2];"));
+        let edges: usize = graph.edges.iter().map(Vec::len).sum();
+        assert_eq!(dot.matches(" -> ").count(), edges);
+    }
+}
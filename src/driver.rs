@@ -0,0 +1,349 @@
+//! This is a driver that executes two-phase commit over local sockets.
+//!
+//! # Nodes
+//!
+//! Each node runs in its own thread with a UDP socket bound to a local port, and drives its
+//! [`Transaction`] by the messages that it receives. The coordinator waits until all the
+//! participants have joined and started the transaction, requests their votes, retransmits the
+//! prepare requests that are not answered in time, and sends the decision once it has received all
+//! the votes. The commit message carries the commit timestamp generated by the coordinator.
+//!
+//! # Execution log
+//!
+//! Every state transition of a node is recorded in a shared log as the [`Action`] of the
+//! [`TransactionModel`](super::transaction_model::TransactionModel) that it implements, in the
+//! order in which the transitions take place, so that the execution can be validated against the
+//! model. Messages that only the implementation needs, such as the confirmation that a
+//! participant has started the transaction, are not recorded.
+//!
+//! # Fault plan
+//!
+//! A [`FaultPlan`] makes participants vote against commit, and drops the first prepare request
+//! sent to participants.
+
+use super::transaction::Transaction;
+use super::transaction_model::Action;
+
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// The time after which the coordinator retransmits the unanswered prepare requests.
+const RETRANSMISSION_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// The commit timestamp that the coordinator generates for the only transaction.
+const COMMIT_TIMESTAMP: usize = 1;
+
+/// The time after which a participant gives up waiting for the coordinator.
+const PARTICIPANT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// [`FaultPlan`] describes the faults injected into an execution.
+#[derive(Clone, Debug, Default)]
+pub struct FaultPlan {
+    /// The participants that vote against commit.
+    pub vote_no: Vec<usize>,
+
+    /// The participants whose first prepare request is dropped.
+    pub drop_prepare: Vec<usize>,
+}
+
+/// [`Message`] is a message exchanged between the nodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Message {
+    /// The participant asks to join the transaction.
+    Join(usize),
+
+    /// The coordinator acknowledges the join request.
+    AckJoin,
+
+    /// The participant has started the transaction.
+    Started(usize),
+
+    /// The coordinator requests the vote of the participant.
+    Prepare,
+
+    /// The participant votes; `true` for commit.
+    Vote(usize, bool),
+
+    /// The coordinator has committed the transaction with the commit timestamp.
+    Commit(usize),
+
+    /// The coordinator has rolled back the transaction.
+    Rollback,
+}
+
+impl Message {
+    fn encode(self) -> String {
+        match self {
+            Message::Join(node_id) => format!("join {node_id}"),
+            Message::AckJoin => "ack-join".to_string(),
+            Message::Started(node_id) => format!("started {node_id}"),
+            Message::Prepare => "prepare".to_string(),
+            Message::Vote(node_id, commit) => format!("vote {node_id} {commit}"),
+            Message::Commit(timestamp) => format!("commit {timestamp}"),
+            Message::Rollback => "rollback".to_string(),
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Message> {
+        let text = std::str::from_utf8(bytes).ok()?;
+        let mut words = text.split(' ');
+        let message = match words.next()? {
+            "join" => Message::Join(words.next()?.parse().ok()?),
+            "ack-join" => Message::AckJoin,
+            "started" => Message::Started(words.next()?.parse().ok()?),
+            "prepare" => Message::Prepare,
+            "vote" => Message::Vote(words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+            "commit" => Message::Commit(words.next()?.parse().ok()?),
+            "rollback" => Message::Rollback,
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+/// [`Endpoint`] is the socket of a node.
+struct Endpoint {
+    /// The socket bound to a local port.
+    socket: UdpSocket,
+
+    /// The execution log shared by all the nodes.
+    log: Arc<Mutex<Vec<Action>>>,
+}
+
+impl Endpoint {
+    fn bind(log: &Arc<Mutex<Vec<Action>>>) -> io::Result<Endpoint> {
+        Ok(Endpoint {
+            socket: UdpSocket::bind("127.0.0.1:0")?,
+            log: log.clone(),
+        })
+    }
+
+    fn send(&self, message: Message, address: SocketAddr) -> io::Result<()> {
+        self.socket
+            .send_to(message.encode().as_bytes(), address)
+            .map(|_| ())
+    }
+
+    fn receive(&self) -> io::Result<(Message, SocketAddr)> {
+        let mut buffer = [0; 64];
+        loop {
+            let (length, address) = self.socket.recv_from(&mut buffer)?;
+            if let Some(message) = Message::decode(&buffer[..length]) {
+                return Ok((message, address));
+            }
+        }
+    }
+
+    /// Applies the transition to the transaction, and records the action if it changes the state
+    /// of the transaction.
+    fn transition<F: FnOnce(&mut Transaction) -> bool>(
+        &self,
+        transaction: &mut Transaction,
+        action: Action,
+        f: F,
+    ) {
+        let mut log = self.log.lock().unwrap_or_else(PoisonError::into_inner);
+        if f(transaction) {
+            log.push(action);
+        }
+    }
+}
+
+/// Executes a transaction over local sockets, and returns the execution log.
+///
+/// # Errors
+///
+/// Returns an error if a socket cannot be used, or a participant times out.
+pub fn run(num_nodes: usize, fault_plan: &FaultPlan) -> io::Result<Vec<Action>> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let coordinator = Endpoint::bind(&log)?;
+    let coordinator_address = coordinator.socket.local_addr()?;
+    let participants = (1..num_nodes)
+        .map(|node_id| {
+            let endpoint = Endpoint::bind(&log)?;
+            let vote = !fault_plan.vote_no.contains(&node_id);
+            Ok(thread::spawn(move || {
+                run_participant(&endpoint, node_id, vote, coordinator_address)
+            }))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    run_coordinator(&coordinator, num_nodes, fault_plan)?;
+    for participant in participants {
+        participant
+            .join()
+            .map_err(|_| io::Error::other("a participant panicked"))??;
+    }
+    let log = log.lock().unwrap_or_else(PoisonError::into_inner);
+    Ok(log.clone())
+}
+
+fn run_coordinator(
+    endpoint: &Endpoint,
+    num_nodes: usize,
+    fault_plan: &FaultPlan,
+) -> io::Result<()> {
+    let mut transaction = Transaction::default();
+    endpoint.transition(&mut transaction, Action::Start(0), Transaction::start);
+
+    // Wait until all the participants have joined and started the transaction.
+    let mut addresses = vec![None; num_nodes];
+    let mut started = vec![false; num_nodes];
+    while started.iter().skip(1).any(|started| !started) {
+        match endpoint.receive()? {
+            (Message::Join(node_id), address) if node_id < num_nodes => {
+                endpoint.transition(&mut transaction, Action::RequestJoin(node_id), |t| {
+                    t.add_participant(node_id)
+                });
+                addresses[node_id] = Some(address);
+                endpoint.send(Message::AckJoin, address)?;
+            }
+            (Message::Started(node_id), _) if node_id < num_nodes => started[node_id] = true,
+            _ => (),
+        }
+    }
+    let addresses = addresses.into_iter().flatten().collect::<Vec<_>>();
+
+    // Request the votes, and retransmit the requests that are not answered in time.
+    endpoint.transition(
+        &mut transaction,
+        Action::RequestPrepare(0),
+        Transaction::prepare,
+    );
+    let mut votes = Vec::new();
+    endpoint
+        .socket
+        .set_read_timeout(Some(RETRANSMISSION_TIMEOUT))?;
+    for (node_id, address) in (1..num_nodes).zip(&addresses) {
+        if !fault_plan.drop_prepare.contains(&node_id) {
+            endpoint.send(Message::Prepare, *address)?;
+        }
+    }
+    while votes.len() + 1 < num_nodes {
+        match endpoint.receive() {
+            Ok((Message::Vote(node_id, commit), _)) => {
+                if !votes.iter().any(|(voter, _)| *voter == node_id) {
+                    votes.push((node_id, commit));
+                }
+            }
+            Ok(_) => (),
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                for (node_id, address) in (1..num_nodes).zip(&addresses) {
+                    if !votes.iter().any(|(voter, _)| *voter == node_id) {
+                        endpoint.send(Message::Prepare, *address)?;
+                    }
+                }
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    // Count the votes only after all the participants have received the prepare request.
+    for (node_id, commit) in votes {
+        if commit {
+            endpoint.transition(&mut transaction, Action::AckPrepare(node_id), |t| {
+                t.report_prepared(node_id)
+            });
+        } else {
+            endpoint.transition(
+                &mut transaction,
+                Action::AckPrepareFail(node_id),
+                Transaction::rollback,
+            );
+        }
+    }
+    let decision = if transaction.is_all_prepared() {
+        endpoint.transition(
+            &mut transaction,
+            Action::Commit(0, COMMIT_TIMESTAMP),
+            Transaction::commit,
+        );
+        Message::Commit(COMMIT_TIMESTAMP)
+    } else {
+        Message::Rollback
+    };
+    for address in addresses {
+        endpoint.send(decision, address)?;
+    }
+    Ok(())
+}
+
+fn run_participant(
+    endpoint: &Endpoint,
+    node_id: usize,
+    vote: bool,
+    coordinator_address: SocketAddr,
+) -> io::Result<()> {
+    let mut transaction = Transaction::default();
+    endpoint
+        .socket
+        .set_read_timeout(Some(PARTICIPANT_TIMEOUT))?;
+    endpoint.send(Message::Join(node_id), coordinator_address)?;
+    loop {
+        match endpoint.receive()?.0 {
+            Message::AckJoin => {
+                endpoint.transition(
+                    &mut transaction,
+                    Action::AckJoin(node_id),
+                    Transaction::start,
+                );
+                endpoint.send(Message::Started(node_id), coordinator_address)?;
+            }
+            Message::Prepare => {
+                endpoint.transition(
+                    &mut transaction,
+                    Action::RequestPrepare(node_id),
+                    Transaction::prepare,
+                );
+                endpoint.send(Message::Vote(node_id, vote), coordinator_address)?;
+            }
+            Message::Commit(timestamp) => {
+                endpoint.transition(
+                    &mut transaction,
+                    Action::Commit(node_id, timestamp),
+                    Transaction::commit,
+                );
+                return Ok(());
+            }
+            Message::Rollback => {
+                endpoint.transition(
+                    &mut transaction,
+                    Action::Rollback(node_id),
+                    Transaction::rollback,
+                );
+                return Ok(());
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, FaultPlan};
+    use crate::trace::Trace;
+    use crate::transaction_model::{Action, TransactionModel};
+
+    #[test]
+    fn admissible() {
+        let model = TransactionModel::new(3, false);
+        let log = run(3, &FaultPlan::default()).unwrap();
+        assert!(log.contains(&Action::Commit(0, 1)));
+        assert!(Trace::replay(&model, log).is_ok());
+
+        let fault_plan = FaultPlan {
+            vote_no: vec![1],
+            drop_prepare: vec![2],
+        };
+        let log = run(3, &fault_plan).unwrap();
+        assert!(log.contains(&Action::AckPrepareFail(1)));
+        assert!(Trace::replay(&model, log).is_ok());
+    }
+}
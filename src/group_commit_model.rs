@@ -0,0 +1,271 @@
+//! This is a model of group commit at the transaction coordinator.
+//!
+//! # Decisions
+//!
+//! The coordinator runs a number of concurrent distributed transactions whose participants have
+//! all voted for commit, and decides to commit or roll back each of them independently. A decision
+//! is appended to the in-memory log buffer of the coordinator, and is not durable until the log is
+//! forced to the disk.
+//!
+//! # Group commit
+//!
+//! A single log force makes all the buffered decisions durable at once, and the decisions are
+//! released to the participants only after the covering force. The coordinator may crash before
+//! forcing the log, losing the buffered decisions; it then decides the transactions again.
+
+use super::state_graph::StateGraph;
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// [`GroupCommitSystem`] is the state of the coordinator and its log.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GroupCommitSystem {
+    /// The transactions of the coordinator.
+    transactions: Vec<GroupTransaction>,
+
+    /// The number of log forces.
+    forces: usize,
+
+    /// The coordinator has crashed once.
+    crashed: bool,
+}
+
+/// [`GroupTransaction`] is a transaction of the coordinator.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct GroupTransaction {
+    /// The coordinator transaction.
+    transaction: Transaction,
+
+    /// The decision has been forced to the log.
+    durable: bool,
+
+    /// The decision released to the participants.
+    released: Option<TransactionState>,
+}
+
+impl GroupTransaction {
+    /// Returns `true` if the decision is buffered in memory, and not yet forced to the log.
+    fn is_buffered(&self) -> bool {
+        self.transaction.state().is_terminal() && !self.durable
+    }
+}
+
+/// [`GroupCommitAction`] is a state transition of [`GroupCommitSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum GroupCommitAction {
+    /// The coordinator decides to commit the transaction.
+    Commit(usize),
+
+    /// The coordinator decides to roll back the transaction.
+    Rollback(usize),
+
+    /// The coordinator forces the log, making all the buffered decisions durable.
+    Force,
+
+    /// The coordinator sends the decision to the participants of the transaction.
+    Release(usize),
+
+    /// The coordinator crashes, and loses the buffered decisions.
+    Crash,
+}
+
+/// [`GroupCommitModel`] implements state transitions of a coordinator using group commit.
+#[derive(Hash)]
+pub struct GroupCommitModel {
+    /// The number of concurrent transactions.
+    num_transactions: usize,
+}
+
+impl GroupCommitModel {
+    /// Creates a new [`GroupCommitModel`].
+    #[must_use]
+    pub fn new(num_transactions: usize) -> GroupCommitModel {
+        GroupCommitModel { num_transactions }
+    }
+
+    /// Returns the number of concurrent transactions.
+    #[must_use]
+    pub fn num_transactions(&self) -> usize {
+        self.num_transactions
+    }
+
+    /// Returns the minimum number of log forces needed to release the decisions of all the
+    /// transactions, or `None` if they are never released.
+    ///
+    /// Without group commit, each decision needs its own log force.
+    #[must_use]
+    pub fn min_log_forces(&self) -> Option<usize> {
+        StateGraph::build(self, usize::MAX)
+            .states
+            .iter()
+            .filter(|state| {
+                state
+                    .transactions
+                    .iter()
+                    .all(|transaction| transaction.released.is_some())
+            })
+            .map(|state| state.forces)
+            .min()
+    }
+
+    fn decide(
+        last_state: &GroupCommitSystem,
+        index: usize,
+        commit: bool,
+    ) -> Option<GroupCommitSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.transactions[index].transaction;
+        let decided = if commit {
+            transaction.commit()
+        } else {
+            transaction.rollback()
+        };
+        decided.then_some(state)
+    }
+
+    fn force(last_state: &GroupCommitSystem) -> GroupCommitSystem {
+        let mut state = last_state.clone();
+        state.forces += 1;
+        for transaction in &mut state.transactions {
+            if transaction.is_buffered() {
+                transaction.durable = true;
+            }
+        }
+        state
+    }
+
+    fn release(last_state: &GroupCommitSystem, index: usize) -> Option<GroupCommitSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.transactions[index];
+        if !transaction.durable || transaction.released.is_some() {
+            return None;
+        }
+        transaction.released = Some(transaction.transaction.state());
+        Some(state)
+    }
+
+    fn crash(last_state: &GroupCommitSystem) -> GroupCommitSystem {
+        let mut state = last_state.clone();
+        state.crashed = true;
+        for transaction in &mut state.transactions {
+            if transaction.is_buffered() {
+                // The participants are still prepared, and the coordinator decides again.
+                transaction.transaction = Self::prepared_transaction();
+            }
+        }
+        state
+    }
+
+    fn prepared_transaction() -> Transaction {
+        let mut transaction = Transaction::default();
+        transaction.start();
+        transaction.prepare();
+        transaction
+    }
+}
+
+impl Model for GroupCommitModel {
+    type State = GroupCommitSystem;
+    type Action = GroupCommitAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![GroupCommitSystem {
+            transactions: vec![
+                GroupTransaction {
+                    transaction: Self::prepared_transaction(),
+                    durable: false,
+                    released: None,
+                };
+                self.num_transactions
+            ],
+            forces: 0,
+            crashed: false,
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for (index, transaction) in state.transactions.iter().enumerate() {
+            match transaction.transaction.state() {
+                TransactionState::Prepared => {
+                    actions.push(GroupCommitAction::Commit(index));
+                    actions.push(GroupCommitAction::Rollback(index));
+                }
+                TransactionState::Committed | TransactionState::RolledBack
+                    if transaction.durable && transaction.released.is_none() =>
+                {
+                    actions.push(GroupCommitAction::Release(index));
+                }
+                _ => (),
+            }
+        }
+        if state.transactions.iter().any(GroupTransaction::is_buffered) {
+            actions.push(GroupCommitAction::Force);
+            if !state.crashed {
+                actions.push(GroupCommitAction::Crash);
+            }
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            GroupCommitAction::Commit(index) => Self::decide(last_state, index, true),
+            GroupCommitAction::Rollback(index) => Self::decide(last_state, index, false),
+            GroupCommitAction::Force => Some(Self::force(last_state)),
+            GroupCommitAction::Release(index) => Self::release(last_state, index),
+            GroupCommitAction::Crash => Some(Self::crash(last_state)),
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("no release before force", |_, state| {
+                // A decision known to the participants is durable, and survives crashes of the
+                // coordinator.
+                state.transactions.iter().all(|transaction| {
+                    transaction.released.is_none_or(|decision| {
+                        transaction.durable && transaction.transaction.state() == decision
+                    })
+                })
+            }),
+            Property::<Self>::sometimes("group commit", |_, state| {
+                // A single log force covers several decisions.
+                state.forces == 1
+                    && state
+                        .transactions
+                        .iter()
+                        .filter(|transaction| transaction.durable)
+                        .count()
+                        > 1
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{GroupCommitModel, Model};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn group_commit() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = GroupCommitModel::new(3)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn log_forces() {
+        let model = GroupCommitModel::new(3);
+        assert_eq!(model.min_log_forces(), Some(1));
+    }
+}
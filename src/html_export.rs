@@ -0,0 +1,198 @@
+//! This is an exporter of a self-contained static HTML state-space browser.
+//!
+//! The exported page embeds the precomputed [`StateGraph`] together with the evaluation of the
+//! model properties in each state, and navigates the graph on the client side; it can be browsed
+//! offline without the explorer web server.
+
+use super::state_graph::StateGraph;
+
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+use stateright::Model;
+
+/// Renders the state graph of the model as a self-contained HTML page.
+pub fn render<M: Model>(model: &M, graph: &StateGraph<M>) -> String
+where
+    M::State: Debug + Hash,
+    M::Action: Debug,
+{
+    let properties = model.properties();
+    let mut data = String::from("{\"properties\":[");
+    for (i, property) in properties.iter().enumerate() {
+        if i != 0 {
+            data.push(',');
+        }
+        let _ = write!(
+            data,
+            "[{},{}]",
+            json_string(property.name),
+            json_string(&format!("{:?}", property.expectation))
+        );
+    }
+    data.push_str("],\"init\":");
+    let _ = write!(data, "{:?}", graph.init_states);
+    let _ = write!(data, ",\"truncated\":{},\"states\":[", graph.truncated);
+    for (index, state) in graph.states.iter().enumerate() {
+        if index != 0 {
+            data.push(',');
+        }
+        let _ = write!(
+            data,
+            "{{\"label\":{},\"holds\":[",
+            json_string(&format!("{state:#?}"))
+        );
+        for (i, property) in properties.iter().enumerate() {
+            if i != 0 {
+                data.push(',');
+            }
+            data.push_str(if (property.condition)(model, state) {
+                "true"
+            } else {
+                "false"
+            });
+        }
+        data.push_str("],\"next\":[");
+        for (i, (action, next_index)) in graph.edges[index].iter().enumerate() {
+            if i != 0 {
+                data.push(',');
+            }
+            let _ = write!(data, "[{},{next_index}]", json_string(action));
+        }
+        data.push_str("]}");
+    }
+    data.push_str("]}");
+    TEMPLATE.replace("/*DATA*/", &data)
+}
+
+/// Encodes the string as a JSON string literal that can be embedded in a script element.
+fn json_string(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len() + 2);
+    encoded.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => encoded.push_str("\\\""),
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '<' | '>' | '&' => {
+                let _ = write!(encoded, "\\u{:04x}", u32::from(c));
+            }
+            c if c.is_control() => {
+                let _ = write!(encoded, "\\u{:04x}", u32::from(c));
+            }
+            c => encoded.push(c),
+        }
+    }
+    encoded.push('"');
+    encoded
+}
+
+/// The page template; `/*DATA*/` is replaced with the state graph.
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>State-space browser</title>
+<style>
+body { font-family: sans-serif; margin: 1em 2em; }
+pre { background: #f4f4f4; padding: 1em; overflow: auto; }
+button { display: block; margin: 0.2em 0; font-family: monospace; }
+.fail { color: #b00; }
+.pass { color: #070; }
+</style>
+</head>
+<body>
+<h1>State-space browser</h1>
+<p id="summary"></p>
+<h2>Path</h2>
+<div id="path"></div>
+<h2>State</h2>
+<pre id="state"></pre>
+<h2>Properties</h2>
+<ul id="properties"></ul>
+<h2>Actions</h2>
+<div id="actions"></div>
+<script>
+const graph = /*DATA*/;
+let path = [];
+
+function render() {
+  const summary = document.getElementById("summary");
+  summary.textContent = graph.states.length + " states" +
+    (graph.truncated ? " (truncated: not all the reachable states are included)" : "");
+
+  const pathView = document.getElementById("path");
+  pathView.innerHTML = "";
+  const restart = document.createElement("button");
+  restart.textContent = "(initial states)";
+  restart.onclick = () => { path = []; render(); };
+  pathView.appendChild(restart);
+  path.forEach((step, i) => {
+    const button = document.createElement("button");
+    button.textContent = step.action;
+    button.onclick = () => { path = path.slice(0, i + 1); render(); };
+    pathView.appendChild(button);
+  });
+
+  const current = path.length ? path[path.length - 1].state : null;
+  const next = current === null
+    ? graph.init.map((index, i) => ["Init " + i, index])
+    : graph.states[current].next;
+
+  document.getElementById("state").textContent =
+    current === null ? "(choose an initial state)" : graph.states[current].label;
+
+  const properties = document.getElementById("properties");
+  properties.innerHTML = "";
+  if (current !== null) {
+    graph.properties.forEach(([name, expectation], i) => {
+      const item = document.createElement("li");
+      const holds = graph.states[current].holds[i];
+      item.className = holds ? "pass" : "fail";
+      item.textContent = expectation + " \"" + name + "\": " + (holds ? "holds" : "does not hold");
+      properties.appendChild(item);
+    });
+  }
+
+  const actions = document.getElementById("actions");
+  actions.innerHTML = "";
+  next.forEach(([action, state]) => {
+    const button = document.createElement("button");
+    button.textContent = action;
+    button.onclick = () => { path.push({ action, state }); render(); };
+    actions.appendChild(button);
+  });
+  if (!next.length) {
+    actions.textContent = "(no actions)";
+  }
+}
+
+render();
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod test {
+    use super::{json_string, render};
+    use crate::lock_model::{LockModel, PreemptionPolicy};
+    use crate::state_graph::StateGraph;
+
+    #[test]
+    fn escape() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_string("</script>"), "\"\\u003c/script\\u003e\"");
+    }
+
+    #[test]
+    fn self_contained() {
+        let model = LockModel::new(2, 2, PreemptionPolicy::WaitDie);
+        let graph = StateGraph::build(&model, 100);
+        let html = render(&model, &graph);
+        assert!(html.contains("\"init\":[0]"));
+        assert!(html.contains("deadlock free"));
+        assert!(!html.contains("/*DATA*/"));
+        assert!(!html.contains("src="));
+    }
+}
@@ -0,0 +1,192 @@
+//! This is a file format of extra invariants of the transaction model.
+//!
+//! # Format
+//!
+//! An invariants file is a JSON array of named invariants, each of which has a condition over the
+//! transaction states of the nodes that has to hold in every reachable state. For example, the
+//! following invariant forbids the coordinator from staying prepared once participant 1 has
+//! committed:
+//!
+//! ```json
+//! [
+//!     {
+//!         "name": "coordinator decides first",
+//!         "condition": {
+//!             "not": {
+//!                 "all": [
+//!                     { "node": { "node": 0, "state": "Prepared" } },
+//!                     { "node": { "node": 1, "state": "Committed" } }
+//!                 ]
+//!             }
+//!         }
+//!     }
+//! ]
+//! ```
+//!
+//! A condition is one of `node`, `any_node` and `every_node`, which compare the transaction state
+//! of a node, some node and every node with a state, combined with `not`, `all` and `any`.
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use super::transaction::State as TransactionState;
+
+/// [`Invariant`] is a named condition that has to hold in every reachable state.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Invariant {
+    /// The name of the invariant.
+    pub name: String,
+
+    /// The condition over the transaction states of the nodes.
+    pub condition: Condition,
+}
+
+/// [`Condition`] is a predicate over the transaction states of the nodes.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Condition {
+    /// The transaction on the node is in the state.
+    Node {
+        /// The node.
+        node: usize,
+
+        /// The transaction state.
+        state: TransactionState,
+    },
+
+    /// The transaction on some node is in the state.
+    AnyNode(TransactionState),
+
+    /// The transaction on every node is in the state.
+    EveryNode(TransactionState),
+
+    /// The condition does not hold.
+    Not(Box<Condition>),
+
+    /// All the conditions hold.
+    All(Vec<Condition>),
+
+    /// Any of the conditions holds.
+    Any(Vec<Condition>),
+}
+
+/// [`InvariantError`] describes why invariants cannot be loaded.
+#[derive(Debug, Eq, PartialEq)]
+pub enum InvariantError {
+    /// The document is not a valid list of invariants.
+    Malformed(String),
+
+    /// An invariant refers to a node that does not exist.
+    UnknownNode(usize),
+}
+
+impl Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::Malformed(error) => write!(f, "malformed invariants: {error}"),
+            InvariantError::UnknownNode(node_id) => write!(f, "unknown node {node_id}"),
+        }
+    }
+}
+
+impl Invariant {
+    /// Loads the list of [`Invariant`] from the JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is malformed, or refers to a node out of `num_nodes`.
+    pub fn load(json: &str, num_nodes: usize) -> Result<Vec<Invariant>, InvariantError> {
+        let invariants: Vec<Invariant> = serde_json::from_str(json)
+            .map_err(|error| InvariantError::Malformed(error.to_string()))?;
+        match invariants
+            .iter()
+            .filter_map(|invariant| invariant.condition.max_node())
+            .find(|node_id| *node_id >= num_nodes)
+        {
+            Some(node_id) => Err(InvariantError::UnknownNode(node_id)),
+            None => Ok(invariants),
+        }
+    }
+
+    /// Returns `true` if the invariant holds for the transaction states of the nodes.
+    #[must_use]
+    pub fn holds(&self, states: &[TransactionState]) -> bool {
+        self.condition.holds(states)
+    }
+}
+
+impl Condition {
+    /// Returns `true` if the condition holds for the transaction states of the nodes.
+    #[must_use]
+    pub fn holds(&self, states: &[TransactionState]) -> bool {
+        match self {
+            Condition::Node { node, state } => states.get(*node) == Some(state),
+            Condition::AnyNode(state) => states.contains(state),
+            Condition::EveryNode(state) => states.iter().all(|s| s == state),
+            Condition::Not(condition) => !condition.holds(states),
+            Condition::All(conditions) => conditions.iter().all(|c| c.holds(states)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.holds(states)),
+        }
+    }
+
+    /// Returns the largest node that the condition refers to.
+    fn max_node(&self) -> Option<usize> {
+        match self {
+            Condition::Node { node, .. } => Some(*node),
+            Condition::AnyNode(_) | Condition::EveryNode(_) => None,
+            Condition::Not(condition) => condition.max_node(),
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().filter_map(Condition::max_node).max()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Condition, Invariant, InvariantError};
+    use crate::transaction::State;
+
+    #[test]
+    fn load() {
+        let invariants = Invariant::load(
+            r#"[{"name": "no mixed", "condition": {"not": {"all": [
+                {"any_node": "Committed"}, {"node": {"node": 1, "state": "RolledBack"}}
+            ]}}}]"#,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            invariants,
+            vec![Invariant {
+                name: "no mixed".to_string(),
+                condition: Condition::Not(Box::new(Condition::All(vec![
+                    Condition::AnyNode(State::Committed),
+                    Condition::Node {
+                        node: 1,
+                        state: State::RolledBack
+                    },
+                ]))),
+            }]
+        );
+        assert!(invariants[0].holds(&[State::Committed, State::Committed]));
+        assert!(!invariants[0].holds(&[State::Committed, State::RolledBack]));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Invariant::load(
+                r#"[{"name": "n", "condition": {"node": {"node": 2, "state": "Active"}}}]"#,
+                2
+            ),
+            Err(InvariantError::UnknownNode(2))
+        );
+        assert!(matches!(
+            Invariant::load(r#"[{"name": "n", "condition": {"every_node": "Done"}}]"#, 2),
+            Err(InvariantError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+#![deny(missing_docs, warnings, clippy::all, clippy::pedantic)]
+
+//! The library implements the transaction state machine, the models of the commit protocols, and
+//! the tooling around them, shared by the command-line tools.
+//!
+//! - `transaction-model-check` checks and explores the models.
+//! - `transaction-driver` executes two-phase commit over local sockets.
+//! - `transaction-admin` reports on several protocols, and maintains the check cache.
+//!
+//! The main types are re-exported at the root, so that other projects can embed the two-phase
+//! commit model, and step or check it:
+//!
+//! ```
+//! use homework::transaction::State;
+//! use homework::{Action, TransactionModel};
+//! use stateright::Model;
+//!
+//! let model = TransactionModel::new(2, false);
+//! let state = model.init_states().remove(0);
+//! let state = model.next_state(&state, Action::Start(0)).unwrap();
+//! assert_eq!(state.nodes()[0].transaction().state(), State::Active);
+//! ```
+
+pub mod blacklist_model;
+pub mod byzantine_coordinator_model;
+pub mod check_cache;
+pub mod check_report;
+pub mod cli;
+pub mod concurrent_transaction_model;
+pub mod coordinator_policy;
+pub mod coverage;
+pub mod dot_export;
+pub mod driver;
+pub mod group_commit_model;
+pub mod html_export;
+pub mod invariant;
+pub mod linear_commit_model;
+pub mod lock_model;
+pub mod message_complexity;
+pub mod model_config;
+pub mod participant_behavior;
+pub mod paxos_commit_model;
+pub mod percolator_model;
+pub mod raft_coordinator_model;
+pub mod saga_model;
+pub mod scenario;
+pub mod shrink;
+pub mod simulation;
+pub mod state_graph;
+pub mod three_phase_commit_model;
+pub mod trace;
+/// The [`Transaction`](transaction::Transaction) state machine of a node.
+pub mod transaction;
+pub mod transaction_model;
+pub mod transaction_model_builder;
+pub mod workload;
+
+pub use transaction::Transaction;
+pub use transaction_model::{Action, System, TransactionModel};
+pub use transaction_model_builder::TransactionModelBuilder;
@@ -0,0 +1,211 @@
+//! This is a model of linear two-phase commit.
+//!
+//! # Prepare chain
+//!
+//! The nodes form a chain headed by the coordinator. Instead of the coordinator asking every
+//! participant, each node that votes for commit forwards the prepare request to the next node in
+//! the chain; the last node has received the yes-votes of all the other nodes, and decides.
+//!
+//! # Decision chain
+//!
+//! The decision flows back along the chain from the last node to the coordinator. A node that
+//! votes against commit rolls back, and the rollback flows both back to the nodes that have
+//! prepared and forward to the nodes that have not been asked yet.
+
+use super::message_complexity::MessageCount;
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// [`LinearSystem`] is the state of the nodes in the chain.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LinearSystem {
+    /// The transactions of the nodes in the chain order.
+    nodes: Vec<Transaction>,
+}
+
+/// [`LinearAction`] is a state transition of [`LinearSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LinearAction {
+    /// The node receives the prepare request from its predecessor, and votes for commit; the
+    /// coordinator starts the chain by itself, and the last node decides to commit.
+    ForwardPrepare(usize),
+
+    /// The node receives the prepare request from its predecessor, and votes against commit.
+    VoteNo(usize),
+
+    /// The node learns the decision from its successor.
+    ReturnDecision(usize),
+
+    /// The node that has not been asked yet learns the rollback from its predecessor.
+    ForwardRollback(usize),
+}
+
+/// [`LinearCommitModel`] implements state transitions of linear two-phase commit.
+#[derive(Hash)]
+pub struct LinearCommitModel {
+    /// The number of nodes including the coordinator.
+    num_nodes: usize,
+}
+
+impl LinearCommitModel {
+    /// Creates a new [`LinearCommitModel`].
+    #[must_use]
+    pub fn new(num_nodes: usize) -> LinearCommitModel {
+        LinearCommitModel { num_nodes }
+    }
+
+    fn forward_prepare(&self, last_state: &LinearSystem, node_id: usize) -> Option<LinearSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.nodes[node_id];
+        let mut voted = transaction.prepare();
+        if node_id + 1 == self.num_nodes {
+            // The last node has received all the yes-votes.
+            voted &= transaction.commit();
+        }
+        voted.then_some(state)
+    }
+
+    fn learn(
+        last_state: &LinearSystem,
+        node_id: usize,
+        decision: TransactionState,
+    ) -> Option<LinearSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.nodes[node_id];
+        let learned = match decision {
+            TransactionState::Committed => transaction.commit(),
+            TransactionState::RolledBack => transaction.rollback(),
+            _ => false,
+        };
+        learned.then_some(state)
+    }
+}
+
+impl Model for LinearCommitModel {
+    type State = LinearSystem;
+    type Action = LinearAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let mut transaction = Transaction::default();
+        transaction.start();
+        vec![LinearSystem {
+            nodes: vec![transaction; self.num_nodes],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for (node_id, transaction) in state.nodes.iter().enumerate() {
+            let predecessor = node_id
+                .checked_sub(1)
+                .map(|predecessor_id| state.nodes[predecessor_id].state());
+            let successor = state.nodes.get(node_id + 1).map(Transaction::state);
+            match transaction.state() {
+                TransactionState::Active => match predecessor {
+                    None | Some(TransactionState::Prepared) => {
+                        actions.push(LinearAction::ForwardPrepare(node_id));
+                        actions.push(LinearAction::VoteNo(node_id));
+                    }
+                    Some(TransactionState::RolledBack) => {
+                        actions.push(LinearAction::ForwardRollback(node_id));
+                    }
+                    _ => (),
+                },
+                TransactionState::Prepared
+                    if successor.is_some_and(TransactionState::is_terminal) =>
+                {
+                    actions.push(LinearAction::ReturnDecision(node_id));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            LinearAction::ForwardPrepare(node_id) => self.forward_prepare(last_state, node_id),
+            LinearAction::VoteNo(node_id) | LinearAction::ForwardRollback(node_id) => {
+                Self::learn(last_state, node_id, TransactionState::RolledBack)
+            }
+            LinearAction::ReturnDecision(node_id) => Self::learn(
+                last_state,
+                node_id,
+                last_state.nodes.get(node_id + 1)?.state(),
+            ),
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("agreement", |_, state| {
+                // No two nodes reach different decisions.
+                let mut decisions = state
+                    .nodes
+                    .iter()
+                    .map(Transaction::state)
+                    .filter(|state| state.is_terminal());
+                decisions.next().is_none_or(|decision| {
+                    decisions.all(|other_decision| other_decision == decision)
+                })
+            }),
+            Property::<Self>::eventually("decision reaches every node", |_, state| {
+                state
+                    .nodes
+                    .iter()
+                    .all(|transaction| transaction.state().is_terminal())
+            }),
+            Property::<Self>::sometimes("commit", |_, state| {
+                state
+                    .nodes
+                    .iter()
+                    .all(|transaction| transaction.state() == TransactionState::Committed)
+            }),
+        ]
+    }
+}
+
+impl MessageCount for LinearCommitModel {
+    fn messages(&self, _state: &Self::State, action: &Self::Action) -> Option<usize> {
+        match action {
+            // The coordinator starts the chain, and a node votes against commit by itself.
+            LinearAction::ForwardPrepare(0) | LinearAction::VoteNo(_) => Some(0),
+            LinearAction::ForwardPrepare(_)
+            | LinearAction::ReturnDecision(_)
+            | LinearAction::ForwardRollback(_) => Some(1),
+        }
+    }
+
+    fn is_committed(&self, state: &Self::State) -> bool {
+        state
+            .nodes
+            .iter()
+            .all(|transaction| transaction.state() == TransactionState::Committed)
+    }
+
+    fn expected_messages(&self) -> usize {
+        // Prepare requests along the chain, and decisions back along the chain.
+        2 * (self.num_nodes - 1)
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{LinearCommitModel, Model};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn linear_commit() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = LinearCommitModel::new(4)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
@@ -0,0 +1,473 @@
+//! This is a model of concurrent transactions competing for locks.
+//!
+//! # Priority
+//!
+//! The priority of a transaction is determined by its age: a transaction with a smaller
+//! identifier is older, and therefore has a higher priority.
+//!
+//! # Locking
+//!
+//! Each transaction locks all the resources one by one in its own order before it prepares for
+//! commit. Two transactions locking the resources in different orders may wait for each other
+//! forever unless a [`PreemptionPolicy`] resolves the conflict.
+//!
+//! # Commit
+//!
+//! Once all the locks are acquired, the transaction is prepared, and the resource managers vote
+//! for commit. The transaction is committed when all the votes are collected, and the locks are
+//! released. The committed transaction is assigned a commit timestamp, and installs a new version
+//! of each resource that it locked.
+//!
+//! ## Read-only transactions
+//!
+//! A read-only transaction neither acquires locks nor prepares for commit. It takes the latest
+//! commit timestamp as its snapshot timestamp when it starts, reads the latest version of each
+//! resource that is not newer than the snapshot, and commits locally.
+
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// [`PreemptionPolicy`] decides which transaction gives way when a transaction requests a lock
+/// held by another transaction.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PreemptionPolicy {
+    /// An older requester wounds, or rolls back, the younger lock holder, and a younger requester
+    /// waits for the older lock holder.
+    WoundWait,
+
+    /// An older requester waits for the younger lock holder, and a younger requester dies, or
+    /// rolls back itself.
+    WaitDie,
+}
+
+/// [`LockSystem`] is the state of the transactions and the locks.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LockSystem {
+    /// The transactions; the participants of a transaction are the resources that it locked.
+    transactions: Vec<Transaction>,
+
+    /// The transaction holding the lock on each resource.
+    locks: Vec<Option<usize>>,
+
+    /// A transaction was preempted after it had collected all the votes.
+    preempted_after_votes: bool,
+
+    /// The latest commit timestamp.
+    clock: usize,
+
+    /// The commit timestamp of each transaction.
+    commit_timestamps: Vec<Option<usize>>,
+
+    /// The committed versions of each resource as pairs of a commit timestamp and a transaction.
+    versions: Vec<Vec<(usize, usize)>>,
+
+    /// The read-only transactions.
+    readers: Vec<Reader>,
+}
+
+/// [`Reader`] is a read-only transaction.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Reader {
+    /// The read-only transaction.
+    transaction: Transaction,
+
+    /// The snapshot timestamp.
+    snapshot: usize,
+
+    /// The commit timestamp of the version read from each resource; the initial version of a
+    /// resource has timestamp `0`.
+    reads: Vec<Option<usize>>,
+}
+
+/// [`LockAction`] is a state transition of [`LockSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LockAction {
+    /// Starts the transaction.
+    Start(usize),
+
+    /// The transaction requests the lock on the resource.
+    Acquire(usize, usize),
+
+    /// Prepares the transaction for commit.
+    Prepare(usize),
+
+    /// The resource votes for commit of the transaction.
+    Vote(usize, usize),
+
+    /// Commits the transaction, and releases its locks.
+    Commit(usize),
+
+    /// Starts the read-only transaction with a snapshot timestamp.
+    StartReadOnly(usize),
+
+    /// The read-only transaction reads the resource.
+    Read(usize, usize),
+
+    /// Commits the read-only transaction locally.
+    CommitReadOnly(usize),
+}
+
+/// [`LockModel`] implements state transitions of concurrent transactions.
+#[derive(Hash)]
+pub struct LockModel {
+    /// The number of transactions.
+    num_transactions: usize,
+
+    /// The number of resources.
+    num_resources: usize,
+
+    /// The preemption policy.
+    policy: PreemptionPolicy,
+
+    /// The number of read-only transactions.
+    num_readers: usize,
+}
+
+impl LockModel {
+    /// Creates a new [`LockModel`].
+    #[must_use]
+    pub fn new(
+        num_transactions: usize,
+        num_resources: usize,
+        policy: PreemptionPolicy,
+    ) -> LockModel {
+        LockModel {
+            num_transactions,
+            num_resources,
+            policy,
+            num_readers: 0,
+        }
+    }
+
+    /// Sets the number of read-only transactions running along with the other transactions.
+    #[must_use]
+    pub fn read_only_transactions(self, num_readers: usize) -> LockModel {
+        LockModel {
+            num_readers,
+            ..self
+        }
+    }
+
+    /// Returns the resource that the transaction has to lock next.
+    fn next_resource(&self, state: &LockSystem, txn_id: usize) -> Option<usize> {
+        (0..self.num_resources)
+            .map(|offset| (txn_id + offset) % self.num_resources)
+            .find(|resource| state.locks[*resource] != Some(txn_id))
+    }
+
+    /// Returns the transaction that the transaction is waiting for.
+    fn waits_for(&self, state: &LockSystem, txn_id: usize) -> Option<usize> {
+        if state.transactions[txn_id].state() != TransactionState::Active {
+            return None;
+        }
+        let holder = self
+            .next_resource(state, txn_id)
+            .and_then(|resource| state.locks[resource])?;
+        let waits = match self.policy {
+            PreemptionPolicy::WoundWait => {
+                txn_id > holder || state.transactions[holder].state() != TransactionState::Active
+            }
+            PreemptionPolicy::WaitDie => txn_id < holder,
+        };
+        waits.then_some(holder)
+    }
+
+    fn rollback(state: &mut LockSystem, txn_id: usize) {
+        let transaction = &mut state.transactions[txn_id];
+        if transaction.state() == TransactionState::Prepared && transaction.is_all_prepared() {
+            state.preempted_after_votes = true;
+        }
+        if transaction.rollback() {
+            Self::release_locks(state, txn_id);
+        }
+    }
+
+    fn release_locks(state: &mut LockSystem, txn_id: usize) {
+        state
+            .locks
+            .iter_mut()
+            .filter(|holder| **holder == Some(txn_id))
+            .for_each(|holder| *holder = None);
+    }
+
+    fn acquire(
+        &self,
+        last_state: &LockSystem,
+        txn_id: usize,
+        resource: usize,
+    ) -> Option<LockSystem> {
+        let mut state = last_state.clone();
+        match last_state.locks[resource] {
+            None => {
+                state.locks[resource] = Some(txn_id);
+                state.transactions[txn_id].add_participant(resource);
+            }
+            Some(holder) if holder != txn_id => {
+                if self.waits_for(last_state, txn_id) == Some(holder) {
+                    // The transaction has to wait until the lock is released.
+                    return None;
+                }
+                match self.policy {
+                    PreemptionPolicy::WoundWait => {
+                        // Wound the younger lock holder, and take over the lock.
+                        Self::rollback(&mut state, holder);
+                        state.locks[resource] = Some(txn_id);
+                        state.transactions[txn_id].add_participant(resource);
+                    }
+                    PreemptionPolicy::WaitDie => {
+                        // The younger requester dies.
+                        Self::rollback(&mut state, txn_id);
+                    }
+                }
+            }
+            Some(_) => return None,
+        }
+        Some(state)
+    }
+
+    fn commit(last_state: &LockSystem, txn_id: usize) -> Option<LockSystem> {
+        let mut state = last_state.clone();
+        if !state.transactions[txn_id].commit() {
+            return None;
+        }
+
+        // Install new versions of the locked resources with a new commit timestamp.
+        state.clock += 1;
+        state.commit_timestamps[txn_id] = Some(state.clock);
+        let clock = state.clock;
+        let versions = &mut state.versions;
+        state.transactions[txn_id].for_each_participant(|resource| {
+            versions[resource].push((clock, txn_id));
+        });
+        Self::release_locks(&mut state, txn_id);
+        Some(state)
+    }
+
+    fn read(last_state: &LockSystem, reader_id: usize, resource: usize) -> Option<LockSystem> {
+        let mut state = last_state.clone();
+        let reader = &mut state.readers[reader_id];
+        if reader.transaction.state() != TransactionState::Active
+            || reader.reads[resource].is_some()
+        {
+            return None;
+        }
+
+        // Read the latest version that is not newer than the snapshot.
+        let version = last_state.versions[resource]
+            .iter()
+            .rev()
+            .find(|(commit_timestamp, _)| *commit_timestamp <= reader.snapshot)
+            .map_or(0, |(commit_timestamp, _)| *commit_timestamp);
+        reader.reads[resource] = Some(version);
+        Some(state)
+    }
+
+    /// Returns `true` if every read of the read-only transaction is consistent with the prefix of
+    /// the committed transactions up to its snapshot timestamp.
+    fn is_consistent_snapshot(state: &LockSystem, reader: &Reader) -> bool {
+        reader.reads.iter().enumerate().all(|(resource, read)| {
+            let Some(read) = read else {
+                return true;
+            };
+            let expected = state
+                .commit_timestamps
+                .iter()
+                .enumerate()
+                .filter(|(txn_id, _)| state.transactions[*txn_id].has_participant(resource))
+                .filter_map(|(_, commit_timestamp)| *commit_timestamp)
+                .filter(|commit_timestamp| *commit_timestamp <= reader.snapshot)
+                .max()
+                .unwrap_or(0);
+            *read == expected
+        })
+    }
+
+    /// Returns `true` if some transactions wait for each other forever.
+    fn is_deadlocked(&self, state: &LockSystem) -> bool {
+        (0..self.num_transactions).any(|txn_id| {
+            // Follow the waits-for chain; revisiting the starting transaction means a cycle.
+            let mut current = txn_id;
+            for _ in 0..self.num_transactions {
+                match self.waits_for(state, current) {
+                    Some(holder) if holder == txn_id => return true,
+                    Some(holder) => current = holder,
+                    None => return false,
+                }
+            }
+            false
+        })
+    }
+}
+
+impl Model for LockModel {
+    type State = LockSystem;
+    type Action = LockAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![LockSystem {
+            transactions: vec![Transaction::default(); self.num_transactions],
+            locks: vec![None; self.num_resources],
+            preempted_after_votes: false,
+            clock: 0,
+            commit_timestamps: vec![None; self.num_transactions],
+            versions: vec![Vec::new(); self.num_resources],
+            readers: vec![
+                Reader {
+                    transaction: Transaction::read_only(),
+                    snapshot: 0,
+                    reads: vec![None; self.num_resources],
+                };
+                self.num_readers
+            ],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        state
+            .transactions
+            .iter()
+            .enumerate()
+            .for_each(|(txn_id, transaction)| match transaction.state() {
+                TransactionState::Inactive => actions.push(LockAction::Start(txn_id)),
+                TransactionState::Active => {
+                    if let Some(resource) = self.next_resource(state, txn_id) {
+                        actions.push(LockAction::Acquire(txn_id, resource));
+                    } else {
+                        actions.push(LockAction::Prepare(txn_id));
+                    }
+                }
+                TransactionState::Prepared => {
+                    if transaction.is_all_prepared() {
+                        actions.push(LockAction::Commit(txn_id));
+                    } else {
+                        transaction.for_each_participant(|resource| {
+                            if !transaction.is_prepared(resource) {
+                                actions.push(LockAction::Vote(txn_id, resource));
+                            }
+                        });
+                    }
+                }
+                _ => (),
+            });
+        state
+            .readers
+            .iter()
+            .enumerate()
+            .for_each(|(reader_id, reader)| match reader.transaction.state() {
+                TransactionState::Inactive => actions.push(LockAction::StartReadOnly(reader_id)),
+                TransactionState::Active => {
+                    if let Some(resource) = reader.reads.iter().position(Option::is_none) {
+                        actions.push(LockAction::Read(reader_id, resource));
+                    } else {
+                        actions.push(LockAction::CommitReadOnly(reader_id));
+                    }
+                }
+                _ => (),
+            });
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            LockAction::Start(txn_id) => {
+                let mut state = last_state.clone();
+                state.transactions[txn_id].start().then_some(state)
+            }
+            LockAction::Acquire(txn_id, resource) => self.acquire(last_state, txn_id, resource),
+            LockAction::Prepare(txn_id) => {
+                let mut state = last_state.clone();
+                state.transactions[txn_id].prepare().then_some(state)
+            }
+            LockAction::Vote(txn_id, resource) => {
+                let mut state = last_state.clone();
+                state.transactions[txn_id]
+                    .report_prepared(resource)
+                    .then_some(state)
+            }
+            LockAction::Commit(txn_id) => Self::commit(last_state, txn_id),
+            LockAction::StartReadOnly(reader_id) => {
+                let mut state = last_state.clone();
+                let reader = &mut state.readers[reader_id];
+                reader.snapshot = last_state.clock;
+                reader.transaction.start().then_some(state)
+            }
+            LockAction::Read(reader_id, resource) => Self::read(last_state, reader_id, resource),
+            LockAction::CommitReadOnly(reader_id) => {
+                let mut state = last_state.clone();
+                state.readers[reader_id]
+                    .transaction
+                    .commit()
+                    .then_some(state)
+            }
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("no preemption after votes", |_, state| {
+                // A transaction that has collected all the votes is never rolled back.
+                !state.preempted_after_votes
+            }),
+            Property::<Self>::always("deadlock free", |model, state| !model.is_deadlocked(state)),
+            Property::<Self>::sometimes("all committed", |_, state| {
+                state
+                    .transactions
+                    .iter()
+                    .all(|transaction| transaction.state() == TransactionState::Committed)
+            }),
+            Property::<Self>::always("read-only snapshot isolation", |_, state| {
+                // The reads of a read-only transaction reflect exactly the transactions committed
+                // at or before its snapshot timestamp.
+                state
+                    .readers
+                    .iter()
+                    .all(|reader| Self::is_consistent_snapshot(state, reader))
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{LockModel, Model, PreemptionPolicy};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn wound_wait() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = LockModel::new(3, 2, PreemptionPolicy::WoundWait)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn wait_die() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = LockModel::new(3, 2, PreemptionPolicy::WaitDie)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn read_only_transactions() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = LockModel::new(2, 2, PreemptionPolicy::WoundWait)
+            .read_only_transactions(1)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
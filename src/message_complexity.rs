@@ -0,0 +1,211 @@
+//! This is an analysis of the number of messages that a protocol sends to commit a transaction.
+//!
+//! Messages are counted along every fault-free trace from the initial states to a state where all
+//! the nodes have committed the transaction, and the best and worst cases are compared with the
+//! textbook numbers of the protocol.
+//!
+//! Retransmissions that do not change the state are not counted; under weak fairness they do not
+//! contribute to the progress of the protocol, and would otherwise make the worst case unbounded.
+
+use super::state_graph::fingerprint;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use stateright::Model;
+
+/// [`MessageCount`] is implemented by models of commit protocols that send messages.
+pub trait MessageCount: Model {
+    /// Returns the number of messages sent by the action, or `None` if the action is a fault.
+    fn messages(&self, state: &Self::State, action: &Self::Action) -> Option<usize>;
+
+    /// Returns `true` if all the nodes have committed the transaction.
+    fn is_committed(&self, state: &Self::State) -> bool;
+
+    /// Returns the textbook number of messages per committed transaction.
+    fn expected_messages(&self) -> usize;
+}
+
+/// [`MessageComplexity`] is the number of messages sent per committed transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MessageComplexity {
+    /// The minimum number of messages.
+    pub best: usize,
+
+    /// The maximum number of messages.
+    pub worst: usize,
+}
+
+/// Analyzes the number of messages per committed transaction.
+///
+/// Returns `None` if no fault-free trace commits the transaction.
+pub fn analyze<M: MessageCount>(model: &M) -> Option<MessageComplexity>
+where
+    M::State: Hash,
+{
+    let mut memo = HashMap::new();
+    model
+        .init_states()
+        .iter()
+        .filter_map(|state| count(model, state, &mut memo))
+        .reduce(merge)
+}
+
+/// Returns the number of messages from the state to a committed state.
+fn count<M: MessageCount>(
+    model: &M,
+    state: &M::State,
+    memo: &mut HashMap<u64, Option<MessageComplexity>>,
+) -> Option<MessageComplexity>
+where
+    M::State: Hash,
+{
+    if model.is_committed(state) {
+        return Some(MessageComplexity { best: 0, worst: 0 });
+    }
+    let key = fingerprint(state);
+    if let Some(result) = memo.get(&key) {
+        return *result;
+    }
+
+    // Mark the state as visited so that a cycle is not followed; the fault-free protocols make
+    // progress in every state-changing step.
+    memo.insert(key, None);
+    let mut result = None;
+    for (action, next_state) in model.next_steps(state) {
+        let Some(messages) = model.messages(state, &action) else {
+            continue;
+        };
+        if fingerprint(&next_state) == key {
+            continue;
+        }
+        if let Some(rest) = count(model, &next_state, memo) {
+            let complexity = MessageComplexity {
+                best: rest.best + messages,
+                worst: rest.worst + messages,
+            };
+            result = Some(result.map_or(complexity, |result| merge(result, complexity)));
+        }
+    }
+    memo.insert(key, result);
+    result
+}
+
+fn merge(left: MessageComplexity, right: MessageComplexity) -> MessageComplexity {
+    MessageComplexity {
+        best: left.best.min(right.best),
+        worst: left.worst.max(right.worst),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{analyze, MessageComplexity, MessageCount};
+    use crate::linear_commit_model::LinearCommitModel;
+    use crate::three_phase_commit_model::ThreePhaseCommitModel;
+    use crate::transaction_model::TransactionModel;
+
+    #[test]
+    fn two_phase_commit() {
+        for num_nodes in 2..=3 {
+            let model = TransactionModel::new(num_nodes, false);
+            let expected = 3 * (num_nodes - 1);
+            assert_eq!(model.expected_messages(), expected);
+            assert_eq!(
+                analyze(&model),
+                Some(MessageComplexity {
+                    best: expected,
+                    worst: expected
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn presumed_commit() {
+        for num_nodes in 2..=3 {
+            let model = TransactionModel::new(num_nodes, true);
+            let expected = 3 * (num_nodes - 1);
+            assert_eq!(model.expected_messages(), expected);
+            assert_eq!(
+                analyze(&model),
+                Some(MessageComplexity {
+                    best: expected,
+                    worst: expected
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn commit_tree() {
+        let model = TransactionModel::with_topology(vec![None, Some(0), Some(1), Some(1)], false);
+        assert_eq!(model.expected_messages(), 9);
+        assert_eq!(
+            analyze(&model),
+            Some(MessageComplexity { best: 9, worst: 9 })
+        );
+    }
+
+    #[test]
+    fn read_only_participant() {
+        let model = TransactionModel::new(3, false).read_only_participants(vec![2]);
+        assert_eq!(model.expected_messages(), 5);
+        assert_eq!(
+            analyze(&model),
+            Some(MessageComplexity { best: 5, worst: 5 })
+        );
+    }
+
+    #[test]
+    fn one_phase() {
+        let model = TransactionModel::new(2, false).one_phase(true);
+        assert_eq!(model.expected_messages(), 2);
+        assert_eq!(
+            analyze(&model),
+            Some(MessageComplexity { best: 2, worst: 2 })
+        );
+    }
+
+    #[test]
+    fn last_agent() {
+        let model = TransactionModel::new(3, false).last_agent(true);
+        assert_eq!(model.expected_messages(), 5);
+        assert_eq!(
+            analyze(&model),
+            Some(MessageComplexity { best: 5, worst: 5 })
+        );
+    }
+
+    #[test]
+    fn linear_commit() {
+        for num_nodes in 2..=4 {
+            let model = LinearCommitModel::new(num_nodes);
+            let expected = 2 * (num_nodes - 1);
+            assert_eq!(model.expected_messages(), expected);
+            assert_eq!(
+                analyze(&model),
+                Some(MessageComplexity {
+                    best: expected,
+                    worst: expected
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn three_phase_commit() {
+        for num_nodes in 2..=3 {
+            let model = ThreePhaseCommitModel::new(num_nodes);
+            let expected = 5 * (num_nodes - 1);
+            assert_eq!(model.expected_messages(), expected);
+            assert_eq!(
+                analyze(&model),
+                Some(MessageComplexity {
+                    best: expected,
+                    worst: expected
+                })
+            );
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! This is a configuration file format of the parameters of the transaction model.
+//!
+//! # Format
+//!
+//! A configuration is a TOML document that sets the number of nodes, the coordinator, the crash
+//! budget, the faults that are enabled, and the properties that are checked; a parameter that is
+//! absent keeps its default. For example, the following configuration checks agreement among four
+//! nodes coordinated by node 1, which crash at most once and stay down:
+//!
+//! ```toml
+//! nodes = 4
+//! coordinator = 1
+//! crash_budget = 1
+//! faults = ["fail-stop", "torn-writes"]
+//! properties = ["agreement"]
+//! ```
+//!
+//! The faults are `fail-stop`, `coordinator-amnesia`, `log-write-failures`, `torn-writes` and
+//! `replacement`. The command-line flags override the values of the file.
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+/// [`ModelConfig`] is the set of parameters of the transaction model.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelConfig {
+    /// The number of nodes including the coordinator.
+    pub nodes: Option<usize>,
+
+    /// The node that coordinates the transaction.
+    pub coordinator: Option<usize>,
+
+    /// The maximum number of crashes of the nodes.
+    pub crash_budget: Option<usize>,
+
+    /// The faults that are enabled.
+    pub faults: Vec<FaultType>,
+
+    /// The names of the properties to check; all the properties are checked if empty.
+    pub properties: Vec<String>,
+}
+
+/// [`FaultType`] is a kind of fault that is enabled by the configuration.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FaultType {
+    /// Crashed nodes stay down instead of restarting.
+    FailStop,
+
+    /// The undecided coordinator may crash, and forget the transaction.
+    CoordinatorAmnesia,
+
+    /// The logs of the participants fill up.
+    LogWriteFailures,
+
+    /// Log records that are not synced are lost when nodes crash.
+    TornWrites,
+
+    /// Participants may fail permanently and be replaced.
+    Replacement,
+}
+
+/// [`ConfigError`] describes why a configuration cannot be loaded.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    /// The document is not a valid configuration.
+    Malformed(String),
+
+    /// The coordinator is not one of the nodes.
+    UnknownCoordinator(usize),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Malformed(error) => write!(f, "malformed configuration: {error}"),
+            ConfigError::UnknownCoordinator(node_id) => write!(f, "unknown coordinator {node_id}"),
+        }
+    }
+}
+
+impl ModelConfig {
+    /// Loads a [`ModelConfig`] from the TOML document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is malformed, or the coordinator is out of the nodes.
+    pub fn load(toml: &str) -> Result<ModelConfig, ConfigError> {
+        let config: ModelConfig =
+            toml::from_str(toml).map_err(|error| ConfigError::Malformed(error.to_string()))?;
+        match (config.coordinator, config.nodes) {
+            (Some(coordinator), Some(nodes)) if coordinator >= nodes => {
+                Err(ConfigError::UnknownCoordinator(coordinator))
+            }
+            _ => Ok(config),
+        }
+    }
+
+    /// Returns the configuration with the parameters that are set in `overrides` replaced; the
+    /// faults of both are enabled.
+    #[must_use]
+    pub fn merge(self, overrides: ModelConfig) -> ModelConfig {
+        let mut faults = self.faults;
+        for fault in overrides.faults {
+            if !faults.contains(&fault) {
+                faults.push(fault);
+            }
+        }
+        ModelConfig {
+            nodes: overrides.nodes.or(self.nodes),
+            coordinator: overrides.coordinator.or(self.coordinator),
+            crash_budget: overrides.crash_budget.or(self.crash_budget),
+            faults,
+            properties: if overrides.properties.is_empty() {
+                self.properties
+            } else {
+                overrides.properties
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConfigError, FaultType, ModelConfig};
+
+    #[test]
+    fn load() {
+        let config = ModelConfig::load(
+            r#"
+            nodes = 4
+            coordinator = 1
+            faults = ["fail-stop", "torn-writes"]
+            properties = ["agreement"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            ModelConfig {
+                nodes: Some(4),
+                coordinator: Some(1),
+                crash_budget: None,
+                faults: vec![FaultType::FailStop, FaultType::TornWrites],
+                properties: vec!["agreement".to_string()],
+            }
+        );
+        assert_eq!(
+            ModelConfig::load("nodes = 2\ncoordinator = 2"),
+            Err(ConfigError::UnknownCoordinator(2))
+        );
+        assert!(matches!(
+            ModelConfig::load(r#"faults = ["meteor"]"#),
+            Err(ConfigError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn merge() {
+        let config = ModelConfig {
+            nodes: Some(4),
+            crash_budget: Some(1),
+            faults: vec![FaultType::FailStop],
+            properties: vec!["agreement".to_string()],
+            ..ModelConfig::default()
+        };
+        let merged = config.merge(ModelConfig {
+            crash_budget: Some(0),
+            faults: vec![FaultType::FailStop, FaultType::Replacement],
+            ..ModelConfig::default()
+        });
+        assert_eq!(
+            merged,
+            ModelConfig {
+                nodes: Some(4),
+                coordinator: None,
+                crash_budget: Some(0),
+                faults: vec![FaultType::FailStop, FaultType::Replacement],
+                properties: vec!["agreement".to_string()],
+            }
+        );
+    }
+}
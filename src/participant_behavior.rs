@@ -0,0 +1,155 @@
+//! This is a set of behaviors of the participants of the two-phase commit model.
+//!
+//! # Behaviors
+//!
+//! A behavior decides how a participant votes, when it reacts to a prepare request, and whether
+//! it recovers from a crash; a participant without a behavior of its own follows the protocol
+//! as [`Cooperative`], so that a model can single out an always-no participant or a slow one
+//! without changing the others.
+//!
+//! A slow participant answers a prepare request only once its timeout has fired, which makes a
+//! difference only with the simulated clock: without it, timeouts may fire at any time.
+
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+/// [`ParticipantBehavior`] decides how a participant node acts in the protocol.
+///
+/// The [`Debug`] representation of a behavior identifies it along with its configuration, and is
+/// hashed into the fingerprint of the model.
+pub trait ParticipantBehavior: Debug + Send + Sync {
+    /// Returns the name of the behavior.
+    fn name(&self) -> &'static str;
+
+    /// Returns `true` if the participant may vote for commit, or against it if `commit` is
+    /// `false`.
+    fn may_vote(&self, commit: bool) -> bool {
+        let _ = commit;
+        true
+    }
+
+    /// Returns `true` if the active participant prepares the transaction on a prepare request,
+    /// given whether its timeout has fired.
+    fn responds_to_prepare(&self, timed_out: bool) -> bool {
+        let _ = timed_out;
+        true
+    }
+
+    /// Returns `true` if the participant restarts and recovers the transaction from its log after
+    /// a crash, unless the nodes are fail-stop.
+    fn recovers_after_crash(&self) -> bool {
+        true
+    }
+}
+
+impl Hash for dyn ParticipantBehavior {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        format!("{self:?}").hash(state);
+    }
+}
+
+/// Returns the participant behavior of the name.
+#[must_use]
+pub fn by_name(name: &str) -> Option<Box<dyn ParticipantBehavior>> {
+    match name {
+        "cooperative" => Some(Box::new(Cooperative)),
+        "no-voter" => Some(Box::new(NoVoter)),
+        "slow" => Some(Box::new(Slow)),
+        "unrecoverable" => Some(Box::new(Unrecoverable)),
+        _ => None,
+    }
+}
+
+/// [`Cooperative`] follows the protocol: it votes either way, prepares when requested, and
+/// recovers after a crash.
+#[derive(Debug)]
+pub struct Cooperative;
+
+impl ParticipantBehavior for Cooperative {
+    fn name(&self) -> &'static str {
+        "cooperative"
+    }
+}
+
+/// [`NoVoter`] always votes against commit.
+#[derive(Debug)]
+pub struct NoVoter;
+
+impl ParticipantBehavior for NoVoter {
+    fn name(&self) -> &'static str {
+        "no-voter"
+    }
+
+    fn may_vote(&self, commit: bool) -> bool {
+        !commit
+    }
+}
+
+/// [`Slow`] answers a prepare request only after its timeout has fired.
+#[derive(Debug)]
+pub struct Slow;
+
+impl ParticipantBehavior for Slow {
+    fn name(&self) -> &'static str {
+        "slow"
+    }
+
+    fn responds_to_prepare(&self, timed_out: bool) -> bool {
+        timed_out
+    }
+}
+
+/// [`Unrecoverable`] stays down after a crash.
+#[derive(Debug)]
+pub struct Unrecoverable;
+
+impl ParticipantBehavior for Unrecoverable {
+    fn name(&self) -> &'static str {
+        "unrecoverable"
+    }
+
+    fn recovers_after_crash(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ParticipantBehavior;
+
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    /// Votes only one way.
+    #[derive(Debug)]
+    struct OneWay {
+        commit: bool,
+    }
+
+    impl ParticipantBehavior for OneWay {
+        fn name(&self) -> &'static str {
+            "one-way"
+        }
+
+        fn may_vote(&self, commit: bool) -> bool {
+            commit == self.commit
+        }
+    }
+
+    #[test]
+    fn hash_configuration() {
+        let hash = |behavior: Box<dyn ParticipantBehavior>| {
+            let mut hasher = DefaultHasher::new();
+            behavior.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_ne!(
+            hash(Box::new(OneWay { commit: true })),
+            hash(Box::new(OneWay { commit: false }))
+        );
+        assert_eq!(
+            hash(Box::new(OneWay { commit: true })),
+            hash(Box::new(OneWay { commit: true }))
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! This is a Paxos Commit model implementation.
+//!
+//! Paxos Commit, by Gray and Lamport, replaces the single coordinator of the two-phase commit
+//! protocol with a Paxos consensus instance per participant: the vote of each participant is
+//! chosen by `2F + 1` acceptors, and the transaction commits if and only if the prepared vote is
+//! chosen for every participant. The protocol does not block as long as at most `F` acceptors
+//! fail.
+//!
+//! # The Algorithm
+//!
+//! ## Voting
+//!
+//! A participant prepares the transaction, or decides to roll it back, and proposes its vote to
+//! the acceptors in ballot `0` of its own instance.
+//!
+//! ## Acceptance
+//!
+//! An acceptor accepts the vote of a ballot unless it has joined a higher ballot of the instance.
+//!
+//! ## Commit
+//!
+//! The leader learns the vote chosen in each instance once a majority of the acceptors accepted
+//! it in the same ballot. The leader commits the transaction if the prepared vote is chosen for
+//! every participant, and rolls it back as soon as a rollback vote is chosen for any.
+//!
+//! ## Recovery
+//!
+//! If a participant does not vote in time, the leader runs ballot `1` of its instance: it asks
+//! the acceptors to join the ballot, and proposes the vote accepted in the highest ballot reported
+//! by a majority of them, or a rollback vote if none of them accepted any. The leader itself is
+//! assumed not to fail.
+//!
+//! # State space
+//!
+//! The acceptors of an instance are interchangeable, so the model keeps them sorted, and the
+//! messages that an acceptor sent are recorded in its own state. Up to `F` acceptors of each
+//! instance may fail independently, which covers the failure of `F` acceptors shared by all the
+//! instances.
+
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// The ballot of the participant proposing its own vote.
+const INITIAL_BALLOT: usize = 0;
+
+/// The ballot of the leader recovering the vote of a participant.
+const RECOVERY_BALLOT: usize = 1;
+
+/// [`Vote`] is the value chosen by a Paxos instance.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Vote {
+    /// The participant has prepared the transaction.
+    Prepared,
+
+    /// The participant has rolled back the transaction.
+    RolledBack,
+}
+
+/// [`Acceptor`] is the state of an acceptor in a Paxos instance.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Acceptor {
+    /// The acceptor has crashed.
+    crashed: bool,
+
+    /// The acceptor has joined the recovery ballot.
+    joined: bool,
+
+    /// The vote accepted last together with its ballot, reported when the acceptor joined the
+    /// recovery ballot.
+    report: Option<(usize, Vote)>,
+
+    /// The ballots and the votes that the acceptor accepted.
+    accepted: Vec<(usize, Vote)>,
+}
+
+impl Acceptor {
+    /// Returns the highest ballot that the acceptor has joined.
+    fn ballot(&self) -> usize {
+        if self.joined {
+            RECOVERY_BALLOT
+        } else {
+            self.accepted
+                .last()
+                .map_or(INITIAL_BALLOT, |(ballot, _)| *ballot)
+        }
+    }
+}
+
+/// [`Instance`] is the Paxos instance choosing the vote of a participant.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Instance {
+    /// The acceptors sorted by their states.
+    acceptors: Vec<Acceptor>,
+
+    /// The leader has started the recovery ballot.
+    recovering: bool,
+
+    /// The votes proposed in each ballot sorted by the ballots.
+    proposals: Vec<(usize, Vote)>,
+}
+
+/// [`PaxosCommitSystem`] is the state of the participants and the acceptors.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PaxosCommitSystem {
+    /// The transactions of the participants.
+    participants: Vec<Transaction>,
+
+    /// The Paxos instances of the participants.
+    instances: Vec<Instance>,
+
+    /// The outcome decided by the leader.
+    outcome: Option<Vote>,
+}
+
+/// [`PaxosCommitAction`] is a state transition of [`PaxosCommitSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PaxosCommitAction {
+    /// The participant prepares the transaction, and votes for commit.
+    Prepare(usize),
+
+    /// The participant rolls back the transaction, and votes against commit.
+    ChooseToRollback(usize),
+
+    /// The participant learns the outcome.
+    Learn(usize),
+
+    /// The leader starts the recovery ballot of the instance.
+    Phase1a(usize),
+
+    /// The acceptor joins the recovery ballot of the instance.
+    Phase1b(usize, usize),
+
+    /// The leader proposes the vote of the instance in the recovery ballot.
+    Phase2a(usize),
+
+    /// The acceptor accepts the vote proposed in the ballot of the instance.
+    Phase2b(usize, usize, usize),
+
+    /// The leader decides the outcome of the transaction.
+    Decide,
+
+    /// The acceptor of the instance crashes.
+    Crash(usize, usize),
+}
+
+/// [`PaxosCommitModel`] implements state transitions of the Paxos Commit protocol.
+#[derive(Hash)]
+pub struct PaxosCommitModel {
+    /// The number of participants.
+    num_participants: usize,
+
+    /// The number of acceptor failures to tolerate.
+    num_faults: usize,
+}
+
+impl PaxosCommitModel {
+    /// Creates a new [`PaxosCommitModel`] with `2 * num_faults + 1` acceptors.
+    #[must_use]
+    pub fn new(num_participants: usize, num_faults: usize) -> PaxosCommitModel {
+        PaxosCommitModel {
+            num_participants,
+            num_faults,
+        }
+    }
+
+    fn is_majority(&self, num_acceptors: usize) -> bool {
+        num_acceptors > self.num_faults
+    }
+
+    /// Returns the vote chosen by the instance.
+    fn chosen(&self, instance: &Instance) -> Option<Vote> {
+        instance
+            .proposals
+            .iter()
+            .find(|proposal| {
+                let num_accepted = instance
+                    .acceptors
+                    .iter()
+                    .filter(|acceptor| acceptor.accepted.contains(proposal))
+                    .count();
+                self.is_majority(num_accepted)
+            })
+            .map(|(_, vote)| *vote)
+    }
+
+    /// Returns the vote that the leader proposes in the recovery ballot of the instance.
+    fn recovery_vote(&self, instance: &Instance) -> Option<Vote> {
+        let reports: Vec<Option<(usize, Vote)>> = instance
+            .acceptors
+            .iter()
+            .filter(|acceptor| acceptor.joined)
+            .map(|acceptor| acceptor.report)
+            .collect();
+        if !self.is_majority(reports.len()) {
+            return None;
+        }
+        Some(
+            reports
+                .into_iter()
+                .flatten()
+                .max_by_key(|(ballot, _)| *ballot)
+                .map_or(Vote::RolledBack, |(_, vote)| vote),
+        )
+    }
+
+    fn add_instance_actions(
+        &self,
+        instance_id: usize,
+        instance: &Instance,
+        participant: &Transaction,
+        actions: &mut Vec<PaxosCommitAction>,
+    ) {
+        if self.chosen(instance).is_some() {
+            // The vote of the instance can no longer change, and the leader has learned it.
+            return;
+        }
+
+        // The leader.
+        if participant.state() == TransactionState::Active && !instance.recovering {
+            // The leader times out waiting for the vote of the participant.
+            actions.push(PaxosCommitAction::Phase1a(instance_id));
+        }
+        if instance.recovering
+            && !instance
+                .proposals
+                .iter()
+                .any(|(ballot, _)| *ballot == RECOVERY_BALLOT)
+        {
+            actions.push(PaxosCommitAction::Phase2a(instance_id));
+        }
+
+        // The acceptors.
+        let num_crashed = instance
+            .acceptors
+            .iter()
+            .filter(|acceptor| acceptor.crashed)
+            .count();
+        for (acceptor_id, acceptor) in instance.acceptors.iter().enumerate() {
+            if acceptor.crashed {
+                continue;
+            }
+            if instance.recovering && !acceptor.joined {
+                actions.push(PaxosCommitAction::Phase1b(instance_id, acceptor_id));
+            }
+            for (ballot, _) in &instance.proposals {
+                if *ballot >= acceptor.ballot()
+                    && !acceptor
+                        .accepted
+                        .iter()
+                        .any(|(accepted_ballot, _)| accepted_ballot == ballot)
+                {
+                    actions.push(PaxosCommitAction::Phase2b(
+                        instance_id,
+                        acceptor_id,
+                        *ballot,
+                    ));
+                }
+            }
+            if num_crashed < self.num_faults {
+                actions.push(PaxosCommitAction::Crash(instance_id, acceptor_id));
+            }
+        }
+    }
+
+    fn vote(last_state: &PaxosCommitSystem, participant: usize, vote: Vote) -> PaxosCommitSystem {
+        let mut state = last_state.clone();
+        let transaction = &mut state.participants[participant];
+        match vote {
+            Vote::Prepared => transaction.prepare(),
+            Vote::RolledBack => transaction.rollback(),
+        };
+        let proposals = &mut state.instances[participant].proposals;
+        proposals.push((INITIAL_BALLOT, vote));
+        proposals.sort();
+        state
+    }
+
+    fn learn(last_state: &PaxosCommitSystem, participant: usize) -> Option<PaxosCommitSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.participants[participant];
+        let learned = match last_state.outcome? {
+            Vote::Prepared => transaction.commit(),
+            Vote::RolledBack => transaction.rollback(),
+        };
+        learned.then_some(state)
+    }
+
+    /// Applies the change to the acceptor, and sorts the acceptors of the instance.
+    fn update_acceptor<F: FnOnce(&mut Acceptor)>(
+        last_state: &PaxosCommitSystem,
+        instance_id: usize,
+        acceptor_id: usize,
+        f: F,
+    ) -> PaxosCommitSystem {
+        let mut state = last_state.clone();
+        let acceptors = &mut state.instances[instance_id].acceptors;
+        f(&mut acceptors[acceptor_id]);
+        acceptors.sort();
+        state
+    }
+
+    fn decide(&self, last_state: &PaxosCommitSystem) -> Option<PaxosCommitSystem> {
+        let votes: Vec<Option<Vote>> = last_state
+            .instances
+            .iter()
+            .map(|instance| self.chosen(instance))
+            .collect();
+        let outcome = if votes.contains(&Some(Vote::RolledBack)) {
+            Vote::RolledBack
+        } else if votes.iter().all(|vote| *vote == Some(Vote::Prepared)) {
+            Vote::Prepared
+        } else {
+            return None;
+        };
+        let mut state = last_state.clone();
+        state.outcome = Some(outcome);
+        Some(state)
+    }
+}
+
+impl Model for PaxosCommitModel {
+    type State = PaxosCommitSystem;
+    type Action = PaxosCommitAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let mut transaction = Transaction::default();
+        transaction.start();
+        let instance = Instance {
+            acceptors: vec![
+                Acceptor {
+                    crashed: false,
+                    joined: false,
+                    report: None,
+                    accepted: Vec::new(),
+                };
+                2 * self.num_faults + 1
+            ],
+            recovering: false,
+            proposals: Vec::new(),
+        };
+        vec![PaxosCommitSystem {
+            participants: vec![transaction; self.num_participants],
+            instances: vec![instance; self.num_participants],
+            outcome: None,
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        for (participant, transaction) in state.participants.iter().enumerate() {
+            match transaction.state() {
+                TransactionState::Active => {
+                    actions.push(PaxosCommitAction::Prepare(participant));
+                    actions.push(PaxosCommitAction::ChooseToRollback(participant));
+                }
+                TransactionState::Prepared if state.outcome.is_some() => {
+                    actions.push(PaxosCommitAction::Learn(participant));
+                }
+                _ => (),
+            }
+        }
+        if state.outcome.is_some() {
+            return;
+        }
+        actions.push(PaxosCommitAction::Decide);
+        for (instance_id, instance) in state.instances.iter().enumerate() {
+            self.add_instance_actions(
+                instance_id,
+                instance,
+                &state.participants[instance_id],
+                actions,
+            );
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            PaxosCommitAction::Prepare(participant) => {
+                Some(Self::vote(last_state, participant, Vote::Prepared))
+            }
+            PaxosCommitAction::ChooseToRollback(participant) => {
+                Some(Self::vote(last_state, participant, Vote::RolledBack))
+            }
+            PaxosCommitAction::Learn(participant) => Self::learn(last_state, participant),
+            PaxosCommitAction::Phase1a(instance_id) => {
+                let mut state = last_state.clone();
+                state.instances[instance_id].recovering = true;
+                Some(state)
+            }
+            PaxosCommitAction::Phase1b(instance_id, acceptor_id) => Some(Self::update_acceptor(
+                last_state,
+                instance_id,
+                acceptor_id,
+                |acceptor| {
+                    acceptor.joined = true;
+                    acceptor.report = acceptor.accepted.last().copied();
+                },
+            )),
+            PaxosCommitAction::Phase2a(instance_id) => {
+                let vote = self.recovery_vote(&last_state.instances[instance_id])?;
+                let mut state = last_state.clone();
+                state.instances[instance_id]
+                    .proposals
+                    .push((RECOVERY_BALLOT, vote));
+                Some(state)
+            }
+            PaxosCommitAction::Phase2b(instance_id, acceptor_id, ballot) => {
+                let proposal = *last_state.instances[instance_id]
+                    .proposals
+                    .iter()
+                    .find(|(proposed_ballot, _)| *proposed_ballot == ballot)?;
+                Some(Self::update_acceptor(
+                    last_state,
+                    instance_id,
+                    acceptor_id,
+                    |acceptor| acceptor.accepted.push(proposal),
+                ))
+            }
+            PaxosCommitAction::Decide => self.decide(last_state),
+            PaxosCommitAction::Crash(instance_id, acceptor_id) => Some(Self::update_acceptor(
+                last_state,
+                instance_id,
+                acceptor_id,
+                |acceptor| acceptor.crashed = true,
+            )),
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("consistency", |_, state| {
+                // No participant commits the transaction if another participant rolled it back.
+                let committed = state
+                    .participants
+                    .iter()
+                    .any(|transaction| transaction.state() == TransactionState::Committed);
+                let rolled_back = state
+                    .participants
+                    .iter()
+                    .any(|transaction| transaction.state() == TransactionState::RolledBack);
+                !(committed && rolled_back)
+            }),
+            Property::<Self>::always("commit validity", |_, state| {
+                // The transaction is committed only if all the participants voted for commit.
+                state.outcome != Some(Vote::Prepared)
+                    || state.instances.iter().all(|instance| {
+                        instance
+                            .proposals
+                            .contains(&(INITIAL_BALLOT, Vote::Prepared))
+                    })
+            }),
+            Property::<Self>::eventually("non-blocking termination", |_, state| {
+                // Every participant learns the outcome even if acceptors fail.
+                state
+                    .participants
+                    .iter()
+                    .all(|transaction| transaction.state().is_terminal())
+            }),
+            Property::<Self>::sometimes("commit", |_, state| {
+                state
+                    .participants
+                    .iter()
+                    .all(|transaction| transaction.state() == TransactionState::Committed)
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{Model, PaxosCommitModel};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn paxos_commit() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = PaxosCommitModel::new(2, 0)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn acceptor_failure() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = PaxosCommitModel::new(1, 1)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
@@ -0,0 +1,335 @@
+//! This is a model of the Percolator commit protocol.
+//!
+//! # Prewrite
+//!
+//! The client writes a lock on every key that the transaction updates, the primary key first. The
+//! lock on the primary key is the primary lock, and the locks on the other keys are secondary locks
+//! that point to the primary key. A write conflict on a key prevents the lock, and the client rolls
+//! back the transaction.
+//!
+//! # Commit
+//!
+//! Once all the keys are locked, the client replaces the primary lock with a commit record; this
+//! single-row write decides the outcome of the transaction. The client then commits the secondary
+//! keys.
+//!
+//! # Lock resolution
+//!
+//! There is no coordinator that other transactions wait for. The client may crash, or its locks
+//! may expire while it is still running, and another transaction that encounters a lock resolves
+//! it by looking up the primary key: an expired primary lock is rolled back, and a secondary lock
+//! is committed or rolled back by the outcome recorded on the primary key. The client commits the
+//! primary key only if its primary lock is still there.
+
+use stateright::{Model, Property};
+
+/// [`PercolatorSystem`] is the state of the client and the keys.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PercolatorSystem {
+    /// The phase of the client.
+    client: Client,
+
+    /// The keys that the transaction updates; the first key is the primary key.
+    keys: Vec<Key>,
+}
+
+/// The phase of the client of the transaction.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Client {
+    /// The client locks the keys.
+    Prewriting,
+
+    /// The client has committed the primary key, and commits the secondary keys.
+    Committing,
+
+    /// The client rolls back the keys that it has locked.
+    RollingBack,
+
+    /// The client has committed all the keys.
+    Committed,
+
+    /// The client has rolled back all the keys.
+    RolledBack,
+
+    /// The client has crashed, and leaves its locks to be resolved by other transactions.
+    Crashed,
+}
+
+/// The state of a key in the transaction.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Key {
+    /// The key has not been locked.
+    Unlocked,
+
+    /// The key is locked by the transaction.
+    Locked,
+
+    /// The key has a commit record of the transaction.
+    Committed,
+
+    /// The key has a rollback record of the transaction.
+    RolledBack,
+}
+
+/// [`PercolatorAction`] is a state transition of [`PercolatorSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PercolatorAction {
+    /// The client locks the key.
+    Prewrite(usize),
+
+    /// The client finds a write conflict on the key, and rolls back the transaction.
+    Conflict(usize),
+
+    /// The client replaces the primary lock with a commit record.
+    CommitPrimary,
+
+    /// The client commits the secondary key.
+    CommitSecondary(usize),
+
+    /// The client rolls back the locked key.
+    Rollback(usize),
+
+    /// The client finishes the transaction.
+    Finish,
+
+    /// The client crashes.
+    Crash,
+
+    /// Another transaction rolls back the expired primary lock.
+    ExpirePrimary,
+
+    /// Another transaction resolves the secondary lock by the outcome on the primary key.
+    ResolveSecondary(usize),
+}
+
+/// [`PercolatorModel`] implements state transitions of the Percolator commit protocol.
+#[derive(Hash)]
+pub struct PercolatorModel {
+    /// The number of keys including the primary key.
+    num_keys: usize,
+}
+
+impl PercolatorModel {
+    /// Creates a new [`PercolatorModel`].
+    #[must_use]
+    pub fn new(num_keys: usize) -> PercolatorModel {
+        PercolatorModel { num_keys }
+    }
+
+    fn update_key(
+        last_state: &PercolatorSystem,
+        key: usize,
+        from: Key,
+        to: Key,
+    ) -> Option<PercolatorSystem> {
+        if *last_state.keys.get(key)? != from {
+            return None;
+        }
+        let mut state = last_state.clone();
+        state.keys[key] = to;
+        Some(state)
+    }
+
+    fn prewrite(last_state: &PercolatorSystem, key: usize, lock: bool) -> Option<PercolatorSystem> {
+        if last_state.client != Client::Prewriting
+            || (key != 0 && last_state.keys[0] != Key::Locked)
+        {
+            // The primary key is locked first.
+            return None;
+        }
+        if lock {
+            Self::update_key(last_state, key, Key::Unlocked, Key::Locked)
+        } else if *last_state.keys.get(key)? == Key::Unlocked {
+            // A conflicting key is left unlocked.
+            Some(PercolatorSystem {
+                client: Client::RollingBack,
+                ..last_state.clone()
+            })
+        } else {
+            None
+        }
+    }
+
+    fn commit_primary(last_state: &PercolatorSystem) -> Option<PercolatorSystem> {
+        if last_state.client != Client::Prewriting {
+            return None;
+        }
+        if last_state.keys[0] == Key::RolledBack {
+            // The primary lock has expired, and the transaction has been rolled back.
+            return Some(PercolatorSystem {
+                client: Client::RollingBack,
+                ..last_state.clone()
+            });
+        }
+        if last_state.keys.iter().any(|key| *key != Key::Locked) {
+            return None;
+        }
+        let mut state = Self::update_key(last_state, 0, Key::Locked, Key::Committed)?;
+        state.client = Client::Committing;
+        Some(state)
+    }
+
+    fn finish(last_state: &PercolatorSystem) -> Option<PercolatorSystem> {
+        let client = match last_state.client {
+            Client::Committing if last_state.keys.iter().all(|key| *key == Key::Committed) => {
+                Client::Committed
+            }
+            Client::RollingBack if !last_state.keys.contains(&Key::Locked) => Client::RolledBack,
+            _ => return None,
+        };
+        Some(PercolatorSystem {
+            client,
+            ..last_state.clone()
+        })
+    }
+
+    fn resolve_secondary(last_state: &PercolatorSystem, key: usize) -> Option<PercolatorSystem> {
+        let outcome = match last_state.keys[0] {
+            Key::Committed => Key::Committed,
+            Key::RolledBack => Key::RolledBack,
+            Key::Unlocked | Key::Locked => return None,
+        };
+        if key == 0 {
+            return None;
+        }
+        Self::update_key(last_state, key, Key::Locked, outcome)
+    }
+
+    /// Returns `true` if the keys do not have both commit and rollback records.
+    fn is_atomic(state: &PercolatorSystem) -> bool {
+        !(state.keys.contains(&Key::Committed) && state.keys.contains(&Key::RolledBack))
+    }
+}
+
+impl Model for PercolatorModel {
+    type State = PercolatorSystem;
+    type Action = PercolatorAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![PercolatorSystem {
+            client: Client::Prewriting,
+            keys: vec![Key::Unlocked; self.num_keys],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        match state.client {
+            Client::Prewriting => {
+                for (key, key_state) in state.keys.iter().enumerate() {
+                    if *key_state == Key::Unlocked && (key == 0 || state.keys[0] == Key::Locked) {
+                        actions.push(PercolatorAction::Prewrite(key));
+                        actions.push(PercolatorAction::Conflict(key));
+                    }
+                }
+                if state.keys[0] == Key::RolledBack
+                    || state.keys.iter().all(|key| *key == Key::Locked)
+                {
+                    actions.push(PercolatorAction::CommitPrimary);
+                }
+            }
+            Client::Committing => {
+                for (key, key_state) in state.keys.iter().enumerate().skip(1) {
+                    if *key_state == Key::Locked {
+                        actions.push(PercolatorAction::CommitSecondary(key));
+                    }
+                }
+                actions.push(PercolatorAction::Finish);
+            }
+            Client::RollingBack => {
+                for (key, key_state) in state.keys.iter().enumerate() {
+                    if *key_state == Key::Locked {
+                        actions.push(PercolatorAction::Rollback(key));
+                    }
+                }
+                actions.push(PercolatorAction::Finish);
+            }
+            Client::Committed | Client::RolledBack | Client::Crashed => (),
+        }
+        if matches!(
+            state.client,
+            Client::Prewriting | Client::Committing | Client::RollingBack
+        ) {
+            actions.push(PercolatorAction::Crash);
+        }
+
+        // Other transactions may encounter the locks at any time.
+        if state.keys[0] == Key::Locked {
+            actions.push(PercolatorAction::ExpirePrimary);
+        }
+        for (key, key_state) in state.keys.iter().enumerate().skip(1) {
+            if *key_state == Key::Locked {
+                actions.push(PercolatorAction::ResolveSecondary(key));
+            }
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            PercolatorAction::Prewrite(key) => Self::prewrite(last_state, key, true),
+            PercolatorAction::Conflict(key) => Self::prewrite(last_state, key, false),
+            PercolatorAction::CommitPrimary => Self::commit_primary(last_state),
+            PercolatorAction::CommitSecondary(key) if last_state.client == Client::Committing => {
+                Self::update_key(last_state, key, Key::Locked, Key::Committed)
+            }
+            PercolatorAction::Rollback(key) if last_state.client == Client::RollingBack => {
+                Self::update_key(last_state, key, Key::Locked, Key::RolledBack)
+            }
+            PercolatorAction::Finish => Self::finish(last_state),
+            PercolatorAction::Crash => Some(PercolatorSystem {
+                client: Client::Crashed,
+                ..last_state.clone()
+            }),
+            PercolatorAction::ExpirePrimary => {
+                Self::update_key(last_state, 0, Key::Locked, Key::RolledBack)
+            }
+            PercolatorAction::ResolveSecondary(key) => Self::resolve_secondary(last_state, key),
+            PercolatorAction::CommitSecondary(_) | PercolatorAction::Rollback(_) => None,
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("atomicity", |_, state| Self::is_atomic(state)),
+            Property::<Self>::always("primary decides", |_, state| {
+                // A key is committed only after the primary key is committed.
+                !state.keys.contains(&Key::Committed) || state.keys[0] == Key::Committed
+            }),
+            Property::<Self>::eventually("no lock left", |_, state| {
+                !state.keys.contains(&Key::Locked)
+            }),
+            Property::<Self>::sometimes("committed", |_, state| state.client == Client::Committed),
+            Property::<Self>::sometimes("committed despite crash", |_, state| {
+                // The transaction of the crashed client is committed on all the keys.
+                state.client == Client::Crashed
+                    && state.keys.iter().all(|key| *key == Key::Committed)
+            }),
+            Property::<Self>::sometimes("expired while running", |_, state| {
+                // The client has locked all the keys, but its primary lock has expired; a
+                // conflict leaves a key unlocked instead.
+                state.client == Client::RolledBack
+                    && state.keys.iter().all(|key| *key == Key::RolledBack)
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{Model, PercolatorModel};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn percolator() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = PercolatorModel::new(3)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
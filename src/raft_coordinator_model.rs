@@ -0,0 +1,326 @@
+//! This is a model of two-phase commit with a coordinator replicated by simplified Raft.
+//!
+//! # Replication
+//!
+//! The coordinator consists of a leader replica and standby replicas. The leader collects the
+//! votes of the participants, decides, and appends the decision to its log; the decision is
+//! replicated to the standby replicas by log append messages, and is released to the participants
+//! only once a majority of the replicas have stored it.
+//!
+//! # Failover
+//!
+//! Replicas are fail-stop, and fewer than half of them may crash. When the leader crashes, a live
+//! replica whose log is at least as up-to-date as the log of every other live replica takes over;
+//! the votes collected by the old leader are lost, and the participants send their votes again to
+//! the new leader. As a decision stored by a majority survives on a live replica, the new leader
+//! never decides differently, and the participants are never blocked by a coordinator crash.
+
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// [`RaftSystem`] is the state of the participants and the coordinator replicas.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RaftSystem {
+    /// The participant transactions.
+    participants: Vec<Transaction>,
+
+    /// The coordinator replicas.
+    replicas: Vec<Replica>,
+
+    /// The current leader replica.
+    leader: usize,
+
+    /// The votes of the participants collected by the current leader.
+    votes: Vec<Option<bool>>,
+}
+
+/// [`Replica`] is a replica of the coordinator.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Replica {
+    /// The replica has crashed, and never recovers.
+    crashed: bool,
+
+    /// The decision stored in the log of the replica.
+    log: Option<TransactionState>,
+}
+
+/// [`RaftAction`] is a state transition of [`RaftSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RaftAction {
+    /// The participant votes for commit, or sends its vote again to a new leader.
+    VoteYes(usize),
+
+    /// The participant votes against commit, or sends its vote again to a new leader.
+    VoteNo(usize),
+
+    /// The leader decides to commit, and appends the decision to its log.
+    Commit,
+
+    /// The leader decides to roll back, and appends the decision to its log.
+    ///
+    /// The leader may roll back the transaction without waiting for all the votes.
+    Rollback,
+
+    /// The leader replicates its log to the standby replica.
+    Append(usize),
+
+    /// The participant learns the decision stored by a majority of the replicas.
+    Learn(usize),
+
+    /// The replica crashes.
+    Crash(usize),
+
+    /// The replica takes over the crashed leader.
+    Failover(usize),
+}
+
+/// [`RaftCoordinatorModel`] implements state transitions of two-phase commit with a replicated
+/// coordinator.
+#[derive(Hash)]
+pub struct RaftCoordinatorModel {
+    /// The number of participants.
+    num_participants: usize,
+
+    /// The number of coordinator replicas including the initial leader.
+    num_replicas: usize,
+}
+
+impl RaftCoordinatorModel {
+    /// Creates a new [`RaftCoordinatorModel`].
+    #[must_use]
+    pub fn new(num_participants: usize, num_replicas: usize) -> RaftCoordinatorModel {
+        RaftCoordinatorModel {
+            num_participants,
+            num_replicas,
+        }
+    }
+
+    /// Returns the maximum number of replicas that may crash.
+    fn max_crashes(&self) -> usize {
+        (self.num_replicas - 1) / 2
+    }
+
+    /// Returns the decision stored by a majority of the replicas.
+    fn chosen_decision(&self, state: &RaftSystem) -> Option<TransactionState> {
+        [TransactionState::Committed, TransactionState::RolledBack]
+            .into_iter()
+            .find(|decision| {
+                state
+                    .replicas
+                    .iter()
+                    .filter(|replica| replica.log == Some(*decision))
+                    .count()
+                    > self.num_replicas / 2
+            })
+    }
+
+    fn decide(last_state: &RaftSystem, decision: TransactionState) -> Option<RaftSystem> {
+        let mut state = last_state.clone();
+        let log = &mut state.replicas[state.leader].log;
+        if log.is_some() {
+            return None;
+        }
+        *log = Some(decision);
+        Some(state)
+    }
+
+    fn vote(last_state: &RaftSystem, participant: usize, yes: bool) -> Option<RaftSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.participants[participant];
+        let voted = if yes {
+            transaction.prepare() || transaction.state() == TransactionState::Prepared
+        } else {
+            transaction.rollback() || transaction.state() == TransactionState::RolledBack
+        };
+        if !voted || state.votes[participant].is_some() {
+            return None;
+        }
+        state.votes[participant] = Some(yes);
+        Some(state)
+    }
+
+    fn learn(&self, last_state: &RaftSystem, participant: usize) -> Option<RaftSystem> {
+        let mut state = last_state.clone();
+        let transaction = &mut state.participants[participant];
+        let learned = match self.chosen_decision(last_state)? {
+            TransactionState::Committed => transaction.commit(),
+            _ => transaction.rollback(),
+        };
+        learned.then_some(state)
+    }
+
+    fn is_up_to_date(state: &RaftSystem, candidate: usize) -> bool {
+        let log = state.replicas[candidate].log;
+        state
+            .replicas
+            .iter()
+            .all(|replica| replica.crashed || replica.log.is_none() || replica.log == log)
+    }
+}
+
+impl Model for RaftCoordinatorModel {
+    type State = RaftSystem;
+    type Action = RaftAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let mut transaction = Transaction::default();
+        transaction.start();
+        vec![RaftSystem {
+            participants: vec![transaction; self.num_participants],
+            replicas: vec![
+                Replica {
+                    crashed: false,
+                    log: None,
+                };
+                self.num_replicas
+            ],
+            leader: 0,
+            votes: vec![None; self.num_participants],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        let leader = &state.replicas[state.leader];
+        if leader.crashed {
+            for (replica_id, replica) in state.replicas.iter().enumerate() {
+                if !replica.crashed && Self::is_up_to_date(state, replica_id) {
+                    actions.push(RaftAction::Failover(replica_id));
+                }
+            }
+        } else {
+            for (participant, transaction) in state.participants.iter().enumerate() {
+                if state.votes[participant].is_none() {
+                    match transaction.state() {
+                        TransactionState::Active => {
+                            actions.push(RaftAction::VoteYes(participant));
+                            actions.push(RaftAction::VoteNo(participant));
+                        }
+                        TransactionState::Prepared => {
+                            actions.push(RaftAction::VoteYes(participant));
+                        }
+                        TransactionState::RolledBack => {
+                            actions.push(RaftAction::VoteNo(participant));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            match leader.log {
+                None => {
+                    if state.votes.iter().all(|vote| *vote == Some(true)) {
+                        actions.push(RaftAction::Commit);
+                    }
+                    actions.push(RaftAction::Rollback);
+                }
+                Some(decision) => {
+                    for (replica_id, replica) in state.replicas.iter().enumerate() {
+                        if !replica.crashed && replica.log != Some(decision) {
+                            actions.push(RaftAction::Append(replica_id));
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.chosen_decision(state).is_some() {
+            for (participant, transaction) in state.participants.iter().enumerate() {
+                if !transaction.state().is_terminal() {
+                    actions.push(RaftAction::Learn(participant));
+                }
+            }
+        }
+
+        let num_crashed = state
+            .replicas
+            .iter()
+            .filter(|replica| replica.crashed)
+            .count();
+        if num_crashed < self.max_crashes() {
+            for (replica_id, replica) in state.replicas.iter().enumerate() {
+                if !replica.crashed {
+                    actions.push(RaftAction::Crash(replica_id));
+                }
+            }
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            RaftAction::VoteYes(participant) => Self::vote(last_state, participant, true),
+            RaftAction::VoteNo(participant) => Self::vote(last_state, participant, false),
+            RaftAction::Commit => Self::decide(last_state, TransactionState::Committed),
+            RaftAction::Rollback => Self::decide(last_state, TransactionState::RolledBack),
+            RaftAction::Append(replica_id) => {
+                let mut state = last_state.clone();
+                state.replicas[replica_id].log = last_state.replicas[last_state.leader].log;
+                Some(state)
+            }
+            RaftAction::Learn(participant) => self.learn(last_state, participant),
+            RaftAction::Crash(replica_id) => {
+                let mut state = last_state.clone();
+                state.replicas[replica_id].crashed = true;
+                Some(state)
+            }
+            RaftAction::Failover(replica_id) => {
+                let mut state = last_state.clone();
+                state.leader = replica_id;
+                state.votes = vec![None; self.num_participants];
+                Some(state)
+            }
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("agreement", |_, state| {
+                // No two participants reach different decisions.
+                let decisions = state
+                    .participants
+                    .iter()
+                    .map(Transaction::state)
+                    .filter(|state| state.is_terminal());
+                decisions.clone().all(|decision| {
+                    decisions
+                        .clone()
+                        .all(|other_decision| decision == other_decision)
+                })
+            }),
+            Property::<Self>::eventually("termination", |_, state| {
+                // A coordinator crash never blocks the participants.
+                state
+                    .participants
+                    .iter()
+                    .all(|transaction| transaction.state().is_terminal())
+            }),
+            Property::<Self>::sometimes("commit", |_, state| {
+                state
+                    .participants
+                    .iter()
+                    .all(|transaction| transaction.state() == TransactionState::Committed)
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{Model, RaftCoordinatorModel};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn raft_coordinator() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = RaftCoordinatorModel::new(2, 3)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
@@ -0,0 +1,208 @@
+//! This is a model of a saga of compensating transactions.
+//!
+//! # Steps
+//!
+//! Unlike two-phase commit, a participant never holds a prepared state: it executes its local
+//! step as an independent transaction that commits immediately, or fails to execute it. The
+//! orchestrator requests the steps concurrently.
+//!
+//! # Compensation
+//!
+//! When a step fails, the orchestrator stops requesting steps, and undoes every executed step by
+//! its compensating action. Compensating actions never fail; they are retried until they succeed.
+
+use stateright::{Model, Property};
+
+/// [`SagaSystem`] is the state of the orchestrator and the participants.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SagaSystem {
+    /// The phase of the orchestrator.
+    phase: Phase,
+
+    /// The local steps of the participants.
+    steps: Vec<Step>,
+}
+
+/// The phase of the saga orchestrator.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Phase {
+    /// The orchestrator requests the steps.
+    Forward,
+
+    /// A step has failed, and the orchestrator compensates the executed steps.
+    Compensating,
+
+    /// All the steps have been executed.
+    Completed,
+
+    /// All the executed steps have been compensated.
+    Aborted,
+}
+
+/// The state of the local step of a participant.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Step {
+    /// The step has not been executed.
+    Pending,
+
+    /// The step has been executed and committed locally.
+    Executed,
+
+    /// The step has failed, and has no effect.
+    Failed,
+
+    /// The executed step has been undone by its compensating action.
+    Compensated,
+}
+
+/// [`SagaAction`] is a state transition of [`SagaSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SagaAction {
+    /// The participant executes its step.
+    Execute(usize),
+
+    /// The participant fails to execute its step.
+    Fail(usize),
+
+    /// The participant compensates its executed step.
+    Compensate(usize),
+
+    /// The orchestrator completes the saga.
+    Complete,
+
+    /// The orchestrator aborts the saga.
+    Abort,
+}
+
+/// [`SagaModel`] implements state transitions of a saga.
+#[derive(Hash)]
+pub struct SagaModel {
+    /// The number of participants.
+    num_participants: usize,
+}
+
+impl SagaModel {
+    /// Creates a new [`SagaModel`].
+    #[must_use]
+    pub fn new(num_participants: usize) -> SagaModel {
+        SagaModel { num_participants }
+    }
+
+    fn update_step(
+        last_state: &SagaSystem,
+        participant: usize,
+        from: Step,
+        to: Step,
+    ) -> Option<SagaSystem> {
+        if last_state.steps[participant] != from {
+            return None;
+        }
+        let mut state = last_state.clone();
+        state.steps[participant] = to;
+        if to == Step::Failed {
+            state.phase = Phase::Compensating;
+        }
+        Some(state)
+    }
+}
+
+impl Model for SagaModel {
+    type State = SagaSystem;
+    type Action = SagaAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        vec![SagaSystem {
+            phase: Phase::Forward,
+            steps: vec![Step::Pending; self.num_participants],
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        match state.phase {
+            Phase::Forward => {
+                for (participant, step) in state.steps.iter().enumerate() {
+                    if *step == Step::Pending {
+                        actions.push(SagaAction::Execute(participant));
+                        actions.push(SagaAction::Fail(participant));
+                    }
+                }
+                if state.steps.iter().all(|step| *step == Step::Executed) {
+                    actions.push(SagaAction::Complete);
+                }
+            }
+            Phase::Compensating => {
+                for (participant, step) in state.steps.iter().enumerate() {
+                    if *step == Step::Executed {
+                        actions.push(SagaAction::Compensate(participant));
+                    }
+                }
+                if !state.steps.contains(&Step::Executed) {
+                    actions.push(SagaAction::Abort);
+                }
+            }
+            Phase::Completed | Phase::Aborted => (),
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        match action {
+            SagaAction::Execute(participant) => {
+                Self::update_step(last_state, participant, Step::Pending, Step::Executed)
+            }
+            SagaAction::Fail(participant) => {
+                Self::update_step(last_state, participant, Step::Pending, Step::Failed)
+            }
+            SagaAction::Compensate(participant) => {
+                Self::update_step(last_state, participant, Step::Executed, Step::Compensated)
+            }
+            SagaAction::Complete => Some(SagaSystem {
+                phase: Phase::Completed,
+                ..last_state.clone()
+            }),
+            SagaAction::Abort => Some(SagaSystem {
+                phase: Phase::Aborted,
+                ..last_state.clone()
+            }),
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("all or nothing", |_, state| {
+                // Either all the steps complete, or all the executed steps are compensated.
+                match state.phase {
+                    Phase::Forward | Phase::Compensating => true,
+                    Phase::Completed => state.steps.iter().all(|step| *step == Step::Executed),
+                    Phase::Aborted => !state.steps.contains(&Step::Executed),
+                }
+            }),
+            Property::<Self>::eventually("termination", |_, state| {
+                matches!(state.phase, Phase::Completed | Phase::Aborted)
+            }),
+            Property::<Self>::sometimes("compensated", |_, state| {
+                state.phase == Phase::Aborted && state.steps.contains(&Step::Compensated)
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{Model, SagaModel};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn saga() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = SagaModel::new(3)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
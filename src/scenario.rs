@@ -0,0 +1,160 @@
+//! This is a file format of fault-injection scenarios of the transaction model.
+//!
+//! # Format
+//!
+//! A scenario is a JSON document that names the scenario, and lists the faults that are enabled
+//! along with their budgets and the nodes that they may affect; a fault that is absent is
+//! disabled. For example, the following scenario lets participant 1 crash once, and the link
+//! between participant 2 and the coordinator fail in one direction:
+//!
+//! ```json
+//! {
+//!     "name": "participant crash and one-way partition",
+//!     "crashes": { "budget": 1, "nodes": [1] },
+//!     "link_failures": { "budget": 1, "nodes": [2] }
+//! }
+//! ```
+//!
+//! A fault without a budget may happen any number of times, and a fault without nodes may affect
+//! any node. A link failure affects the links between the listed nodes and their parents.
+
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+/// [`Scenario`] is a named set of faults that are injected into the transaction model.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    /// The name of the scenario.
+    pub name: String,
+
+    /// Nodes crash, and restart unless `fail_stop` is set.
+    pub crashes: Option<Fault>,
+
+    /// Crashed nodes stay down forever.
+    pub fail_stop: bool,
+
+    /// The undecided coordinator may crash, and forget the transaction.
+    pub coordinator_amnesia: bool,
+
+    /// The logs of the participants fill up.
+    pub log_write_failures: Option<Fault>,
+
+    /// Directed links of the asynchronous network fail, and the messages sent over them are lost.
+    pub link_failures: Option<Fault>,
+
+    /// The number of delivered messages that the asynchronous network duplicates.
+    pub duplicates: usize,
+
+    /// The participant that may die permanently along with the coordinator.
+    pub dead_participant: Option<usize>,
+}
+
+/// [`Fault`] is the budget of a kind of fault, and the nodes that it may affect.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fault {
+    /// The maximum number of the faults, or `None` if they happen any number of times.
+    pub budget: Option<usize>,
+
+    /// The nodes that the faults may affect, or `None` if they may affect any node.
+    pub nodes: Option<Vec<usize>>,
+}
+
+/// [`ScenarioError`] describes why a scenario cannot be loaded.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ScenarioError {
+    /// The document is not a valid scenario.
+    Malformed(String),
+
+    /// The scenario refers to a node that does not exist.
+    UnknownNode(usize),
+}
+
+impl Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Malformed(error) => write!(f, "malformed scenario: {error}"),
+            ScenarioError::UnknownNode(node_id) => write!(f, "unknown node {node_id}"),
+        }
+    }
+}
+
+impl Fault {
+    /// Returns `true` if the fault may affect the node.
+    #[must_use]
+    pub fn affects(&self, node_id: usize) -> bool {
+        self.nodes
+            .as_ref()
+            .is_none_or(|nodes| nodes.contains(&node_id))
+    }
+}
+
+impl Scenario {
+    /// Loads a [`Scenario`] from the JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is malformed, or refers to a node out of `num_nodes`.
+    pub fn load(json: &str, num_nodes: usize) -> Result<Scenario, ScenarioError> {
+        let scenario: Scenario = serde_json::from_str(json)
+            .map_err(|error| ScenarioError::Malformed(error.to_string()))?;
+        let unknown_node = [
+            &scenario.crashes,
+            &scenario.log_write_failures,
+            &scenario.link_failures,
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|fault| fault.nodes.as_ref())
+        .flatten()
+        .copied()
+        .chain(scenario.dead_participant)
+        .find(|node_id| *node_id >= num_nodes);
+        match unknown_node {
+            Some(node_id) => Err(ScenarioError::UnknownNode(node_id)),
+            None => Ok(scenario),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Fault, Scenario, ScenarioError};
+
+    #[test]
+    fn load() {
+        let scenario = Scenario::load(
+            r#"{"name": "crash", "crashes": {"budget": 1, "nodes": [1]}, "duplicates": 2}"#,
+            3,
+        )
+        .unwrap();
+        assert_eq!(
+            scenario,
+            Scenario {
+                name: "crash".to_string(),
+                crashes: Some(Fault {
+                    budget: Some(1),
+                    nodes: Some(vec![1]),
+                }),
+                duplicates: 2,
+                ..Scenario::default()
+            }
+        );
+        assert!(scenario.crashes.unwrap().affects(1));
+        assert!(Fault::default().affects(0));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Scenario::load(r#"{"link_failures": {"nodes": [3]}}"#, 3),
+            Err(ScenarioError::UnknownNode(3))
+        );
+        assert!(matches!(
+            Scenario::load(r#"{"partitions": 1}"#, 3),
+            Err(ScenarioError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! This is a minimizer of the discoveries of the model checker.
+//!
+//! A discovery of the depth-first search is often long, and most of its actions, such as crashes
+//! and retransmissions, have nothing to do with the discovery. The minimizer repeatedly removes
+//! chunks of actions, halving the chunk size down to single actions, and keeps a removal if the
+//! remaining actions can still be taken from the initial state and reach a discovery of the
+//! property. The result is minimal in that no single action can be removed from it.
+//!
+//! A discovery of an eventually-property depends on the path ending in a terminal state, which the
+//! minimizer does not check, so it is left as it is.
+
+use stateright::{Expectation, Model, Path, Property};
+
+/// Returns the actions of the discovery of the property with as many actions removed as possible.
+pub fn shrink<M: Model>(
+    model: &M,
+    property: &Property<M>,
+    path: Path<M::State, M::Action>,
+) -> Vec<M::Action>
+where
+    M::Action: Clone + PartialEq,
+{
+    let mut steps = path.into_vec().into_iter();
+    let Some((init_state, first_action)) = steps.next() else {
+        return Vec::new();
+    };
+    let mut actions: Vec<M::Action> = first_action
+        .into_iter()
+        .chain(steps.filter_map(|(_, action)| action))
+        .collect();
+    let Some(length) = discovery_length(model, property, &init_state, &actions) else {
+        return actions;
+    };
+    actions.truncate(length);
+
+    let mut chunk = (actions.len() / 2).max(1);
+    loop {
+        let mut removed = false;
+        let mut start = 0;
+        while start < actions.len() {
+            let end = (start + chunk).min(actions.len());
+            let candidate: Vec<M::Action> = actions[..start]
+                .iter()
+                .chain(&actions[end..])
+                .cloned()
+                .collect();
+            if let Some(length) = discovery_length(model, property, &init_state, &candidate) {
+                actions = candidate;
+                actions.truncate(length);
+                removed = true;
+            } else {
+                start = end;
+            }
+        }
+        if !removed {
+            if chunk == 1 {
+                return actions;
+            }
+            chunk /= 2;
+        }
+    }
+}
+
+/// Returns the number of the actions taken from the initial state until the first discovery of
+/// the property, or `None` if an action is not enabled or no discovery is reached.
+fn discovery_length<M: Model>(
+    model: &M,
+    property: &Property<M>,
+    init_state: &M::State,
+    actions: &[M::Action],
+) -> Option<usize>
+where
+    M::Action: PartialEq,
+{
+    let is_discovery = |state: &M::State| match property.expectation {
+        Expectation::Always => !(property.condition)(model, state),
+        Expectation::Sometimes => (property.condition)(model, state),
+        Expectation::Eventually => false,
+    };
+    if is_discovery(init_state) {
+        return Some(0);
+    }
+    let mut steps = model.next_steps(init_state);
+    for (index, action) in actions.iter().enumerate() {
+        let (_, state) = steps
+            .into_iter()
+            .find(|(next_action, _)| next_action == action)?;
+        if is_discovery(&state) {
+            return Some(index + 1);
+        }
+        steps = model.next_steps(&state);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{discovery_length, shrink};
+    use crate::transaction_model::TransactionModel;
+
+    use stateright::{Checker, Model};
+
+    #[test]
+    fn shrink_discovery() {
+        let model = TransactionModel::new(3, false).heuristics(true);
+        let checker = TransactionModel::new(3, false)
+            .heuristics(true)
+            .checker()
+            .spawn_dfs()
+            .join();
+        let path = checker.discovery("no heuristic-mixed outcome").unwrap();
+        let init_state = path.clone().into_vec().remove(0).0;
+        let length = path.clone().into_actions().len();
+        let property = model.property("no heuristic-mixed outcome");
+
+        let actions = shrink(&model, &property, path);
+        assert!(actions.len() <= length);
+        assert_eq!(
+            discovery_length(&model, &property, &init_state, &actions),
+            Some(actions.len())
+        );
+        for index in 0..actions.len() {
+            let mut removed = actions.clone();
+            removed.remove(index);
+            assert_eq!(
+                discovery_length(&model, &property, &init_state, &removed),
+                None
+            );
+        }
+    }
+}
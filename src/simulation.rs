@@ -0,0 +1,198 @@
+//! This is a random simulation of a model, as a quick alternative to exhaustive checking.
+//!
+//! Each run is a random walk from an initial state that takes up to a number of steps, picking one
+//! of the enabled actions uniformly at random, and checks the properties of the model along the
+//! way. A step that does not change the state is never picked, as a walk would otherwise spend
+//! most of its steps retransmitting; a run without any other step has ended. An always-property
+//! is violated by a state in which it does not hold, and an eventually-property by a run that
+//! ends before it has held. A run stops at the first violation.
+//!
+//! The runs are seeded one after another from the seed of the simulation, and a violation is
+//! reported with the seed of its run, so that the run is reproduced by simulating a single run
+//! with that seed.
+
+use stateright::{Expectation, Model};
+
+/// [`SimulationReport`] is the result of a random simulation.
+#[derive(Debug, Eq, PartialEq)]
+pub struct SimulationReport<A> {
+    /// The number of runs.
+    pub runs: usize,
+
+    /// The number of steps taken in all the runs.
+    pub steps: usize,
+
+    /// The first violation of each violated property, in the order of discovery.
+    pub violations: Vec<Violation<A>>,
+}
+
+/// [`Violation`] is a run that violates a property.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Violation<A> {
+    /// The name of the violated property.
+    pub property: &'static str,
+
+    /// The seed that reproduces the run.
+    pub seed: u64,
+
+    /// The actions taken from the initial state up to the violation.
+    pub actions: Vec<A>,
+}
+
+/// Simulates `runs` random runs of at most `max_steps` steps each, seeded from `seed`.
+pub fn simulate<M: Model>(
+    model: &M,
+    seed: u64,
+    max_steps: usize,
+    runs: usize,
+) -> SimulationReport<M::Action>
+where
+    M::Action: Clone,
+    M::State: PartialEq,
+{
+    let mut report = SimulationReport {
+        runs,
+        steps: 0,
+        violations: Vec::new(),
+    };
+    for run_seed in (0..runs as u64).map(|run| seed.wrapping_add(run)) {
+        let (steps, violations) = simulate_run(model, run_seed, max_steps);
+        report.steps += steps;
+        for violation in violations {
+            if report
+                .violations
+                .iter()
+                .all(|known| known.property != violation.property)
+            {
+                report.violations.push(violation);
+            }
+        }
+    }
+    report
+}
+
+/// Simulates a run, and returns the number of steps taken along with the violations.
+fn simulate_run<M: Model>(
+    model: &M,
+    seed: u64,
+    max_steps: usize,
+) -> (usize, Vec<Violation<M::Action>>)
+where
+    M::Action: Clone,
+    M::State: PartialEq,
+{
+    let mut rng = Rng(seed);
+    let properties = model.properties();
+    let mut init_states: Vec<M::State> = model
+        .init_states()
+        .into_iter()
+        .filter(|state| model.within_boundary(state))
+        .collect();
+    if init_states.is_empty() {
+        return (0, Vec::new());
+    }
+    let mut state = init_states.swap_remove(rng.below(init_states.len()));
+    let mut actions = Vec::new();
+    let mut satisfied = vec![false; properties.len()];
+    loop {
+        let mut violated = Vec::new();
+        for (index, property) in properties.iter().enumerate() {
+            let holds = (property.condition)(model, &state);
+            match property.expectation {
+                Expectation::Always if !holds => violated.push(property.name),
+                Expectation::Eventually if holds => satisfied[index] = true,
+                _ => {}
+            }
+        }
+
+        let mut next_steps: Vec<(M::Action, M::State)> = if violated.is_empty() {
+            model
+                .next_steps(&state)
+                .into_iter()
+                .filter(|(_, next_state)| *next_state != state && model.within_boundary(next_state))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if violated.is_empty() && next_steps.is_empty() {
+            // The run has ended before the eventually-properties that have not held.
+            violated.extend(
+                properties
+                    .iter()
+                    .zip(&satisfied)
+                    .filter(|(property, satisfied)| {
+                        matches!(property.expectation, Expectation::Eventually) && !**satisfied
+                    })
+                    .map(|(property, _)| property.name),
+            );
+        }
+        if !violated.is_empty() || next_steps.is_empty() || actions.len() == max_steps {
+            let steps = actions.len();
+            let violations = violated
+                .into_iter()
+                .map(|property| Violation {
+                    property,
+                    seed,
+                    actions: actions.clone(),
+                })
+                .collect();
+            return (steps, violations);
+        }
+
+        let (action, next_state) = next_steps.swap_remove(rng.below(next_steps.len()));
+        actions.push(action);
+        state = next_state;
+    }
+}
+
+/// [`Rng`] is a `SplitMix64` pseudo-random number generator.
+struct Rng(u64);
+
+impl Rng {
+    /// Returns the next pseudo-random number.
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random number below `bound`, which must not be zero.
+    fn below(&mut self, bound: usize) -> usize {
+        usize::try_from(self.next() % bound as u64).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::simulate;
+    use crate::transaction_model::TransactionModel;
+
+    use stateright::Model;
+
+    #[test]
+    fn reproducible() {
+        let model = TransactionModel::new(3, false).byzantine_participant(2);
+        let report = simulate(&model, 7, 50, 200);
+        assert_eq!(report.runs, 200);
+        assert_eq!(report, simulate(&model, 7, 50, 200));
+
+        // The Byzantine participant forges a vote for commit without preparing the transaction.
+        let violation = report
+            .violations
+            .iter()
+            .find(|violation| violation.property == "commit validity")
+            .unwrap();
+        let rerun = simulate(&model, violation.seed, 50, 1);
+        assert!(rerun.violations.contains(violation));
+
+        let mut state = model.init_states().remove(0);
+        for action in &violation.actions {
+            state = model.next_state(&state, action.clone()).unwrap();
+        }
+        assert!(!(model.property("commit validity").condition)(
+            &model, &state
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+//! This is a reachable state graph of a model.
+//!
+//! The graph is built by a breadth-first search from the initial states, and is small enough to
+//! be analyzed or exported only for small model configurations.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+use stateright::Model;
+
+/// [`StateGraph`] is the reachable state graph of a model.
+pub struct StateGraph<M: Model> {
+    /// The reachable states.
+    pub states: Vec<M::State>,
+
+    /// The indexes of the initial states.
+    pub init_states: Vec<usize>,
+
+    /// The outgoing edges of each state as pairs of a formatted action and a target state index.
+    pub edges: Vec<Vec<(String, usize)>>,
+
+    /// The exploration stopped before visiting all the reachable states.
+    pub truncated: bool,
+}
+
+impl<M: Model> StateGraph<M>
+where
+    M::State: Hash,
+    M::Action: Debug,
+{
+    /// Builds the state graph with at most `max_states` states.
+    pub fn build(model: &M, max_states: usize) -> StateGraph<M> {
+        let mut graph = StateGraph {
+            states: Vec::new(),
+            init_states: Vec::new(),
+            edges: Vec::new(),
+            truncated: false,
+        };
+        let mut index_map: HashMap<u64, usize> = HashMap::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+
+        for state in model.init_states() {
+            if let Some(index) = graph.insert(&mut index_map, state, max_states) {
+                if !graph.init_states.contains(&index) {
+                    graph.init_states.push(index);
+                    queue.push_back(index);
+                }
+            }
+        }
+
+        while let Some(index) = queue.pop_front() {
+            if !model.within_boundary(&graph.states[index]) {
+                continue;
+            }
+            for (action, next_state) in model.next_steps(&graph.states[index]) {
+                let num_states = graph.states.len();
+                let Some(next_index) = graph.insert(&mut index_map, next_state, max_states) else {
+                    continue;
+                };
+                if next_index == num_states {
+                    queue.push_back(next_index);
+                }
+                graph.edges[index].push((model.format_action(&action), next_index));
+            }
+        }
+
+        graph
+    }
+
+    /// Inserts the state if it is new, and returns its index.
+    fn insert(
+        &mut self,
+        index_map: &mut HashMap<u64, usize>,
+        state: M::State,
+        max_states: usize,
+    ) -> Option<usize> {
+        let fingerprint = fingerprint(&state);
+        if let Some(index) = index_map.get(&fingerprint) {
+            return Some(*index);
+        }
+        if self.states.len() >= max_states {
+            self.truncated = true;
+            return None;
+        }
+        let index = self.states.len();
+        index_map.insert(fingerprint, index);
+        self.states.push(state);
+        self.edges.push(Vec::new());
+        Some(index)
+    }
+}
+
+/// Returns the fingerprint of the state.
+pub fn fingerprint<T: Hash>(state: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::StateGraph;
+    use crate::lock_model::{LockModel, PreemptionPolicy};
+
+    use stateright::{Checker, Model};
+
+    #[test]
+    fn agrees_with_checker() {
+        let model = LockModel::new(2, 2, PreemptionPolicy::WaitDie);
+        let graph = StateGraph::build(&model, usize::MAX);
+        assert!(!graph.truncated);
+        assert_eq!(graph.init_states, vec![0]);
+
+        let checker = model.checker().spawn_bfs().join();
+        assert_eq!(graph.states.len(), checker.unique_state_count());
+    }
+
+    #[test]
+    fn truncate() {
+        let model = LockModel::new(2, 2, PreemptionPolicy::WaitDie);
+        let graph = StateGraph::build(&model, 5);
+        assert!(graph.truncated);
+        assert_eq!(graph.states.len(), 5);
+    }
+}
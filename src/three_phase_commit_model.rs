@@ -0,0 +1,374 @@
+//! This is a three-phase commit model implementation.
+//!
+//! # The Algorithm
+//!
+//! ## Voting
+//!
+//! The coordinator asks all the participants whether they can commit the transaction, and each
+//! participant votes for or against commit.
+//!
+//! ## Pre-commit
+//!
+//! If all the participants voted for commit, the coordinator pre-commits the transaction and
+//! sends a pre-commit message to all the participants. A participant acknowledges the message
+//! after pre-committing the transaction.
+//!
+//! ## Commit
+//!
+//! Once all the live participants acknowledged the pre-commit message, the coordinator commits
+//! the transaction and sends a commit message to the participants. If any participant voted
+//! against commit, the coordinator rolls back the transaction instead.
+//!
+//! ## Termination
+//!
+//! Nodes fail by stopping, and the failure of a node is eventually detected by the other nodes.
+//! If the coordinator fails, a participant that times out runs the termination protocol: it
+//! pre-commits the transaction if any live participant has pre-committed it, commits the
+//! transaction once all the live participants have pre-committed it, and rolls back the
+//! transaction if no live participant has pre-committed it. Unlike the two-phase commit protocol,
+//! participants are never blocked by a failed coordinator.
+
+use super::message_complexity::MessageCount;
+use super::transaction::State as TransactionState;
+use super::transaction::Transaction;
+
+use stateright::{Model, Property};
+
+/// [`ThreePhaseNode`] is a node of the three-phase commit protocol.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ThreePhaseNode {
+    /// The only transaction on the node.
+    transaction: Transaction,
+
+    /// The node has stopped.
+    crashed: bool,
+
+    /// The participant voted for commit.
+    voted_yes: bool,
+
+    /// The coordinator received the acknowledgement of the pre-commit message.
+    acked_pre_commit: bool,
+}
+
+/// [`ThreePhaseSystem`] is the state of the nodes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ThreePhaseSystem {
+    nodes: Vec<ThreePhaseNode>,
+}
+
+/// [`ThreePhaseAction`] is a state transition of [`ThreePhaseSystem`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ThreePhaseAction {
+    /// The coordinator asks the participants to vote.
+    RequestVote,
+
+    /// The participant votes for commit.
+    VoteYes(usize),
+
+    /// The participant votes against commit.
+    VoteNo(usize),
+
+    /// The coordinator pre-commits the transaction after collecting all the votes.
+    DecidePreCommit,
+
+    /// The participant pre-commits the transaction and acknowledges it.
+    PreCommit(usize),
+
+    /// The coordinator commits the transaction after collecting all the acknowledgements.
+    DecideCommit,
+
+    /// The participant commits the transaction.
+    Commit(usize),
+
+    /// The participant rolls back the transaction.
+    Rollback(usize),
+
+    /// The node stops.
+    Crash(usize),
+
+    /// The node times out waiting for a failed node.
+    Timeout(usize),
+}
+
+/// [`ThreePhaseCommitModel`] implements state transitions of the three-phase commit protocol.
+#[derive(Hash)]
+pub struct ThreePhaseCommitModel {
+    /// The number of nodes.
+    num_nodes: usize,
+}
+
+impl ThreePhaseCommitModel {
+    /// Creates a new [`ThreePhaseCommitModel`].
+    #[must_use]
+    pub fn new(num_nodes: usize) -> ThreePhaseCommitModel {
+        ThreePhaseCommitModel { num_nodes }
+    }
+
+    /// Returns the fixed coordinator node ID.
+    #[must_use]
+    pub const fn coordinator_node_id() -> usize {
+        0
+    }
+
+    fn participants(
+        state: &ThreePhaseSystem,
+    ) -> impl Clone + Iterator<Item = (usize, &ThreePhaseNode)> {
+        state.nodes.iter().enumerate().skip(1)
+    }
+
+    fn add_coordinator_actions(state: &ThreePhaseSystem, actions: &mut Vec<ThreePhaseAction>) {
+        let coordinator = &state.nodes[Self::coordinator_node_id()];
+        match coordinator.transaction.state() {
+            TransactionState::Active => actions.push(ThreePhaseAction::RequestVote),
+            TransactionState::Prepared => {
+                if Self::participants(state).all(|(_, node)| node.voted_yes) {
+                    actions.push(ThreePhaseAction::DecidePreCommit);
+                } else if Self::participants(state).any(|(_, node)| node.crashed && !node.voted_yes)
+                {
+                    // A failed participant will never vote.
+                    actions.push(ThreePhaseAction::Timeout(Self::coordinator_node_id()));
+                }
+            }
+            TransactionState::PreCommitted
+                if Self::participants(state)
+                    .all(|(_, node)| node.crashed || node.acked_pre_commit) =>
+            {
+                actions.push(ThreePhaseAction::DecideCommit);
+            }
+            _ => (),
+        }
+    }
+
+    fn add_participant_actions(
+        state: &ThreePhaseSystem,
+        node_id: usize,
+        node: &ThreePhaseNode,
+        actions: &mut Vec<ThreePhaseAction>,
+    ) {
+        let coordinator = &state.nodes[Self::coordinator_node_id()];
+        if coordinator.crashed {
+            if !node.transaction.state().is_terminal() {
+                actions.push(ThreePhaseAction::Timeout(node_id));
+            }
+            return;
+        }
+        match (coordinator.transaction.state(), node.transaction.state()) {
+            (TransactionState::Prepared, TransactionState::Active) => {
+                actions.push(ThreePhaseAction::VoteYes(node_id));
+                actions.push(ThreePhaseAction::VoteNo(node_id));
+            }
+            (TransactionState::PreCommitted, TransactionState::Prepared) => {
+                actions.push(ThreePhaseAction::PreCommit(node_id));
+            }
+            (TransactionState::Committed, state) if state != TransactionState::Committed => {
+                actions.push(ThreePhaseAction::Commit(node_id));
+            }
+            (TransactionState::RolledBack, state) if state != TransactionState::RolledBack => {
+                actions.push(ThreePhaseAction::Rollback(node_id));
+            }
+            _ => (),
+        }
+    }
+
+    /// Runs the termination protocol on the node.
+    fn terminate(last_state: &ThreePhaseSystem, node_id: usize) -> Option<ThreePhaseSystem> {
+        let pre_committed = |node: &ThreePhaseNode| {
+            matches!(
+                node.transaction.state(),
+                TransactionState::PreCommitted | TransactionState::Committed
+            )
+        };
+        let mut live_participants =
+            Self::participants(last_state).filter(|(_, node)| !node.crashed);
+        let mut state = last_state.clone();
+        let transaction = &mut state.nodes[node_id].transaction;
+        let changed = if live_participants
+            .clone()
+            .all(|(_, node)| pre_committed(node))
+        {
+            transaction.commit()
+        } else if live_participants.any(|(_, node)| pre_committed(node)) {
+            transaction.pre_commit()
+        } else {
+            transaction.rollback()
+        };
+        changed.then_some(state)
+    }
+}
+
+impl Model for ThreePhaseCommitModel {
+    type State = ThreePhaseSystem;
+    type Action = ThreePhaseAction;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        // All the participants have joined the active transaction.
+        let mut nodes = Vec::with_capacity(self.num_nodes);
+        for node_id in 0..self.num_nodes {
+            let mut transaction = Transaction::default();
+            transaction.start();
+            if node_id == Self::coordinator_node_id() {
+                (1..self.num_nodes).for_each(|participant_node_id| {
+                    transaction.add_participant(participant_node_id);
+                });
+            }
+            nodes.push(ThreePhaseNode {
+                transaction,
+                crashed: false,
+                voted_yes: false,
+                acked_pre_commit: false,
+            });
+        }
+        vec![ThreePhaseSystem { nodes }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        if !state.nodes[Self::coordinator_node_id()].crashed {
+            Self::add_coordinator_actions(state, actions);
+        }
+        Self::participants(state)
+            .filter(|(_, node)| !node.crashed)
+            .for_each(|(node_id, node)| {
+                Self::add_participant_actions(state, node_id, node, actions);
+            });
+
+        // Any node can stop any time.
+        state
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| !node.crashed)
+            .for_each(|(node_id, _)| actions.push(ThreePhaseAction::Crash(node_id)));
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let coordinator_node_id = Self::coordinator_node_id();
+        let mut state = last_state.clone();
+        let changed = match action {
+            ThreePhaseAction::RequestVote => state.nodes[coordinator_node_id].transaction.prepare(),
+            ThreePhaseAction::VoteYes(node_id) => {
+                let node = &mut state.nodes[node_id];
+                node.voted_yes = true;
+                let prepared = node.transaction.prepare();
+                state.nodes[coordinator_node_id]
+                    .transaction
+                    .report_prepared(node_id);
+                prepared
+            }
+            ThreePhaseAction::VoteNo(node_id) => {
+                state.nodes[node_id].transaction.rollback();
+                state.nodes[coordinator_node_id].transaction.rollback()
+            }
+            ThreePhaseAction::DecidePreCommit => {
+                state.nodes[coordinator_node_id].transaction.pre_commit()
+            }
+            ThreePhaseAction::PreCommit(node_id) => {
+                let pre_committed = state.nodes[node_id].transaction.pre_commit();
+                state.nodes[node_id].acked_pre_commit = pre_committed;
+                pre_committed
+            }
+            ThreePhaseAction::DecideCommit => state.nodes[coordinator_node_id].transaction.commit(),
+            ThreePhaseAction::Commit(node_id) => {
+                let transaction = &mut state.nodes[node_id].transaction;
+                transaction.pre_commit();
+                transaction.commit()
+            }
+            ThreePhaseAction::Rollback(node_id) => state.nodes[node_id].transaction.rollback(),
+            ThreePhaseAction::Crash(node_id) => {
+                state.nodes[node_id].crashed = true;
+                true
+            }
+            ThreePhaseAction::Timeout(node_id) if node_id == coordinator_node_id => {
+                state.nodes[coordinator_node_id].transaction.rollback()
+            }
+            ThreePhaseAction::Timeout(node_id) => return Self::terminate(last_state, node_id),
+        };
+        changed.then_some(state)
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![
+            Property::<Self>::always("agreement", |_, state| {
+                // No node commits the transaction if another node rolled it back.
+                let committed = state
+                    .nodes
+                    .iter()
+                    .any(|node| node.transaction.state() == TransactionState::Committed);
+                let rolled_back = state
+                    .nodes
+                    .iter()
+                    .any(|node| node.transaction.state() == TransactionState::RolledBack);
+                !(committed && rolled_back)
+            }),
+            Property::<Self>::always("commit validity", |_, state| {
+                // The transaction is committed only if all the participants voted for commit.
+                !state
+                    .nodes
+                    .iter()
+                    .any(|node| node.transaction.state() == TransactionState::Committed)
+                    || Self::participants(state).all(|(_, node)| node.voted_yes)
+            }),
+            Property::<Self>::eventually("non-blocking termination", |_, state| {
+                // Every live node eventually decides even if the coordinator fails.
+                state
+                    .nodes
+                    .iter()
+                    .all(|node| node.crashed || node.transaction.state().is_terminal())
+            }),
+            Property::<Self>::sometimes("commit", |_, state| {
+                state
+                    .nodes
+                    .iter()
+                    .all(|node| node.transaction.state() == TransactionState::Committed)
+            }),
+        ]
+    }
+}
+
+impl MessageCount for ThreePhaseCommitModel {
+    fn messages(&self, _: &Self::State, action: &Self::Action) -> Option<usize> {
+        match action {
+            ThreePhaseAction::RequestVote => Some(self.num_nodes - 1),
+            ThreePhaseAction::DecidePreCommit | ThreePhaseAction::DecideCommit => Some(0),
+            ThreePhaseAction::VoteYes(_)
+            | ThreePhaseAction::VoteNo(_)
+            | ThreePhaseAction::Commit(_)
+            | ThreePhaseAction::Rollback(_) => Some(1),
+            ThreePhaseAction::PreCommit(_) => Some(2),
+            ThreePhaseAction::Crash(_) | ThreePhaseAction::Timeout(_) => None,
+        }
+    }
+
+    fn is_committed(&self, state: &Self::State) -> bool {
+        state
+            .nodes
+            .iter()
+            .all(|node| node.transaction.state() == TransactionState::Committed)
+    }
+
+    fn expected_messages(&self) -> usize {
+        // Vote requests, votes, pre-commit messages and their acknowledgements, and commit
+        // messages.
+        5 * (self.num_nodes - 1)
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{Model, ThreePhaseCommitModel};
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::Checker;
+
+    #[test]
+    fn three_phase_commit() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = ThreePhaseCommitModel::new(3)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+}
@@ -0,0 +1,410 @@
+//! This is a versioned file format of traces through the state space of a model.
+//!
+//! # Format
+//!
+//! A trace is a JSON document with a `version` field, the name of the model type, and the steps
+//! of the trace; each step is the action taken, absent for the initial state, and the debug
+//! representation of the state that the action leads to.
+//!
+//! A saved trace is named after the property that it violates and numbered, such as
+//! `traces/no-protocol-violation-001.json`, so that saving a trace never overwrites another.
+//!
+//! # Compatibility
+//!
+//! A trace of an older version is upgraded to the current version by the migrations before it is
+//! deserialized, and a trace of a newer version is rejected. A trace recorded before the model
+//! changed is validated by replaying its actions, and the first step that the model no longer
+//! reproduces is reported. Its actions can also be replayed without comparing the states, so that
+//! a counterexample recorded before a fix can be studied on the fixed model.
+
+use std::any::type_name;
+use std::fmt::{self, Debug, Display};
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path as FilePath, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use stateright::{Model, Path};
+
+/// The current version of the trace format.
+pub const TRACE_VERSION: u64 = 1;
+
+/// A migration upgrades a trace document of a version to the next version.
+type Migration = fn(&mut Value);
+
+/// `MIGRATIONS[i]` upgrades a trace document of version `i + 1` to version `i + 2`.
+const MIGRATIONS: &[Migration] = &[];
+
+/// [`Trace`] is a sequence of steps from an initial state of a model.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Trace {
+    /// The version of the trace format.
+    pub version: u64,
+
+    /// The name of the model type.
+    pub model: String,
+
+    /// The steps of the trace.
+    pub steps: Vec<TraceStep>,
+}
+
+/// [`TraceStep`] is a step of a [`Trace`].
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TraceStep {
+    /// The action taken, or `None` for the initial state.
+    pub action: Option<String>,
+
+    /// The state that the action leads to.
+    pub state: String,
+}
+
+/// [`TraceError`] describes why a trace cannot be loaded or replayed.
+#[derive(Debug, Eq, PartialEq)]
+pub enum TraceError {
+    /// The document is not a valid trace.
+    Malformed(String),
+
+    /// The document has no version field.
+    MissingVersion,
+
+    /// The version of the document is not supported.
+    UnsupportedVersion(u64),
+
+    /// The trace was recorded for a different model.
+    ModelMismatch(String),
+
+    /// The initial state of the trace is not an initial state of the model.
+    InitialState,
+
+    /// The action of the step is not enabled in the model.
+    Action(usize, String),
+
+    /// The action of the step leads to a different state in the model.
+    State(usize),
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::Malformed(error) => write!(f, "malformed trace: {error}"),
+            TraceError::MissingVersion => write!(f, "the trace has no version"),
+            TraceError::UnsupportedVersion(version) => write!(
+                f,
+                "trace version {version} is not supported (supported: 1 to {TRACE_VERSION})"
+            ),
+            TraceError::ModelMismatch(model) => write!(f, "the trace is for model {model}"),
+            TraceError::InitialState => {
+                write!(f, "the initial state is not reproduced by the model")
+            }
+            TraceError::Action(step, action) => {
+                write!(
+                    f,
+                    "step {step}: action {action} is not enabled in the model"
+                )
+            }
+            TraceError::State(step) => {
+                write!(f, "step {step}: the state is not reproduced by the model")
+            }
+        }
+    }
+}
+
+impl Trace {
+    /// Creates a [`Trace`] of the path through the state space of the model.
+    #[must_use]
+    pub fn from_path<M: Model>(path: Path<M::State, M::Action>) -> Trace
+    where
+        M::Action: Debug,
+        M::State: Debug,
+    {
+        let mut steps = Vec::new();
+        let mut action = None;
+        for (state, next_action) in path.into_vec() {
+            steps.push(TraceStep {
+                action: action.map(|action| format!("{action:?}")),
+                state: format!("{state:?}"),
+            });
+            action = next_action;
+        }
+        Trace {
+            version: TRACE_VERSION,
+            model: type_name::<M>().to_string(),
+            steps,
+        }
+    }
+
+    /// Loads a [`Trace`] from the JSON document, upgrading it to the current version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is malformed, or of an unknown version.
+    pub fn load(json: &str) -> Result<Trace, TraceError> {
+        let mut document: Value =
+            serde_json::from_str(json).map_err(|error| TraceError::Malformed(error.to_string()))?;
+        let version = document
+            .get("version")
+            .ok_or(TraceError::MissingVersion)?
+            .as_u64()
+            .ok_or_else(|| TraceError::Malformed("the version is not a number".to_string()))?;
+        if version == 0 || version > TRACE_VERSION {
+            return Err(TraceError::UnsupportedVersion(version));
+        }
+        for migration in MIGRATIONS
+            .iter()
+            .skip(usize::try_from(version).unwrap_or(0) - 1)
+        {
+            migration(&mut document);
+        }
+        document["version"] = TRACE_VERSION.into();
+        serde_json::from_value(document).map_err(|error| TraceError::Malformed(error.to_string()))
+    }
+
+    /// Creates a [`Trace`] by replaying the actions from the initial state of the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at the first action that is not enabled in the model.
+    pub fn replay<M: Model>(model: &M, actions: Vec<M::Action>) -> Result<Trace, TraceError>
+    where
+        M::Action: Debug + PartialEq,
+        M::State: Debug,
+    {
+        let mut state = model
+            .init_states()
+            .into_iter()
+            .next()
+            .ok_or(TraceError::InitialState)?;
+        let mut steps = vec![TraceStep {
+            action: None,
+            state: format!("{state:?}"),
+        }];
+        for (step, action) in actions.into_iter().enumerate() {
+            let action_name = format!("{action:?}");
+            state = model
+                .next_steps(&state)
+                .into_iter()
+                .find(|(next_action, _)| *next_action == action)
+                .map(|(_, next_state)| next_state)
+                .ok_or(TraceError::Action(step + 1, action_name.clone()))?;
+            steps.push(TraceStep {
+                action: Some(action_name),
+                state: format!("{state:?}"),
+            });
+        }
+        Ok(Trace {
+            version: TRACE_VERSION,
+            model: type_name::<M>().to_string(),
+            steps,
+        })
+    }
+
+    /// Returns the JSON document of the trace.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Saves the trace to the first unused file named after the property in the directory, and
+    /// returns the path of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or the file cannot be written.
+    pub fn save(&self, dir: &FilePath, property: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let stem: String = property
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        for number in 1.. {
+            let path = dir.join(format!("{stem}-{number:03}.json"));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(self.to_json().as_bytes())?;
+                    return Ok(path);
+                }
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(error),
+            }
+        }
+        unreachable!("a trace file number is always unused")
+    }
+
+    /// Replays the actions of the trace on the model, and returns the states that they lead to,
+    /// starting with the initial state; the states of the trace are not compared.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error at the first action that is not enabled in the model.
+    pub fn replay_actions<M: Model>(&self, model: &M) -> Result<Vec<M::State>, TraceError>
+    where
+        M::Action: Debug,
+        M::State: Debug,
+    {
+        let mut init_states = model.init_states();
+        let init_state = match self.steps.first().and_then(|first| {
+            init_states
+                .iter()
+                .position(|state| format!("{state:?}") == first.state)
+        }) {
+            Some(index) => init_states.swap_remove(index),
+            None => init_states
+                .into_iter()
+                .next()
+                .ok_or(TraceError::InitialState)?,
+        };
+        let mut states = vec![init_state];
+        for (step, trace_step) in self.steps.iter().enumerate().skip(1) {
+            let action = trace_step.action.clone().unwrap_or_default();
+            let next_state = model
+                .next_steps(&states[step - 1])
+                .into_iter()
+                .find(|(next_action, _)| format!("{next_action:?}") == action)
+                .map(|(_, next_state)| next_state)
+                .ok_or(TraceError::Action(step, action))?;
+            states.push(next_state);
+        }
+        Ok(states)
+    }
+
+    /// Validates the trace by replaying its actions on the model.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the trace was recorded on another model, or the model does not
+    /// reproduce a step of the trace.
+    pub fn validate<M: Model>(&self, model: &M) -> Result<(), TraceError>
+    where
+        M::Action: Debug,
+        M::State: Debug,
+    {
+        if self.model != type_name::<M>() {
+            return Err(TraceError::ModelMismatch(self.model.clone()));
+        }
+        let Some((first, rest)) = self.steps.split_first() else {
+            return Ok(());
+        };
+        let mut state = model
+            .init_states()
+            .into_iter()
+            .find(|state| format!("{state:?}") == first.state)
+            .ok_or(TraceError::InitialState)?;
+        for (step, trace_step) in rest.iter().enumerate() {
+            let step = step + 1;
+            let action = trace_step.action.clone().unwrap_or_default();
+            let next_state = model
+                .next_steps(&state)
+                .into_iter()
+                .filter(|(next_action, _)| format!("{next_action:?}") == action)
+                .map(|(_, next_state)| next_state)
+                .collect::<Vec<_>>();
+            if next_state.is_empty() {
+                return Err(TraceError::Action(step, action));
+            }
+            state = next_state
+                .into_iter()
+                .find(|next_state| format!("{next_state:?}") == trace_step.state)
+                .ok_or(TraceError::State(step))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Trace, TraceError, TRACE_VERSION};
+    use crate::saga_model::SagaModel;
+
+    use std::env;
+    use std::fs;
+
+    use stateright::{Checker, Model};
+
+    #[test]
+    fn round_trip() {
+        let model = SagaModel::new(2);
+        let checker = SagaModel::new(2).checker().spawn_bfs().join();
+        let path = checker.discovery("compensated").unwrap();
+        let trace = Trace::from_path::<SagaModel>(path);
+        assert_eq!(trace.version, TRACE_VERSION);
+
+        let loaded = Trace::load(&trace.to_json()).unwrap();
+        assert_eq!(loaded, trace);
+        assert_eq!(loaded.validate(&model), Ok(()));
+        assert_eq!(
+            Trace::load(&trace.to_json())
+                .unwrap()
+                .validate(&SagaModel::new(3)),
+            Err(TraceError::InitialState)
+        );
+    }
+
+    #[test]
+    fn replay_actions() {
+        let model = SagaModel::new(2);
+        let checker = SagaModel::new(2).checker().spawn_bfs().join();
+        let mut trace = Trace::from_path::<SagaModel>(checker.discovery("compensated").unwrap());
+        let states = trace.replay_actions(&model).unwrap();
+        assert_eq!(
+            states
+                .iter()
+                .map(|state| format!("{state:?}"))
+                .collect::<Vec<_>>(),
+            trace
+                .steps
+                .iter()
+                .map(|step| step.state.clone())
+                .collect::<Vec<_>>()
+        );
+
+        // The recorded states are not compared.
+        trace.steps[1].state = String::new();
+        assert_eq!(trace.replay_actions(&model).unwrap(), states);
+        trace.steps[1].action = Some("Bogus".to_string());
+        assert_eq!(
+            trace.replay_actions(&model),
+            Err(TraceError::Action(1, "Bogus".to_string()))
+        );
+    }
+
+    #[test]
+    fn save() {
+        let checker = SagaModel::new(2).checker().spawn_bfs().join();
+        let trace = Trace::from_path::<SagaModel>(checker.discovery("compensated").unwrap());
+        let dir = env::temp_dir().join(format!("traces-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let first = trace.save(&dir, "ACID violation").unwrap();
+        let second = trace.save(&dir, "ACID violation").unwrap();
+        assert_eq!(first, dir.join("acid-violation-001.json"));
+        assert_eq!(second, dir.join("acid-violation-002.json"));
+        assert_eq!(
+            Trace::load(&fs::read_to_string(&second).unwrap()).unwrap(),
+            trace
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn incompatible() {
+        assert_eq!(
+            Trace::load(r#"{"model": "m", "steps": []}"#),
+            Err(TraceError::MissingVersion)
+        );
+        assert_eq!(
+            Trace::load(r#"{"version": 99, "model": "m", "steps": []}"#),
+            Err(TraceError::UnsupportedVersion(99))
+        );
+        assert!(matches!(
+            Trace::load(r#"{"version": 1, "model": "m"}"#),
+            Err(TraceError::Malformed(_))
+        ));
+    }
+}
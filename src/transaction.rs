@@ -1,24 +1,69 @@
+use serde::{Deserialize, Serialize};
 use stateright::util::HashableHashMap;
 
 /// [`Transaction`] represents a database transaction.
+///
+/// Like a global transaction of the XA specification, the transaction may consist of branches,
+/// one for each resource manager that it accesses; each branch is identified by its [`Xid`], and
+/// is prepared and committed separately.
+///
+/// The transaction may also start nested child transactions. A child commits provisionally: its
+/// commit becomes durable only when the top-level transaction commits, and it is rolled back along
+/// with any of its ancestors.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct Transaction {
     state: State,
     participants: HashableHashMap<usize, bool>,
+    read_only: bool,
+    branches: HashableHashMap<Xid, State>,
+    children: Vec<Transaction>,
+    sequence: usize,
+}
+
+/// [`Xid`] identifies a transaction branch.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Xid {
+    /// The global transaction identifier.
+    pub gtrid: usize,
+
+    /// The branch qualifier; the identifier of the resource manager of the branch.
+    pub bqual: usize,
 }
 
 impl Transaction {
+    /// Creates a new read-only [`Transaction`].
+    ///
+    /// A read-only transaction skips the prepare phase, and commits from the active state.
+    #[must_use]
+    pub fn read_only() -> Transaction {
+        Transaction {
+            read_only: true,
+            ..Transaction::default()
+        }
+    }
+
     /// Returns the state of the transaction.
+    #[must_use]
     pub fn state(&self) -> State {
         self.state
     }
 
+    /// Returns the sequence number of the transaction that is incremented whenever the state of
+    /// the transaction changes.
+    ///
+    /// Messages about the transaction are stamped with the sequence number so that a message sent
+    /// in an earlier state is told apart from a message sent in a later state.
+    #[must_use]
+    pub fn sequence(&self) -> usize {
+        self.sequence
+    }
+
     /// Starts the transaction.
     ///
     /// Returns `true` if the transaction has started by the method call.
     pub fn start(&mut self) -> bool {
         if self.state == State::Inactive {
-            self.state = State::Active;
+            self.set_state(State::Active);
             true
         } else {
             false
@@ -32,24 +77,209 @@ impl Transaction {
         self.participants.insert(node_id, false).is_none()
     }
 
-    /// Executes the supplied closure on each participant node id.
-    pub fn for_each_participant<F: FnMut(usize)>(&self, mut f: F) {
-        self.participants
-            .iter()
-            .for_each(|(node_id, _prepared)| f(*node_id));
+    /// Removes the participant from the transaction.
+    ///
+    /// Returns `true` if the participant was removed from the transaction.
+    pub fn remove_participant(&mut self, node_id: usize) -> bool {
+        self.participants.remove(&node_id).is_some()
+    }
+
+    /// Returns `true` if the node participates in the transaction.
+    #[must_use]
+    pub fn has_participant(&self, node_id: usize) -> bool {
+        self.participants.contains_key(&node_id)
+    }
+
+    /// Returns `true` if the participant has reported that it prepared the transaction.
+    #[must_use]
+    pub fn is_prepared(&self, node_id: usize) -> bool {
+        self.participants.get(&node_id).copied().unwrap_or(false)
+    }
+
+    /// Executes the supplied closure on each participant node id, in ascending order so that the
+    /// order does not depend on the hash map.
+    pub fn for_each_participant<F: FnMut(usize)>(&self, f: F) {
+        let mut node_ids: Vec<usize> = self.participants.keys().copied().collect();
+        node_ids.sort_unstable();
+        node_ids.into_iter().for_each(f);
     }
 
     /// Returns `true` if all the participants have prepared for commit.
+    #[must_use]
     pub fn is_all_prepared(&self) -> bool {
-        !self.participants.iter().any(|(_, prepared)| !*prepared)
+        self.is_quorum_prepared(self.participants.len())
+    }
+
+    /// Returns `true` if at least `quorum` participants, or all of them if there are fewer, have
+    /// prepared for commit.
+    #[must_use]
+    pub fn is_quorum_prepared(&self, quorum: usize) -> bool {
+        self.participants
+            .iter()
+            .filter(|(_, prepared)| **prepared)
+            .count()
+            >= quorum.min(self.participants.len())
+    }
+
+    /// Starts a new branch of the active transaction.
+    ///
+    /// Returns `true` if the branch has started by the method call.
+    pub fn start_branch(&mut self, xid: Xid) -> bool {
+        if self.state == State::Active && !self.branches.contains_key(&xid) {
+            self.branches.insert(xid, State::Active);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the state of the branch, or `None` if the branch has not started.
+    #[must_use]
+    pub fn branch_state(&self, xid: Xid) -> Option<State> {
+        self.branches.get(&xid).copied()
+    }
+
+    /// Prepares the branch for commit.
+    ///
+    /// Returns `true` if the branch is prepared for commit by the method call.
+    pub fn prepare_branch(&mut self, xid: Xid) -> bool {
+        self.transition_branch(xid, State::Active, State::Prepared)
+    }
+
+    /// Commits the prepared branch of the committed transaction.
+    ///
+    /// Returns `true` if the branch has been committed by the method call.
+    pub fn commit_branch(&mut self, xid: Xid) -> bool {
+        self.state == State::Committed
+            && self.transition_branch(xid, State::Prepared, State::Committed)
+    }
+
+    /// Rolls back the branch.
+    ///
+    /// An active branch that fails is rolled back, and the transaction can no longer be prepared;
+    /// a prepared branch is rolled back only after the transaction is rolled back.
+    ///
+    /// Returns `true` if the branch has been rolled back by the method call.
+    pub fn rollback_branch(&mut self, xid: Xid) -> bool {
+        if self.state == State::RolledBack
+            && self.transition_branch(xid, State::Prepared, State::RolledBack)
+        {
+            return true;
+        }
+        self.state != State::Committed
+            && self.transition_branch(xid, State::Active, State::RolledBack)
+    }
+
+    /// Returns `true` if all the branches have prepared for commit.
+    #[must_use]
+    pub fn is_all_branches_prepared(&self) -> bool {
+        self.branches
+            .values()
+            .all(|state| *state == State::Prepared)
+    }
+
+    /// Starts a new child transaction of the active transaction.
+    ///
+    /// Returns the index of the child transaction if it has started by the method call.
+    pub fn start_child(&mut self) -> Option<usize> {
+        if self.state != State::Active {
+            return None;
+        }
+        let mut child = Transaction::default();
+        child.start();
+        self.children.push(child);
+        Some(self.children.len() - 1)
+    }
+
+    /// Returns the child transaction.
+    #[must_use]
+    pub fn child(&self, index: usize) -> Option<&Transaction> {
+        self.children.get(index)
+    }
+
+    /// Returns the child transaction for starting its own children.
+    pub fn child_mut(&mut self, index: usize) -> Option<&mut Transaction> {
+        self.children.get_mut(index)
+    }
+
+    /// Commits the child transaction provisionally.
+    ///
+    /// The child commits only while the transaction is active, and after all of its own children
+    /// have finished.
+    ///
+    /// Returns `true` if the child transaction has been committed by the method call.
+    pub fn commit_child(&mut self, index: usize) -> bool {
+        if self.state != State::Active {
+            return false;
+        }
+        self.children.get_mut(index).is_some_and(|child| {
+            if child.state == State::Active && child.is_all_children_finished() {
+                child.set_state(State::Committed);
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Rolls back the child transaction of the active transaction along with its descendants.
+    ///
+    /// Returns `true` if the child transaction has been rolled back by the method call.
+    pub fn rollback_child(&mut self, index: usize) -> bool {
+        if self.state != State::Active {
+            return false;
+        }
+        self.children.get_mut(index).is_some_and(|child| {
+            if child.state == State::RolledBack {
+                false
+            } else {
+                child.abort_nested();
+                true
+            }
+        })
+    }
+
+    /// Returns `true` if all the child transactions have committed or rolled back.
+    #[must_use]
+    pub fn is_all_children_finished(&self) -> bool {
+        self.children.iter().all(|child| child.state.is_terminal())
+    }
+
+    /// Rolls back the descendants, including the provisionally committed ones.
+    fn abort_nested(&mut self) {
+        self.set_state(State::RolledBack);
+        self.children.iter_mut().for_each(Transaction::abort_nested);
+    }
+
+    fn set_state(&mut self, state: State) {
+        self.state = state;
+        self.sequence += 1;
+    }
+
+    fn transition_branch(&mut self, xid: Xid, from: State, to: State) -> bool {
+        self.branches.get_mut(&xid).is_some_and(|state| {
+            if *state == from {
+                *state = to;
+                true
+            } else {
+                false
+            }
+        })
     }
 
     /// Prepares the transaction for commit.
     ///
+    /// The transaction is prepared only after all of its branches have prepared, and all of its
+    /// child transactions have finished.
+    ///
     /// Returns `true` if the transaction is prepared for commit by the method call.
     pub fn prepare(&mut self) -> bool {
-        if self.state == State::Active {
-            self.state = State::Prepared;
+        if self.state == State::Active
+            && !self.read_only
+            && self.is_all_branches_prepared()
+            && self.is_all_children_finished()
+        {
+            self.set_state(State::Prepared);
             true
         } else {
             false
@@ -63,24 +293,36 @@ impl Transaction {
         if self.state != State::Prepared {
             return false;
         }
-        self.participants
-            .get_mut(&node_id)
-            .map_or(false, |prepared| {
-                if *prepared {
-                    false
-                } else {
-                    *prepared = true;
-                    true
-                }
-            })
+        self.participants.get_mut(&node_id).is_some_and(|prepared| {
+            if *prepared {
+                false
+            } else {
+                *prepared = true;
+                true
+            }
+        })
+    }
+
+    /// Pre-commits the prepared transaction.
+    ///
+    /// Returns `true` if the transaction is pre-committed by the method call.
+    pub fn pre_commit(&mut self) -> bool {
+        if self.state == State::Prepared {
+            self.set_state(State::PreCommitted);
+            true
+        } else {
+            false
+        }
     }
 
     /// Commits the transaction.
     ///
     /// Returns `true` if the transaction has been committed by the method call.
     pub fn commit(&mut self) -> bool {
-        if self.state == State::Prepared {
-            self.state = State::Committed;
+        if matches!(self.state, State::Prepared | State::PreCommitted)
+            || (self.read_only && self.state == State::Active && self.is_all_children_finished())
+        {
+            self.set_state(State::Committed);
             true
         } else {
             false
@@ -89,11 +331,14 @@ impl Transaction {
 
     /// Rolls back the transaction.
     ///
+    /// The child transactions are rolled back along with the transaction.
+    ///
     /// Returns `true` if the transaction has been rolled back by the method call.
     pub fn rollback(&mut self) -> bool {
         // TODO: is this condition OK?
         if self.state != State::RolledBack {
-            self.state = State::RolledBack;
+            self.set_state(State::RolledBack);
+            self.children.iter_mut().for_each(Transaction::abort_nested);
             return true;
         }
         false
@@ -105,12 +350,16 @@ impl Default for Transaction {
         Self {
             state: State::Inactive,
             participants: HashableHashMap::default(),
+            read_only: false,
+            branches: HashableHashMap::default(),
+            children: Vec::new(),
+            sequence: 0,
         }
     }
 }
 
 /// The state of a transaction is expressed as [`State`].
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum State {
     /// The transaction has not started.
     Inactive,
@@ -121,6 +370,11 @@ pub enum State {
     /// The transaction is prepared for commit.
     Prepared,
 
+    /// The transaction is prepared, and all the participants are known to be prepared.
+    ///
+    /// Only the three-phase commit protocol pre-commits a transaction.
+    PreCommitted,
+
     /// The transaction is committed.
     Committed,
 
@@ -132,7 +386,7 @@ impl State {
     /// Returns `true` if it is a terminal state.
     ///
     /// TODO: when shall this method be called?
-    #[allow(dead_code)]
+    #[must_use]
     pub fn is_terminal(self) -> bool {
         matches!(self, Self::Committed | Self::RolledBack)
     }
@@ -140,12 +394,16 @@ impl State {
 
 #[cfg(test)]
 mod test {
-    use super::Transaction;
+    use super::{State, Transaction, Xid};
 
     use quickcheck::quickcheck;
 
     quickcheck! { fn prop_api_safety(xs: Vec<usize>) -> bool { check_api_safety(&xs) } }
 
+    quickcheck! { fn prop_branch_safety(xs: Vec<usize>) -> bool { check_branch_safety(&xs) } }
+
+    quickcheck! { fn prop_nested_safety(xs: Vec<usize>) -> bool { check_nested_safety(&xs) } }
+
     fn check_api_safety(seq: &[usize]) -> bool {
         let mut started = false;
         let mut prepared = false;
@@ -206,4 +464,118 @@ mod test {
             }
         })
     }
+
+    fn check_branch_safety(seq: &[usize]) -> bool {
+        let xids = [0, 1].map(|bqual| Xid { gtrid: 0, bqual });
+        let mut transaction = Transaction::default();
+        transaction.start();
+        !seq.iter().any(|op_code| {
+            // Returns `true` if it detects anything illegal.
+            let xid = xids[(op_code / 6) % xids.len()];
+            match op_code % 6 {
+                0 => {
+                    transaction.start_branch(xid);
+                    false
+                }
+                1 => {
+                    transaction.prepare_branch(xid);
+                    false
+                }
+                2 => {
+                    // 2 => branch failure.
+                    let prepared = transaction.branch_state(xid) == Some(State::Prepared);
+                    transaction.rollback_branch(xid)
+                        && prepared
+                        && transaction.state() != State::RolledBack
+                }
+                3 => {
+                    // A transaction is prepared only after all of its branches have prepared.
+                    transaction.prepare() && !transaction.is_all_branches_prepared()
+                }
+                4 => {
+                    // 4 => decide.
+                    if !transaction.commit() && !transaction.state().is_terminal() {
+                        transaction.rollback();
+                    }
+                    false
+                }
+                _ => {
+                    // A branch is committed only if all the branches are committed or prepared.
+                    transaction.commit_branch(xid)
+                        && xids.iter().any(|xid| {
+                            matches!(
+                                transaction.branch_state(*xid),
+                                Some(State::Active | State::RolledBack)
+                            )
+                        })
+                }
+            }
+        })
+    }
+
+    fn check_nested_safety(seq: &[usize]) -> bool {
+        let mut transaction = Transaction::default();
+        transaction.start();
+        seq.iter().all(|op_code| {
+            let index = (op_code / 8) % 2;
+            match op_code % 8 {
+                0 => {
+                    transaction.start_child();
+                }
+                1 => {
+                    if let Some(child) = transaction.child_mut(index) {
+                        child.start_child();
+                    }
+                }
+                2 => {
+                    transaction.commit_child(index);
+                }
+                3 => {
+                    transaction.rollback_child(index);
+                }
+                4 => {
+                    if let Some(child) = transaction.child_mut(index) {
+                        child.commit_child(0);
+                    }
+                }
+                5 => {
+                    if let Some(child) = transaction.child_mut(index) {
+                        child.rollback_child(0);
+                    }
+                }
+                6 => {
+                    if transaction.prepare() {
+                        transaction.commit();
+                    }
+                }
+                _ => {
+                    transaction.rollback();
+                }
+            }
+            is_nested_consistent(&transaction)
+        })
+    }
+
+    /// Returns `true` if no child commit outlives an aborted ancestor, and a transaction is
+    /// decided only after all of its children have finished.
+    fn is_nested_consistent(transaction: &Transaction) -> bool {
+        let children = (0..)
+            .map_while(|index| transaction.child(index))
+            .collect::<Vec<_>>();
+        match transaction.state() {
+            State::RolledBack => children.iter().all(|child| is_all_rolled_back(child)),
+            State::Prepared | State::Committed => {
+                transaction.is_all_children_finished()
+                    && children.iter().all(|child| is_nested_consistent(child))
+            }
+            _ => children.iter().all(|child| is_nested_consistent(child)),
+        }
+    }
+
+    fn is_all_rolled_back(transaction: &Transaction) -> bool {
+        transaction.state() == State::RolledBack
+            && (0..)
+                .map_while(|index| transaction.child(index))
+                .all(is_all_rolled_back)
+    }
 }
@@ -12,6 +12,11 @@
 //! If the coordinator received an agreement message from all of them, the coordinator generates a
 //! commit timestamp and sends a commit message to the participants.
 //!
+//! The commit timestamp is a logical timestamp that is greater than any commit timestamp known to
+//! the nodes. It is carried by the commit message, and recorded by every participant along with
+//! the commit; a participant that learns the decision from another node records the commit
+//! timestamp of that node.
+//!
 //! ## Rollback
 //!
 //! If the coordinator fails to receive an agreement message from all of them, the coordinator sends
@@ -21,14 +26,248 @@
 //!
 //! If a participant did not get any message from the coordinator, the participant checks the
 //! transaction state by sending a check message to the coordinator.
+//!
+//! A prepared participant that has restarted or waited too long sends a check message to its
+//! parent, which replies with its [`Decision`]: the decision if it has decided, or that the
+//! transaction is still pending. A parent that does not know the transaction presumes that it was
+//! rolled back, or committed if it has forgotten the committed transaction.
+//!
+//! ## Commit tree
+//!
+//! The nodes may form a commit tree rooted at the coordinator, where an intermediate node acts as
+//! a participant towards its parent and as a coordinator towards its children: it joins the
+//! transaction of its parent before its children join its own, forwards prepare requests to its
+//! children, and votes for commit only after all of them have voted for commit. A decision is
+//! propagated from the parents to the children.
+//!
+//! ## Workload
+//!
+//! A [`Workload`] may determine the data stored in the nodes and the operations that the
+//! transaction performs on them; a node applies the buffered writes when it commits, and the
+//! invariant of the workload is checked over the data that the nodes hold once the decision is
+//! applied everywhere.
+//!
+//! ## Coordinator policy
+//!
+//! A [`CoordinatorPolicy`] decides when the coordinator starts the voting phase, whether it may
+//! roll back despite unanimous yes votes, and whether it gives up on missing votes; by default,
+//! the coordinator may do any of them but giving up.
+//!
+//! ## One-phase commit
+//!
+//! In the one-phase variant, a coordinator that has exactly one participant skips the voting
+//! phase: it delegates the decision to the participant, which commits or rolls back by itself and
+//! reports the decision back to the coordinator.
+//!
+//! ## Last-agent optimization
+//!
+//! With the last-agent optimization, the coordinator prepares all but the last participant, and
+//! once they have voted for commit, it delegates the decision to the last participant, which
+//! commits or rolls back locally; the coordinator then sends the decision of the last agent to the
+//! other participants, and ignores their votes after the delegation.
+//!
+//! ## Quorum commit
+//!
+//! In the quorum variant, the coordinator commits once a quorum of its participants has voted for
+//! commit. The remaining participants are stragglers that catch up later: the coordinator requests
+//! their votes again after it has committed, and ignores their votes against commit. A participant
+//! never rolls back unilaterally as the coordinator may commit without its vote.
+//!
+//! ## Replicated participants
+//!
+//! Each participant may be backed by a number of replicas that hold copies of its transaction
+//! state. The participant replicates its state to the replicas asynchronously, except that it votes
+//! for commit only after all the surviving replicas have the prepare record. Up to all but one of
+//! the replicas of a participant may be lost; as any of them may have hosted the participant, the
+//! participant then fails over to the most advanced state among the surviving replicas, unless it
+//! has already decided the transaction.
+//!
+//! ## Read-only participants
+//!
+//! A participant that has only read data has nothing to prepare: it releases its resources when it
+//! receives a prepare request, and replies with a read-only vote instead of a yes-vote. The parent
+//! removes the participant from the transaction, and never sends it the decision.
+//!
+//! ## Flow control
+//!
+//! An overloaded participant may defer its vote by answering a prepare request with a retry hint
+//! instead of voting against commit. The coordinator backs off and requests the vote again, and
+//! gives up on the participant only after it has deferred more times than the retry budget.
+//!
+//! ## Crashes
+//!
+//! Any node may crash at any time and restart. The crashes may be bounded by a crash budget shared
+//! by all the nodes, so that scenarios with at most one or two crashes are checked separately
+//! without exploring endless crash loops.
+//!
+//! In the fail-stop failure model, a crashed node never restarts instead; comparing the properties
+//! that hold in either failure model shows which of them rely on recovery.
+//!
+//! A node logs the start, the participants, the prepare record and the decision of the transaction
+//! before it acts on them, and a restarted node rebuilds the transaction by replaying its log; a
+//! participant that has prepared the transaction stays prepared across a crash, and a node that
+//! has committed it stays committed. A node that has logged a decision recovers the decision of
+//! its last decision record. A participant that crashes before its prepare record is durable has
+//! lost the work of the transaction, and is checked never to commit it.
+//!
+//! The coordinator may crash after it has logged the decision but before it has sent it to any
+//! participant; it recovers the decision and the participants from its log, and sends the decision
+//! again.
+//!
+//! A node may checkpoint the transaction: it snapshots the transaction recovered from the log,
+//! and truncates the log, so that a restarted node replays only the records after the checkpoint.
+//!
+//! With torn writes, a log record is durable only after the node syncs its log, and a crash loses
+//! the records that are written but not synced; recovery replays only the synced records. A node
+//! syncs its log before it acts on the records, e.g., before it sends its vote or decision.
+//!
+//! With coordinator amnesia, the coordinator may crash before it has decided, or before it has
+//! logged the participants in the presumed-commit variant, and lose its log. It restarts knowing
+//! nothing about the transaction, and presumes that the transaction was rolled back; the prepared
+//! participants learn the rollback by checking the status with the coordinator.
+//!
+//! The log of a participant may fill up, after which appending records to it fails. A participant
+//! that cannot log the prepare record rolls back and votes against commit instead of preparing.
+//!
+//! ## Timeouts
+//!
+//! A node rolls back its active transaction, or inquires the coordinator about the decision, when
+//! it times out. Timeouts fire at any time unless the simulated clock is enabled, in which case
+//! they fire only after the node has waited in its current state for the configured number of
+//! ticks; the clock is advanced by explicit ticks up to a bound.
+//!
+//! With explicit timers, a timeout fires only by a [`Action::Timeout`] of the node, which is
+//! enabled only while the node is waiting: an active transaction waits for the prepare request,
+//! and a participant that has voted waits for the decision. The timer is reset whenever the
+//! transaction state of the node changes.
+//!
+//! ## Cooperative termination
+//!
+//! A participant learns the identifiers of its peers along with the prepare request. When the
+//! coordinator of a prepared participant is down, the participant queries its peers for the
+//! outcome: a peer that has learned the decision answers with it, and a peer that has not voted yet
+//! rolls back and answers with the rollback. The participant stays blocked only if all of its peers
+//! are down or prepared as well.
+//!
+//! ## Coordinator election
+//!
+//! When the coordinator fails, the running participant with the lowest identifier is elected as
+//! the acting coordinator. It collects the states of the running participants, and completes the
+//! transaction if a participant has committed it, or aborts it if a participant has not voted for
+//! commit; if all of them are prepared, the decision of the failed coordinator is unknown, and the
+//! transaction stays blocked.
+//!
+//! ## Backup coordinator
+//!
+//! A hot-standby replica of the coordinator receives a copy of every prepare and decision record
+//! of the coordinator synchronously. When the coordinator fails, the standby takes over: it commits
+//! the transaction if the coordinator has committed it or all the participants have voted for
+//! commit, rolls it back otherwise, and sends the decision to the participants. Participants are
+//! blocked only if both replicas of the coordinator have failed.
+//!
+//! ## Heuristic decisions
+//!
+//! When the coordinator fails, a prepared participant may stop waiting for the decision and
+//! commit or roll back heuristically, releasing its resources at the risk of contradicting the
+//! decision of the coordinator or of other participants. Heuristic decisions are excluded from the
+//! atomic commitment properties, and a separate property exposes the heuristic-mixed outcome in
+//! which some participants have committed and others have rolled back.
+//!
+//! ## Asynchronous network
+//!
+//! By default, a message is received in the same step as it is sent. With the asynchronous network,
+//! a node sends a message to the network, and the message is delivered to its destination in a
+//! later step, after any number of steps of the other nodes; a message that its destination
+//! cannot process when it is delivered, e.g., because the destination is down, is lost.
+//!
+//! Messages in flight are delivered in any order. Each message is stamped with the sequence number
+//! of the transaction of its sender, which changes whenever the state of the transaction changes,
+//! so that a stale message sent in an earlier state, e.g., a prepare request sent before the
+//! rollback, may be delivered after the messages sent in later states. The network holds at most
+//! one copy of each message, and a node that repeats a message that is still in flight does not
+//! change the state.
+//!
+//! The network may also duplicate a message that has already been delivered, up to a bound, and
+//! deliver the duplicate after the state of its destination has changed; the handlers of the
+//! messages have to be idempotent.
+//!
+//! Each direction of the link between a participant and its parent may fail independently, up to a
+//! bound; a message sent over a failed link is lost when it is delivered. A participant may then
+//! receive the prepare request and the decision of its parent while its votes never arrive.
+//!
+//! ## Dead participant
+//!
+//! A participant may die permanently along with the coordinator. If they die after the other
+//! participants have prepared, the remaining participants cannot terminate the transaction: the
+//! dead participant may have learned the decision, and any decision of the remaining participants
+//! may contradict it. This is the classic blocking scenario of the two-phase commit protocol.
+//!
+//! ## Byzantine participant
+//!
+//! A Byzantine participant does not follow the protocol: it may vote for commit without preparing
+//! the transaction, and send forged commit messages to the other participants. The protocol does
+//! not tolerate it, and the nodes that follow the protocol may disagree on the outcome.
+//!
+//! ## Validation
+//!
+//! A participant logs the first vote that it sends, and cross-checks the decisions of its parent
+//! against its log: a commit arriving after it has voted against commit, or a rollback arriving
+//! after it has committed, is flagged as a protocol violation and is not applied. A node that
+//! reverses its own decision in a transition, like a coordinator that rolls back after it has
+//! committed, is reported even if the other nodes follow it (decision stability).
+//!
+//! The atomic commitment properties are checked separately: all the nodes that have decided agree
+//! on the decision (agreement), the coordinator commits only if all the participants have voted
+//! for commit by the time it decides (commit validity), a participant that has voted for commit
+//! rolls back only if its parent has rolled back (abort validity), and all the nodes may commit in
+//! an execution without faults (non-triviality). All the nodes may roll back the transaction as
+//! well, so that a model that can only take one of the decisions does not pass vacuously.
+//!
+//! Without crashes, every running node eventually commits or rolls back the transaction
+//! (termination): a state in which no node can take a step before all of them have decided is
+//! reported as a deadlock. With a bounded number of crashes, once the coordinator commits, every
+//! participant that is not read-only eventually commits rather than staying prepared.
+//!
+//! The liveness properties are only checked at the end of an execution, which never comes while a
+//! node can retransmit a message forever. Under fairness, a step that does not change the state is
+//! pruned, so that an execution ends once the nodes stop making progress; along with a crash
+//! budget, a node that is waiting for a message that never comes is then reported.
+//!
+//! When crashes and retransmissions make the executions too long to explore, the exploration may
+//! be bounded by a maximum depth. The result is then a bounded verification: a discovery that
+//! takes more actions than the depth is missed, and the liveness properties are not checked, as
+//! an execution cut at the depth has not ended.
+//!
+//! ## Presumed commit
+//!
+//! In the presumed-commit variant, the coordinator logs the participant list before it sends
+//! prepare messages, and forgets the transaction once it has committed it. A prepared participant
+//! that inquires about a transaction unknown to the coordinator presumes that it was committed.
+//!
+//! Every applied action is logged at the trace level along with the fingerprints of the states
+//! before and after it, so that `RUST_LOG=homework::transaction_model=trace` shows the transitions
+//! that the checker explores.
 
+use super::coordinator_policy::{CoordinatorPolicy, Permissive};
+use super::coverage::Coverage;
+use super::invariant::Invariant;
+use super::message_complexity::MessageCount;
+use super::model_config::{FaultType, ModelConfig};
+use super::participant_behavior::{Cooperative, ParticipantBehavior};
+use super::scenario::{Fault, Scenario};
+use super::state_graph::fingerprint;
 use super::transaction::State as TransactionState;
 use super::transaction::Transaction;
+use super::transaction_model_builder::TransactionModelBuilder;
+use super::workload::{Operation, Workload};
 
 use std::hash::{Hash, Hasher};
 
-use stateright::{Model, Property};
+use log::{log_enabled, trace, Level};
+use stateright::{Expectation, Model, Property};
 
+/// [`Node`] is a node participating in the distributed transaction.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug, Eq)]
 pub struct Node {
     /// The node identifier.
@@ -37,17 +276,217 @@ pub struct Node {
     /// The only transaction on the node.
     transaction: Transaction,
 
-    /// The persistent storage of the node.
+    /// The persistent storage of the node; the log of the transaction state changes that the
+    /// node replays when it restarts after a crash.
     persistency: Vec<Action>,
+
+    /// The log records that have been written but not yet synced to the persistent storage.
+    pending: Vec<Action>,
+
+    /// The snapshot of the transaction that the log was truncated at by the last checkpoint; the
+    /// node replays the log on top of it when it restarts.
+    checkpoint: Option<Transaction>,
+
+    /// The log records discarded by the checkpoints; only kept to check the recovery from the
+    /// checkpoint against the recovery from the full log.
+    truncated: Vec<Action>,
+
+    /// The node is running, shut down, or failed.
+    status: NodeStatus,
+
+    /// The incarnation number of the node that is incremented when the node is replaced.
+    incarnation: usize,
+
+    /// The decisions applied to the data stored in the node, and the incarnations that applied
+    /// them.
+    ///
+    /// The data survives replacement of the node as the replacement restores the data from its
+    /// peers.
+    applied: Vec<(usize, TransactionState)>,
+
+    /// The coordinator has forgotten the committed transaction.
+    forgotten: bool,
+
+    /// The simulated clock reading when the transaction on the node last changed its state.
+    since: usize,
+
+    /// The node has recovered the decision on the transaction from its log after a crash.
+    recovered: bool,
+
+    /// The node had committed the transaction, and synced the commit record, before a crash, but
+    /// recovered the transaction in another state.
+    lost_commit: bool,
+
+    /// The node has recovered the transaction in a state other than the last decision record in
+    /// its durable log.
+    misrecovered: bool,
+
+    /// The participant has crashed before its prepare record was durable.
+    crashed_unprepared: bool,
+
+    /// The coordinator has lost its log before the participants were durable, and presumes that
+    /// the transaction it knows nothing about was rolled back.
+    amnesiac: bool,
+
+    /// The log of the node is full, and appending records to it fails.
+    disk_full: bool,
+
+    /// The timer of the node has expired since the transaction on the node last changed its
+    /// state.
+    timer_expired: bool,
+
+    /// The number of prepare requests that the participant has deferred.
+    retries: usize,
+
+    /// The participant has only read data.
+    read_only: bool,
+
+    /// The coordinator has delegated the decision to its only participant.
+    delegated: bool,
+
+    /// The data stored in the node.
+    data: Vec<i64>,
+
+    /// The operations of the transaction on the node; the writes are buffered until the node
+    /// commits.
+    operations: Vec<Operation>,
+
+    /// The other participants of the parent, learned along with the prepare request.
+    peers: Vec<usize>,
+
+    /// The vote that the participant has logged; `true` for commit.
+    vote: Option<bool>,
+
+    /// The decision of the parent that contradicts the log of the participant.
+    violation: Option<Action>,
+
+    /// The decision that the participant has taken heuristically; `true` for commit.
+    heuristic: Option<bool>,
+
+    /// The commit timestamp recorded along with the commit.
+    commit_timestamp: Option<usize>,
+
+    /// The reply of the parent to the check message of the participant that is not yet received,
+    /// or the last reply if the transaction was pending.
+    status_reply: Option<Decision>,
+
+    /// The transaction state held by each replica of the participant, or `None` if the replica
+    /// has been lost; empty if the participant is not replicated.
+    replicas: Vec<Option<TransactionState>>,
 }
 
 impl Node {
     /// Creates a new [`Node`] instance.
+    #[must_use]
     pub fn with_id(id: usize) -> Node {
         Node {
             id,
             transaction: Transaction::default(),
             persistency: Vec::default(),
+            pending: Vec::default(),
+            checkpoint: None,
+            truncated: Vec::default(),
+            status: NodeStatus::Running,
+            incarnation: 0,
+            applied: Vec::default(),
+            forgotten: false,
+            since: 0,
+            recovered: false,
+            lost_commit: false,
+            misrecovered: false,
+            crashed_unprepared: false,
+            amnesiac: false,
+            disk_full: false,
+            timer_expired: false,
+            retries: 0,
+            read_only: false,
+            delegated: false,
+            data: Vec::default(),
+            operations: Vec::default(),
+            peers: Vec::default(),
+            vote: None,
+            violation: None,
+            heuristic: None,
+            commit_timestamp: None,
+            status_reply: None,
+            replicas: Vec::default(),
+        }
+    }
+
+    /// Creates a new [`Node`] instance of a participant that only reads data.
+    #[must_use]
+    pub fn read_only(id: usize) -> Node {
+        Node {
+            transaction: Transaction::read_only(),
+            read_only: true,
+            ..Node::with_id(id)
+        }
+    }
+
+    /// Returns a fresh transaction of the node.
+    fn new_transaction(&self) -> Transaction {
+        if self.read_only {
+            Transaction::read_only()
+        } else {
+            Transaction::default()
+        }
+    }
+
+    /// Returns `true` if all the surviving replicas of the participant hold its transaction state.
+    fn is_replicated(&self) -> bool {
+        self.replicas
+            .iter()
+            .flatten()
+            .all(|state| *state == self.transaction.state())
+    }
+
+    /// Returns the node identifier.
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the transaction on the node.
+    #[must_use]
+    pub fn transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+
+    /// Returns `true` if the node is running.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.status == NodeStatus::Running
+    }
+
+    /// Writes the record to the log; the record is durable only after the log is synced.
+    ///
+    /// The record is dropped if the log is full.
+    fn log(&mut self, record: Action) {
+        if !self.disk_full {
+            self.pending.push(record);
+        }
+    }
+
+    /// Syncs the written log records to the persistent storage.
+    fn sync(&mut self) {
+        self.persistency.append(&mut self.pending);
+    }
+
+    /// Records that the current incarnation of the node has applied the decision.
+    fn apply_decision(&mut self) {
+        if self
+            .applied
+            .last()
+            .is_none_or(|(incarnation, _)| *incarnation != self.incarnation)
+        {
+            if self.applied.is_empty() && self.transaction.state() == TransactionState::Committed {
+                // Apply the buffered writes to the data.
+                for operation in &self.operations {
+                    operation.apply(&mut self.data);
+                }
+            }
+            self.applied
+                .push((self.incarnation, self.transaction.state()));
         }
     }
 }
@@ -55,18 +494,242 @@ impl Node {
 impl Hash for Node {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.transaction.hash(state);
+        self.persistency.hash(state);
+        self.pending.hash(state);
+        self.checkpoint.hash(state);
+        self.truncated.hash(state);
+        self.status.hash(state);
+        self.incarnation.hash(state);
+        self.applied.hash(state);
+        self.forgotten.hash(state);
+        self.since.hash(state);
+        self.recovered.hash(state);
+        self.lost_commit.hash(state);
+        self.misrecovered.hash(state);
+        self.crashed_unprepared.hash(state);
+        self.amnesiac.hash(state);
+        self.disk_full.hash(state);
+        self.timer_expired.hash(state);
+        self.retries.hash(state);
+        self.read_only.hash(state);
+        self.delegated.hash(state);
+        self.data.hash(state);
+        self.peers.hash(state);
+        self.vote.hash(state);
+        self.violation.hash(state);
+        self.heuristic.hash(state);
+        self.commit_timestamp.hash(state);
+        self.status_reply.hash(state);
+        self.replicas.hash(state);
     }
 }
 
 impl PartialEq for Node {
     fn eq(&self, other: &Self) -> bool {
         self.transaction == other.transaction
+            && self.persistency == other.persistency
+            && self.pending == other.pending
+            && self.checkpoint == other.checkpoint
+            && self.truncated == other.truncated
+            && self.status == other.status
+            && self.incarnation == other.incarnation
+            && self.applied == other.applied
+            && self.forgotten == other.forgotten
+            && self.since == other.since
+            && self.recovered == other.recovered
+            && self.lost_commit == other.lost_commit
+            && self.misrecovered == other.misrecovered
+            && self.crashed_unprepared == other.crashed_unprepared
+            && self.amnesiac == other.amnesiac
+            && self.disk_full == other.disk_full
+            && self.timer_expired == other.timer_expired
+            && self.retries == other.retries
+            && self.read_only == other.read_only
+            && self.delegated == other.delegated
+            && self.data == other.data
+            && self.peers == other.peers
+            && self.vote == other.vote
+            && self.violation == other.violation
+            && self.heuristic == other.heuristic
+            && self.commit_timestamp == other.commit_timestamp
+            && self.status_reply == other.status_reply
+            && self.replicas == other.replicas
     }
 }
 
+/// [`Decision`] is the transaction status that a parent reports to a participant.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Decision {
+    /// The transaction has been committed with the commit timestamp.
+    Commit(usize),
+
+    /// The transaction has been rolled back.
+    Rollback,
+
+    /// The transaction has not been decided yet.
+    Pending,
+}
+
+/// The status of a [`Node`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NodeStatus {
+    /// The node is running.
+    Running,
+
+    /// The node has been gracefully shut down.
+    ShutDown,
+
+    /// The node has permanently failed, and is waiting for its replacement.
+    Failed,
+
+    /// The node has crashed, and never restarts in the fail-stop failure model.
+    Crashed,
+}
+
+/// [`FailureModel`] decides whether a crashed node restarts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FailureModel {
+    /// A crashed node restarts, and recovers the transaction from its log.
+    CrashRecovery,
+
+    /// A crashed node stays down forever.
+    FailStop,
+}
+
+/// [`System`] is the state of all the nodes.
 #[derive(Clone, Debug, Eq)]
 pub struct System {
     node_map: Vec<Node>,
+
+    /// The number of transitions since the coordinator received the last yes-vote, counted until
+    /// all the participants learn the decision.
+    ///
+    /// It is only tracked when the decision latency is checked.
+    latency: Option<usize>,
+
+    /// The number of ticks of the simulated clock.
+    clock: usize,
+
+    /// The participant elected as the coordinator after the coordinator failed.
+    acting_coordinator: Option<usize>,
+
+    /// The hot-standby replica of the coordinator, or `None` if there is no backup coordinator.
+    standby: Option<Standby>,
+
+    /// The messages in flight in the asynchronous network, sorted.
+    network: Vec<Message>,
+
+    /// The messages that have been delivered, sorted; only tracked when messages may be
+    /// duplicated.
+    delivered: Vec<Message>,
+
+    /// The number of delivered messages that the network has duplicated.
+    duplicates: usize,
+
+    /// The failed directed links of the asynchronous network as pairs of the sender and the
+    /// destination, sorted.
+    failed_links: Vec<(usize, usize)>,
+
+    /// The number of crashes of the nodes; only tracked when the crashes are bounded.
+    crashes: usize,
+
+    /// The coordinator decided to commit before all the participants had voted for commit.
+    unvoted_commit: bool,
+
+    /// A fault, such as a crash or a link failure, has been injected into the execution.
+    faulty: bool,
+
+    /// A node has rolled back the transaction that it had committed, or the other way around.
+    decision_flipped: bool,
+
+    /// The number of actions taken from the initial state; only tracked when the depth is
+    /// bounded.
+    depth: usize,
+}
+
+/// [`Standby`] is the hot-standby replica of the coordinator.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Standby {
+    /// The copy of the coordinator transaction.
+    transaction: Transaction,
+
+    /// The copy of the commit timestamp of the coordinator.
+    commit_timestamp: Option<usize>,
+
+    /// The standby is running or failed.
+    status: NodeStatus,
+
+    /// The standby has taken over the transaction from the failed coordinator.
+    active: bool,
+}
+
+impl Standby {
+    /// Returns `true` if the standby is running.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.status == NodeStatus::Running
+    }
+}
+
+impl System {
+    /// Returns the nodes in the order of their identifiers.
+    #[must_use]
+    pub fn nodes(&self) -> &[Node] {
+        &self.node_map
+    }
+
+    /// Records that a fault has been injected into the execution.
+    fn with_fault(self) -> System {
+        System {
+            faulty: true,
+            ..self
+        }
+    }
+
+    /// Returns `true` if the coordinator has received the yes-votes of all the participants.
+    fn is_all_voted(&self, coordinator_node_id: usize) -> bool {
+        let coordinator = &self.node_map[coordinator_node_id];
+        coordinator.transaction.state() == TransactionState::Prepared
+            && coordinator.transaction.is_all_prepared()
+    }
+
+    /// Returns `true` if no participant has flagged a decision of its parent as a protocol
+    /// violation.
+    fn is_violation_free(&self) -> bool {
+        self.node_map.iter().all(|node| node.violation.is_none())
+    }
+
+    /// Returns `true` if all the nodes that have committed the transaction by the protocol have
+    /// recorded the same commit timestamp.
+    fn is_commit_timestamp_agreed(&self) -> bool {
+        let mut timestamps = self
+            .node_map
+            .iter()
+            .filter(|node| {
+                !node.read_only
+                    && node.heuristic.is_none()
+                    && node.transaction.state() == TransactionState::Committed
+            })
+            .map(|node| node.commit_timestamp);
+        let first = timestamps.next();
+        first.is_none_or(|first| first.is_some() && timestamps.all(|timestamp| timestamp == first))
+    }
+
+    /// Returns `true` if the coordinator and all the participants have learned the decision.
+    fn is_all_decided(&self, coordinator_node_id: usize) -> bool {
+        self.node_map[coordinator_node_id]
+            .transaction
+            .state()
+            .is_terminal()
+            && self.node_map.iter().all(|node| {
+                matches!(
+                    node.transaction.state(),
+                    TransactionState::Inactive
+                        | TransactionState::Committed
+                        | TransactionState::RolledBack
+                )
+            })
+    }
 }
 
 impl Hash for System {
@@ -78,16 +741,56 @@ impl Hash for System {
                 node_id.hash(state);
                 node.hash(state);
             });
+        self.latency.hash(state);
+        self.clock.hash(state);
+        self.acting_coordinator.hash(state);
+        self.standby.hash(state);
+        self.network.hash(state);
+        self.delivered.hash(state);
+        self.duplicates.hash(state);
+        self.failed_links.hash(state);
+        self.crashes.hash(state);
+        self.unvoted_commit.hash(state);
+        self.faulty.hash(state);
+        self.decision_flipped.hash(state);
+        self.depth.hash(state);
     }
 }
 
 impl PartialEq for System {
     fn eq(&self, other: &Self) -> bool {
         self.node_map == other.node_map
+            && self.latency == other.latency
+            && self.clock == other.clock
+            && self.acting_coordinator == other.acting_coordinator
+            && self.standby == other.standby
+            && self.network == other.network
+            && self.delivered == other.delivered
+            && self.duplicates == other.duplicates
+            && self.failed_links == other.failed_links
+            && self.crashes == other.crashes
+            && self.unvoted_commit == other.unvoted_commit
+            && self.faulty == other.faulty
+            && self.decision_flipped == other.decision_flipped
+            && self.depth == other.depth
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// [`Message`] is a message in the asynchronous network.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Message {
+    /// The action that the destination takes when the message is delivered.
+    action: Action,
+
+    /// The sequence number of the transaction of the sender when the message was sent.
+    sequence: usize,
+
+    /// The node that sent the message.
+    sender: usize,
+}
+
+/// [`Action`] is a state transition of [`System`].
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Action {
     /// Starts the transaction on the specified node.
     Start(usize),
@@ -106,398 +809,5450 @@ pub enum Action {
     AckPrepare(usize),
 
     /// The participant node failed to prepare the transaction for commit.
-    AckPrepareFail,
+    AckPrepareFail(usize),
 
-    /// Commits the transaction.
-    Commit(usize),
+    /// The read-only participant node has released its resources without preparing the
+    /// transaction.
+    AckReadOnly(usize),
+
+    /// The coordinator delegates the decision to the participant node, its only participant.
+    Delegate(usize),
+
+    /// The participant node that the decision is delegated to commits the transaction.
+    OnePhaseCommit(usize),
+
+    /// The participant node that the decision is delegated to reports its decision.
+    AckDecision(usize),
+
+    /// The first participant node whose coordinator is down queries the second participant node
+    /// for the outcome.
+    QueryPeer(usize, usize),
+
+    /// The first participant node answers the query of the second participant node with its
+    /// decision.
+    PeerAnswer(usize, usize),
+
+    /// The participant node is elected as the coordinator after the coordinator failed.
+    Elect(usize),
+
+    /// The prepared participant commits the transaction without the decision of its parent.
+    HeuristicCommit(usize),
+
+    /// The prepared participant rolls back the transaction without the decision of its parent.
+    HeuristicRollback(usize),
+
+    /// Commits the transaction with the commit timestamp.
+    Commit(usize, usize),
 
     /// Commits the transaction.
     Rollback(usize),
 
-    /// Crashes the node.
+    /// Crashes the node, which restarts and recovers the transaction from its log, or stays down
+    /// in the fail-stop failure model.
     Crash(usize),
-}
 
-/// [`TransactionModel`] implements state transitions.
-#[derive(Hash)]
-pub struct TransactionModel {
-    /// The number of nodes.
-    num_nodes: usize,
-}
+    /// Crashes the undecided coordinator node before its participants are durable; it restarts
+    /// knowing nothing about the transaction.
+    Amnesia(usize),
 
-impl TransactionModel {
-    /// Creates a new [`TransactionModel`].
-    pub fn new(num_nodes: usize) -> TransactionModel {
-        TransactionModel { num_nodes }
-    }
+    /// Snapshots the transaction of the node, and truncates its log.
+    Checkpoint(usize),
 
-    /// Determines if the node is the fixed transaction coordinator or not.
-    pub const fn is_coordinator(node_id: usize) -> bool {
-        node_id == 0
-    }
+    /// The log of the active participant node fills up, and appending records to it fails.
+    LogWriteFail(usize),
 
-    /// Returns the fixed coordinator node ID.
-    pub const fn coordinator_node_id() -> usize {
-        0
-    }
-}
+    /// Syncs the log records that the node has written to the persistent storage.
+    Fsync(usize),
 
-impl TransactionModel {
-    fn add_actions_per_node(node_id: usize, node: &Node, actions: &mut Vec<Action>) {
-        match node.transaction.state() {
-            TransactionState::Inactive => {
-                if Self::is_coordinator(node_id) {
-                    // If the transaction is inactive, has the node start the transaction.
-                    actions.push(Action::Start(node_id));
-                } else {
-                    // Send a request to the coordinator to participate in the distributed transaction.
-                    actions.push(Action::RequestJoin(node_id));
-                }
-            }
-            TransactionState::Active => {
-                if Self::is_coordinator(node_id) {
-                    // Repeatedly send `AckJoin` to all the participants.
-                    node.transaction
-                        .for_each_participant(|participant_node_id| {
-                            actions.push(Action::AckJoin(participant_node_id));
-                        });
+    /// Gracefully shuts down the node.
+    ///
+    /// Unlike [`Action::Crash`], the node aborts its unfinished work, notifies the coordinator,
+    /// and flushes its log before it stops.
+    Shutdown(usize),
 
-                    // The coordinator transaction decides when to start committing the transaction.
-                    actions.push(Action::RequestPrepare(node_id));
-                }
+    /// Permanently fails the participant node.
+    Fail(usize),
 
-                // A transaction can be rolled back any time.
-                actions.push(Action::Rollback(node_id));
-            }
-            TransactionState::Prepared => {
-                if Self::is_coordinator(node_id) {
-                    if node.transaction.is_all_prepared() {
-                        // This model emulates the coordinator voting against the unanimous
-                        // decision of the participants as sending `Rollback` to all the
-                        // participants.
-                        actions.push(Action::Commit(Self::coordinator_node_id()));
-                        actions.push(Action::Rollback(Self::coordinator_node_id()));
-                    } else {
-                        // Send `RequestPrepare` repeatedly until it gets ACKs from all the
-                        // participants.
-                        node.transaction
-                            .for_each_participant(|participant_node_id| {
-                                actions.push(Action::RequestPrepare(participant_node_id));
-                            });
-                    }
-                } else {
-                    // This model emulates voting against commit as sending `AckPrepareFail` to the
-                    // coordinator.
-                    actions.push(Action::AckPrepare(node_id));
-                    actions.push(Action::AckPrepareFail);
-                }
-            }
-            TransactionState::Committed => {
-                if Self::is_coordinator(node_id) {
-                    // Send `Commit` messages to all the participants.
-                    node.transaction
-                        .for_each_participant(|participant_node_id| {
-                            actions.push(Action::Commit(participant_node_id));
-                        });
-                }
-            }
-            TransactionState::RolledBack => {
-                if Self::is_coordinator(node_id) {
-                    // Send `Rollback` messages to all the participants.
-                    node.transaction
-                        .for_each_participant(|participant_node_id| {
-                            actions.push(Action::Rollback(participant_node_id));
-                        });
-                }
-            }
-        }
+    /// Replaces the permanently failed participant node with a fresh node.
+    ///
+    /// The replacement restores the transaction state from the coordinator before it participates
+    /// in the transaction again.
+    Replace(usize),
 
-        // Any node can crash any time.
-        //
-        // TODO: how to make it work??
-        actions.push(Action::Crash(node_id));
-    }
+    /// The prepared participant node inquires the coordinator about the decision.
+    Inquire(usize),
 
-    fn next_system_state(last_state: &System, node_id: usize, next_node_state: Node) -> System {
-        let mut next_node_map: Vec<Node> = last_state.node_map.clone();
-        next_node_map[node_id] = next_node_state;
-        System {
-            node_map: next_node_map,
-        }
-    }
+    /// The prepared participant node sends a check message to its parent.
+    CheckStatus(usize),
 
-    fn start_transaction(node: &Node) -> Option<Node> {
-        if node.transaction.state() == TransactionState::Inactive {
-            let mut new_node_state = node.clone();
-            new_node_state.transaction.start();
-            Some(new_node_state)
-        } else {
-            None
-        }
-    }
+    /// The participant node receives the reply of its parent to the check message.
+    StatusReply(usize, Decision),
 
-    fn add_participant(node: &Node, participant_node_id: usize) -> Node {
-        let mut new_node_state = node.clone();
+    /// The coordinator forgets the committed transaction.
+    Forget(usize),
 
-        // Make sure that the transaction is active.
-        new_node_state.transaction.start();
-        if new_node_state.transaction.state() == TransactionState::Active {
-            new_node_state
-                .transaction
-                .add_participant(participant_node_id);
+    /// Advances the simulated clock by one tick.
+    Tick,
 
-            // Record the transaction state change: a new participant added.
-            new_node_state
-                .persistency
-                .push(Action::RequestJoin(participant_node_id));
-        }
+    /// The timer of the waiting node expires.
+    Timeout(usize),
 
-        new_node_state
-    }
+    /// The participant copies its transaction state to the replica.
+    Replicate(usize, usize),
 
-    fn start_distributed_transaction(node: &Node) -> Node {
-        let mut new_node_state = node.clone();
+    /// The replica of the participant is lost, and the participant fails over to the surviving
+    /// replicas.
+    LoseReplica(usize, usize),
 
-        // The coordinator knows that this node participates in the distributed transaction.
-        if new_node_state.transaction.start() {
-            // Record the transaction state change: a transaction started in the node.
-            new_node_state
-                .persistency
-                .push(Action::Start(new_node_state.id));
-        }
+    /// The standby takes over the transaction from the failed coordinator.
+    Takeover,
 
-        new_node_state
-    }
+    /// The standby commits the transaction after taking over.
+    StandbyCommit,
+
+    /// The standby rolls back the transaction after taking over.
+    StandbyRollback,
+
+    /// The standby permanently fails.
+    FailStandby,
+
+    /// The overloaded participant node defers its vote, and asks the coordinator to retry.
+    Retry(usize),
+
+    /// The coordinator gives up on the participant node that has deferred its vote too many times.
+    GiveUp(usize),
+
+    /// Sends the message to the asynchronous network.
+    Send(Box<Message>),
+
+    /// Delivers the message in flight to its destination.
+    Deliver(Box<Message>),
+
+    /// The network duplicates the delivered message, and has it in flight again.
+    Duplicate(Box<Message>),
+
+    /// The directed link from the first node to the second node fails, and the messages sent over
+    /// it are lost.
+    FailLink(usize, usize),
+
+    /// The Byzantine participant node sends the forged message.
+    Forge(Box<Action>),
+}
+
+/// [`TransactionModel`] implements state transitions.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Hash)]
+pub struct TransactionModel {
+    /// The parent of each node in the commit tree; the coordinator has no parent.
+    parents: Vec<Option<usize>>,
+
+    /// The presumed-commit variant of the protocol.
+    presumed_commit: bool,
+
+    /// Nodes may be gracefully shut down.
+    graceful_shutdown: bool,
+
+    /// Participant nodes may permanently fail and be replaced.
+    replacement: bool,
+
+    /// Nodes never crash, and the decision latency is checked.
+    decision_latency: bool,
+
+    /// The number of ticks after which a timeout fires, or `None` if timeouts fire at any time.
+    timeout_ticks: Option<usize>,
+
+    /// The maximum number of ticks of the simulated clock.
+    max_ticks: usize,
+
+    /// The number of times a participant may defer its vote, or `None` if participants never
+    /// defer.
+    retry_budget: Option<usize>,
+
+    /// The participant nodes that only read data.
+    read_only: Vec<usize>,
+
+    /// A coordinator with a single participant delegates the decision to it.
+    one_phase: bool,
+
+    /// The coordinator delegates the decision to its last participant after preparing the others.
+    last_agent: bool,
+
+    /// The workload of the transaction, or `None` if the data is not modeled.
+    workload: Option<Box<dyn Workload>>,
+
+    /// Nodes may fail, and participants of a failed coordinator query their peers for the
+    /// outcome.
+    cooperative_termination: bool,
+
+    /// The coordinator may fail, and a participant is elected to take over the transaction.
+    coordinator_election: bool,
+
+    /// The node that coordinates the transaction.
+    coordinator: usize,
+
+    /// The coordinator may fail, and its prepared participants may decide heuristically.
+    heuristics: bool,
+
+    /// A hot-standby replica of the coordinator takes over when the coordinator fails.
+    backup_coordinator: bool,
+
+    /// The number of yes-votes after which the coordinator commits, or `None` if all the
+    /// participants have to vote for commit.
+    quorum: Option<usize>,
+
+    /// The number of replicas of each participant.
+    replicas: usize,
+
+    /// Messages are delivered asynchronously after they are sent.
+    asynchronous_network: bool,
+
+    /// The maximum number of delivered messages that the asynchronous network duplicates.
+    max_duplicates: usize,
+
+    /// The maximum number of directed links of the asynchronous network that fail.
+    max_link_failures: usize,
+
+    /// The maximum number of crashes of the nodes, or `None` if nodes crash any number of times.
+    max_crashes: Option<usize>,
+
+    /// Whether a crashed node restarts.
+    failure_model: FailureModel,
+
+    /// Steps that do not change the state are pruned, so that the liveness properties are checked
+    /// at the end of an execution in which the nodes only retransmit.
+    fairness: bool,
+
+    /// The maximum number of actions taken from the initial state, or `None` if the executions
+    /// are explored to the end.
+    max_depth: Option<usize>,
+
+    /// Nodes may checkpoint the transaction, and truncate their logs.
+    checkpointing: bool,
+
+    /// Log records are durable only after the node syncs its log, and a crash loses the records
+    /// that are not synced.
+    torn_writes: bool,
+
+    /// The participant node that sends arbitrary protocol messages, or `None` if all the nodes
+    /// follow the protocol.
+    byzantine: Option<usize>,
+
+    /// Timeouts fire only by explicit timer expirations of waiting nodes.
+    timers: bool,
+
+    /// The coordinator may crash before its participants are durable, and forget the
+    /// transaction.
+    coordinator_amnesia: bool,
+
+    /// Appending records to the log of a participant may fail.
+    log_write_failures: bool,
+
+    /// The participant node that may die permanently along with the coordinator, or `None` if
+    /// no participant dies.
+    dead_participant: Option<usize>,
+
+    /// The nodes that may crash, or `None` if any node may crash.
+    crash_nodes: Option<Vec<usize>>,
+
+    /// The participant nodes whose logs may fill up, or `None` if any participant's log may.
+    log_write_failure_nodes: Option<Vec<usize>>,
+
+    /// The nodes whose links to their parents may fail, or `None` if any link may fail.
+    link_failure_nodes: Option<Vec<usize>>,
+
+    /// The names of the properties to check, or `None` if all the properties are checked.
+    selected_properties: Option<Vec<String>>,
+
+    /// The extra invariants over the transaction states of the nodes.
+    invariants: Vec<Invariant>,
+
+    /// The behaviors of the participant nodes that do not follow the protocol as
+    /// [`Cooperative`], sorted by the node.
+    behaviors: Vec<(usize, Box<dyn ParticipantBehavior>)>,
+
+    /// The decision policy of the coordinator.
+    coordinator_policy: Box<dyn CoordinatorPolicy>,
+}
+
+impl TransactionModel {
+    /// Returns a [`TransactionModelBuilder`] that validates the parameters of the model.
+    #[must_use]
+    pub fn builder() -> TransactionModelBuilder {
+        TransactionModelBuilder::new()
+    }
+
+    /// Creates a new [`TransactionModel`] of the two-phase commit protocol, or of its
+    /// presumed-commit variant.
+    #[must_use]
+    pub fn new(num_nodes: usize, presumed_commit: bool) -> TransactionModel {
+        Self::with_topology(vec![None; num_nodes], presumed_commit)
+    }
+
+    /// Creates a new [`TransactionModel`] of the hierarchical two-phase commit protocol.
+    ///
+    /// `parents[node_id]` is the parent of the node in the commit tree rooted at the coordinator;
+    /// a node without a parent is a child of the coordinator.
+    #[must_use]
+    pub fn with_topology(parents: Vec<Option<usize>>, presumed_commit: bool) -> TransactionModel {
+        TransactionModel {
+            parents,
+            presumed_commit,
+            graceful_shutdown: false,
+            replacement: false,
+            decision_latency: false,
+            timeout_ticks: None,
+            max_ticks: 0,
+            retry_budget: None,
+            read_only: Vec::new(),
+            one_phase: false,
+            last_agent: false,
+            workload: None,
+            cooperative_termination: false,
+            coordinator_election: false,
+            coordinator: 0,
+            heuristics: false,
+            backup_coordinator: false,
+            quorum: None,
+            replicas: 1,
+            asynchronous_network: false,
+            max_duplicates: 0,
+            max_link_failures: 0,
+            max_crashes: None,
+            failure_model: FailureModel::CrashRecovery,
+            fairness: false,
+            max_depth: None,
+            checkpointing: false,
+            torn_writes: false,
+            byzantine: None,
+            timers: false,
+            coordinator_amnesia: false,
+            log_write_failures: false,
+            dead_participant: None,
+            crash_nodes: None,
+            log_write_failure_nodes: None,
+            link_failure_nodes: None,
+            selected_properties: None,
+            invariants: Vec::new(),
+            behaviors: Vec::new(),
+            coordinator_policy: Box::new(Permissive),
+        }
+    }
+
+    /// Allows nodes to be gracefully shut down.
+    #[must_use]
+    pub fn graceful_shutdown(self, graceful_shutdown: bool) -> TransactionModel {
+        TransactionModel {
+            graceful_shutdown,
+            ..self
+        }
+    }
+
+    /// Allows participant nodes to permanently fail and be replaced with fresh nodes.
+    #[must_use]
+    pub fn replacement(self, replacement: bool) -> TransactionModel {
+        TransactionModel {
+            replacement,
+            ..self
+        }
+    }
+
+    /// Checks the decision latency in a fault-free system.
+    #[must_use]
+    pub fn decision_latency(self, decision_latency: bool) -> TransactionModel {
+        TransactionModel {
+            decision_latency,
+            ..self
+        }
+    }
+
+    /// Enables the simulated clock: timeouts fire only after `timeout_ticks` ticks, and the clock
+    /// advances at most `max_ticks` ticks.
+    #[must_use]
+    pub fn simulated_clock(self, timeout_ticks: usize, max_ticks: usize) -> TransactionModel {
+        TransactionModel {
+            timeout_ticks: Some(timeout_ticks),
+            max_ticks,
+            ..self
+        }
+    }
+
+    /// Allows overloaded participants to defer their votes; the coordinator gives up on a
+    /// participant that has deferred more than `retry_budget` times.
+    #[must_use]
+    pub fn flow_control(self, retry_budget: usize) -> TransactionModel {
+        TransactionModel {
+            retry_budget: Some(retry_budget),
+            ..self
+        }
+    }
+
+    /// Makes the leaf participant nodes read-only: they reply with read-only votes, and are
+    /// excluded from the decision.
+    #[must_use]
+    pub fn read_only_participants(self, read_only: Vec<usize>) -> TransactionModel {
+        TransactionModel { read_only, ..self }
+    }
+
+    /// Lets the coordinator prepare all but the last participant, and delegate the decision to the
+    /// last participant.
+    #[must_use]
+    pub fn last_agent(self, last_agent: bool) -> TransactionModel {
+        TransactionModel { last_agent, ..self }
+    }
+
+    /// Lets a coordinator with a single participant skip the voting phase, and delegate the
+    /// decision to the participant.
+    #[must_use]
+    pub fn one_phase(self, one_phase: bool) -> TransactionModel {
+        TransactionModel { one_phase, ..self }
+    }
+
+    /// Allows nodes to fail permanently, and lets the participants of a failed coordinator query
+    /// their peers for the outcome.
+    #[must_use]
+    pub fn cooperative_termination(self, cooperative_termination: bool) -> TransactionModel {
+        TransactionModel {
+            cooperative_termination,
+            ..self
+        }
+    }
+
+    /// Allows the coordinator to fail, and elects a participant to take over the transaction.
+    #[must_use]
+    pub fn coordinator_election(self, coordinator_election: bool) -> TransactionModel {
+        TransactionModel {
+            coordinator_election,
+            ..self
+        }
+    }
+
+    /// Lets the coordinator fail, and its prepared participants decide heuristically.
+    #[must_use]
+    pub fn heuristics(self, heuristics: bool) -> TransactionModel {
+        TransactionModel { heuristics, ..self }
+    }
+
+    /// Adds a hot-standby replica of the coordinator that takes over when the coordinator fails.
+    #[must_use]
+    pub fn backup_coordinator(self, backup_coordinator: bool) -> TransactionModel {
+        TransactionModel {
+            backup_coordinator,
+            ..self
+        }
+    }
+
+    /// Lets the coordinator commit once `quorum` participants have voted for commit.
+    #[must_use]
+    pub fn quorum(self, quorum: usize) -> TransactionModel {
+        TransactionModel {
+            quorum: Some(quorum),
+            ..self
+        }
+    }
+
+    /// Backs each participant by `replicas` replicas.
+    #[must_use]
+    pub fn replicated_participants(self, replicas: usize) -> TransactionModel {
+        TransactionModel { replicas, ..self }
+    }
+
+    /// Delivers messages asynchronously: a message is sent to the network, and delivered to its
+    /// destination in a later step.
+    #[must_use]
+    pub fn asynchronous_network(self, asynchronous_network: bool) -> TransactionModel {
+        TransactionModel {
+            asynchronous_network,
+            ..self
+        }
+    }
+
+    /// Lets the asynchronous network duplicate up to `max_duplicates` delivered messages.
+    #[must_use]
+    pub fn message_duplication(self, max_duplicates: usize) -> TransactionModel {
+        TransactionModel {
+            max_duplicates,
+            ..self
+        }
+    }
+
+    /// Lets up to `max_link_failures` directed links of the asynchronous network fail, so that a
+    /// node may still receive messages from a node that no longer receives its messages.
+    #[must_use]
+    pub fn link_failures(self, max_link_failures: usize) -> TransactionModel {
+        TransactionModel {
+            max_link_failures,
+            ..self
+        }
+    }
+
+    /// Lets the nodes crash at most `max_crashes` times in total.
+    #[must_use]
+    pub fn crash_budget(self, max_crashes: usize) -> TransactionModel {
+        TransactionModel {
+            max_crashes: Some(max_crashes),
+            ..self
+        }
+    }
+
+    /// Sets whether a crashed node restarts.
+    #[must_use]
+    pub fn failure_model(self, failure_model: FailureModel) -> TransactionModel {
+        TransactionModel {
+            failure_model,
+            ..self
+        }
+    }
+
+    /// Prunes the steps that do not change the state, such as a retransmission of a decision that
+    /// the participant has already applied.
+    ///
+    /// Liveness properties are only checked at the end of an execution, which never comes if a
+    /// node can retransmit forever; along with a crash budget, the pruning makes every execution
+    /// end once the nodes stop making progress.
+    #[must_use]
+    pub fn fairness(self, fairness: bool) -> TransactionModel {
+        TransactionModel { fairness, ..self }
+    }
+
+    /// Explores the executions of at most `max_depth` actions.
+    ///
+    /// The liveness properties are not checked, as an execution cut at the depth has not ended.
+    #[must_use]
+    pub fn max_depth(self, max_depth: usize) -> TransactionModel {
+        TransactionModel {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    /// Lets the nodes checkpoint the transaction, and truncate their logs.
+    #[must_use]
+    pub fn checkpointing(self, checkpointing: bool) -> TransactionModel {
+        TransactionModel {
+            checkpointing,
+            ..self
+        }
+    }
+
+    /// Makes log records durable only after the node explicitly syncs its log.
+    #[must_use]
+    pub fn torn_writes(self, torn_writes: bool) -> TransactionModel {
+        TransactionModel {
+            torn_writes,
+            ..self
+        }
+    }
+
+    /// Makes the participant node Byzantine: it may vote for commit without preparing the
+    /// transaction, and send commit messages to the other participants.
+    #[must_use]
+    pub fn byzantine_participant(self, node_id: usize) -> TransactionModel {
+        TransactionModel {
+            byzantine: Some(node_id),
+            ..self
+        }
+    }
+
+    /// Lets the participant node and the coordinator die permanently in the middle of the
+    /// protocol.
+    #[must_use]
+    pub fn dead_participant(self, node_id: usize) -> TransactionModel {
+        TransactionModel {
+            dead_participant: Some(node_id),
+            ..self
+        }
+    }
+
+    /// Checks only the properties of the names.
+    ///
+    /// The "no blocking" property, which fails whenever the coordinator may stay down, is checked
+    /// only when it is selected.
+    #[must_use]
+    pub fn select_properties(self, names: Vec<String>) -> TransactionModel {
+        TransactionModel {
+            selected_properties: Some(names),
+            ..self
+        }
+    }
+
+    /// Checks the extra invariants along with the built-in properties.
+    #[must_use]
+    pub fn invariants(self, invariants: Vec<Invariant>) -> TransactionModel {
+        TransactionModel { invariants, ..self }
+    }
+
+    /// Injects the faults of the scenario, and only them.
+    ///
+    /// The asynchronous network is enabled if the scenario has network faults.
+    #[must_use]
+    pub fn scenario(self, scenario: &Scenario) -> TransactionModel {
+        let nodes = |fault: Option<&Fault>| fault.and_then(|fault| fault.nodes.clone());
+        TransactionModel {
+            asynchronous_network: self.asynchronous_network
+                || scenario.link_failures.is_some()
+                || scenario.duplicates > 0,
+            max_crashes: scenario
+                .crashes
+                .as_ref()
+                .map_or(Some(0), |crashes| crashes.budget),
+            crash_nodes: nodes(scenario.crashes.as_ref()),
+            failure_model: if scenario.fail_stop {
+                FailureModel::FailStop
+            } else {
+                FailureModel::CrashRecovery
+            },
+            coordinator_amnesia: scenario.coordinator_amnesia,
+            log_write_failures: scenario.log_write_failures.is_some(),
+            log_write_failure_nodes: nodes(scenario.log_write_failures.as_ref()),
+            max_link_failures: scenario.link_failures.as_ref().map_or(0, |link_failures| {
+                link_failures.budget.unwrap_or(usize::MAX)
+            }),
+            link_failure_nodes: nodes(scenario.link_failures.as_ref()),
+            max_duplicates: scenario.duplicates,
+            dead_participant: scenario.dead_participant,
+            ..self
+        }
+    }
+
+    /// Applies the coordinator, the crash budget, the faults and the properties of the
+    /// configuration; the parameters that it does not set are left as they are.
+    ///
+    /// The number of nodes is fixed when the model is created.
+    #[must_use]
+    pub fn config(self, config: &ModelConfig) -> TransactionModel {
+        let mut model = self;
+        if let Some(coordinator) = config.coordinator {
+            model = model.coordinator(coordinator);
+        }
+        if let Some(max_crashes) = config.crash_budget {
+            model = model.crash_budget(max_crashes);
+        }
+        for fault in &config.faults {
+            model = match fault {
+                FaultType::FailStop => model.failure_model(FailureModel::FailStop),
+                FaultType::CoordinatorAmnesia => model.coordinator_amnesia(true),
+                FaultType::LogWriteFailures => model.log_write_failures(true),
+                FaultType::TornWrites => model.torn_writes(true),
+                FaultType::Replacement => model.replacement(true),
+            };
+        }
+        if !config.properties.is_empty() {
+            model = model.select_properties(config.properties.clone());
+        }
+        model
+    }
+
+    /// Fires timeouts by explicit timer expirations that are enabled only while a node is
+    /// waiting.
+    #[must_use]
+    pub fn timers(self, timers: bool) -> TransactionModel {
+        TransactionModel { timers, ..self }
+    }
+
+    /// Lets the undecided coordinator crash before its participants are durable, and restart
+    /// knowing nothing about the transaction.
+    #[must_use]
+    pub fn coordinator_amnesia(self, coordinator_amnesia: bool) -> TransactionModel {
+        TransactionModel {
+            coordinator_amnesia,
+            ..self
+        }
+    }
+
+    /// Lets the logs of the participants fill up, so that appending records to them fails.
+    #[must_use]
+    pub fn log_write_failures(self, log_write_failures: bool) -> TransactionModel {
+        TransactionModel {
+            log_write_failures,
+            ..self
+        }
+    }
+
+    /// Sets the node that coordinates the transaction.
+    #[must_use]
+    pub fn coordinator(self, coordinator: usize) -> TransactionModel {
+        TransactionModel {
+            coordinator,
+            ..self
+        }
+    }
+
+    /// Sets the behavior of the participant node, replacing its previous behavior.
+    #[must_use]
+    pub fn participant_behavior(
+        self,
+        node_id: usize,
+        behavior: Box<dyn ParticipantBehavior>,
+    ) -> TransactionModel {
+        let mut behaviors = self.behaviors;
+        behaviors.retain(|(behaving_node_id, _)| *behaving_node_id != node_id);
+        behaviors.push((node_id, behavior));
+        behaviors.sort_unstable_by_key(|(behaving_node_id, _)| *behaving_node_id);
+        TransactionModel { behaviors, ..self }
+    }
+
+    /// Sets the decision policy of the coordinator.
+    #[must_use]
+    pub fn coordinator_policy(
+        self,
+        coordinator_policy: Box<dyn CoordinatorPolicy>,
+    ) -> TransactionModel {
+        TransactionModel {
+            coordinator_policy,
+            ..self
+        }
+    }
+
+    /// Sets the workload that determines the data operations of the transaction.
+    #[must_use]
+    pub fn workload(self, workload: Box<dyn Workload>) -> TransactionModel {
+        TransactionModel {
+            workload: Some(workload),
+            ..self
+        }
+    }
+
+    /// Returns the maximum number of transitions between the coordinator receiving the last
+    /// yes-vote and all the participants learning the decision.
+    ///
+    /// The coordinator decides in one transition, and each of the `num_nodes - 1` participants
+    /// learns the decision from one message; transitions that do not change the state, such as
+    /// retransmissions, are not counted as weak fairness eventually makes progress, and neither are
+    /// the status queries of the participants that time out, as only their replies carry the
+    /// decision.
+    #[must_use]
+    pub fn decision_latency_bound(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns the short description of the property with the name, or `None` if the model has no
+    /// such property.
+    #[must_use]
+    pub fn property_description(name: &str) -> Option<&'static str> {
+        Some(match name {
+            "agreement" => "All the nodes that have decided agree on the decision.",
+            "commit validity" => {
+                "The coordinator commits only if all the participants have voted for commit."
+            }
+            "abort validity" => {
+                "A participant that has voted for commit rolls back only if its parent has."
+            }
+            "non-triviality" => "All the nodes may commit in an execution without faults.",
+            "global rollback" => "All the nodes may roll back the transaction.",
+            "decision stability" => "No node reverses its decision.",
+            "no protocol violation" => "No participant flags a decision of its parent as invalid.",
+            "same commit timestamp" => "All the committed nodes record the same commit timestamp.",
+            "prepared survives crash" => {
+                "A node that has logged the prepare record stays prepared."
+            }
+            "committed survives crash" => "A committed node recovers the commit after a crash.",
+            "recovery correctness" => "A node recovers the last decision in its durable log.",
+            "unprepared crash aborts" => {
+                "A participant that crashed before it was prepared never commits."
+            }
+            "decision resent after recovery" => {
+                "A recovered coordinator can resend its decision to the prepared participants."
+            }
+            "no blocking" => "No prepared participant loses every node that knows the decision.",
+            "crash before fan-out" => {
+                "The coordinator may crash after committing and before sending the decision."
+            }
+            "termination" => "Every running node eventually decides without crashes.",
+            "commit reaches participants" => {
+                "Once the coordinator commits, every participant eventually commits."
+            }
+            "recovery from checkpoint" => {
+                "A node recovers the same transaction from its checkpoint as from the full log."
+            }
+            "one-way link failure" => "A vote may be lost over a failed link.",
+            "blocking after failures" => {
+                "The participants may block when the coordinator and a participant die."
+            }
+            "honest agreement" => "The nodes that follow the protocol agree on the decision.",
+            "termination after amnesia" => {
+                "A coordinator that has lost its log rolls back, and no participant commits."
+            }
+            "no ack without prepare record" | "vote synced before sent" => {
+                "A participant counted as prepared has synced its prepare record."
+            }
+            "no in-doubt shutdown" => "A node is not shut down with an unfinished transaction.",
+            "decision latency bound" => {
+                "The participants learn the decision within a bound after the last yes-vote."
+            }
+            "data consistency" => "The data of the nodes satisfies the invariant of the workload.",
+            "progress after coordinator failure" => {
+                "The transaction can be decided after the coordinator fails."
+            }
+            "vote survives replica loss" => {
+                "A participant that has voted keeps its prepare record or a replica of it."
+            }
+            "stragglers catch up" => "A participant of a committed transaction never rolls back.",
+            "progress while a coordinator replica is alive" => {
+                "No participant is in doubt forever while the standby coordinator runs."
+            }
+            "no heuristic-mixed outcome" => {
+                "Heuristic decisions do not contradict each other or the coordinator."
+            }
+            "blocking only when peers are down" => {
+                "An in-doubt participant queries a peer unless every peer is down or in doubt."
+            }
+            "no abort within retry budget" => {
+                "Deferring the vote rolls back only after the retry budget is exhausted."
+            }
+            "no double apply after replacement" => {
+                "A replacement never applies a decision that its predecessor applied."
+            }
+            "custom invariants" => "The invariants loaded from the invariants file hold.",
+            _ => return None,
+        })
+    }
+
+    /// Determines if the node is the transaction coordinator or not.
+    #[must_use]
+    pub const fn is_coordinator(&self, node_id: usize) -> bool {
+        node_id == self.coordinator
+    }
+
+    /// Returns the coordinator node ID.
+    #[must_use]
+    pub const fn coordinator_node_id(&self) -> usize {
+        self.coordinator
+    }
+
+    /// Returns the state of the transaction at the coordinator.
+    #[must_use]
+    pub fn coordinator_state(&self, state: &System) -> TransactionState {
+        state.node_map[self.coordinator].transaction.state()
+    }
+
+    /// Returns the parent of the node in the commit tree.
+    ///
+    /// The parent of the coordinator is ignored.
+    #[must_use]
+    pub fn parent_node_id(&self, node_id: usize) -> usize {
+        if self.is_coordinator(node_id) {
+            return self.coordinator;
+        }
+        self.parents
+            .get(node_id)
+            .copied()
+            .flatten()
+            .unwrap_or(self.coordinator)
+    }
+
+    /// Returns the identifier of the node that processes the action.
+    #[must_use]
+    pub fn destination(&self, action: &Action) -> usize {
+        match action {
+            Action::RequestJoin(node_id)
+            | Action::AckPrepare(node_id)
+            | Action::AckPrepareFail(node_id)
+            | Action::AckReadOnly(node_id)
+            | Action::Delegate(node_id)
+            | Action::AckDecision(node_id)
+            | Action::Inquire(node_id)
+            | Action::CheckStatus(node_id)
+            | Action::Retry(node_id)
+            | Action::GiveUp(node_id) => self.parent_node_id(*node_id),
+            Action::Start(node_id)
+            | Action::AckJoin(node_id)
+            | Action::RequestPrepare(node_id)
+            | Action::Commit(node_id, _)
+            | Action::Rollback(node_id)
+            | Action::OnePhaseCommit(node_id)
+            | Action::Crash(node_id)
+            | Action::Amnesia(node_id)
+            | Action::Checkpoint(node_id)
+            | Action::LogWriteFail(node_id)
+            | Action::Fsync(node_id)
+            | Action::Timeout(node_id)
+            | Action::Shutdown(node_id)
+            | Action::Fail(node_id)
+            | Action::Replace(node_id)
+            | Action::Forget(node_id)
+            | Action::QueryPeer(_, node_id)
+            | Action::PeerAnswer(_, node_id)
+            | Action::Elect(node_id)
+            | Action::HeuristicCommit(node_id)
+            | Action::HeuristicRollback(node_id)
+            | Action::Replicate(node_id, _)
+            | Action::LoseReplica(node_id, _)
+            | Action::StatusReply(node_id, _)
+            | Action::FailLink(_, node_id) => *node_id,
+            Action::Send(message) | Action::Deliver(message) | Action::Duplicate(message) => {
+                self.destination(&message.action)
+            }
+            Action::Forge(forged) => self.destination(forged),
+            // The clock is shared by all the nodes, and the standby shadows the coordinator.
+            Action::Tick
+            | Action::Takeover
+            | Action::StandbyCommit
+            | Action::StandbyRollback
+            | Action::FailStandby => self.coordinator_node_id(),
+        }
+    }
+}
+
+impl TransactionModel {
+    fn add_actions_per_node(
+        &self,
+        state: &System,
+        node_id: usize,
+        node: &Node,
+        actions: &mut Vec<Action>,
+    ) {
+        if !node.is_running() {
+            // A node that has been shut down or failed does nothing.
+            return;
+        }
+        if !node.pending.is_empty() {
+            // The node syncs the log records before it acts on them, and may crash before.
+            actions.push(Action::Fsync(node_id));
+            self.add_crash_actions(state, node_id, actions);
+            return;
+        }
+        let timed_out = self.is_timed_out(state, node);
+        if self.timers && !timed_out && self.is_waiting(node_id, node) {
+            actions.push(Action::Timeout(node_id));
+        }
+
+        match node.transaction.state() {
+            TransactionState::Inactive => {
+                if self.is_coordinator(node_id) {
+                    // If the transaction is inactive, has the node start the transaction unless
+                    // it has forgotten the committed transaction.
+                    if !node.forgotten {
+                        actions.push(Action::Start(node_id));
+                    }
+                } else {
+                    // Send a request to the coordinator to participate in the distributed transaction.
+                    actions.push(Action::RequestJoin(node_id));
+                }
+            }
+            TransactionState::Active => {
+                // Repeatedly send `AckJoin` to all the participants.
+                node.transaction
+                    .for_each_participant(|participant_node_id| {
+                        actions.push(Action::AckJoin(participant_node_id));
+                    });
+
+                if self.is_coordinator(node_id)
+                    && self.is_all_joined(state)
+                    && self
+                        .coordinator_policy
+                        .starts_voting(self.joined_participants(state), self.parents.len() - 1)
+                {
+                    // The coordinator transaction decides when to start committing the transaction.
+                    match self.delegate_to(node) {
+                        Some(participant_node_id) => {
+                            actions.push(Action::Delegate(participant_node_id));
+                        }
+                        None => actions.push(Action::RequestPrepare(node_id)),
+                    }
+                }
+
+                // A transaction can be rolled back when it times out; in the quorum variant, only
+                // the coordinator can.
+                if timed_out && (self.quorum.is_none() || self.is_coordinator(node_id)) {
+                    actions.push(Action::Rollback(node_id));
+                }
+            }
+            TransactionState::Prepared => {
+                if node.delegated {
+                    // The coordinator waits for the decision of the participant that it has
+                    // delegated the decision to.
+                } else if let Some(last_agent) = self.last_agent_of(node_id, node) {
+                    Self::add_last_agent_actions(node, last_agent, actions);
+                } else if self.is_coordinator(node_id) {
+                    self.add_decision_actions(state, node_id, node, actions);
+                } else if !node.transaction.is_all_prepared() {
+                    // An intermediate node forwards `RequestPrepare` to its participants before it
+                    // votes.
+                    node.transaction
+                        .for_each_participant(|participant_node_id| {
+                            actions.push(Action::RequestPrepare(participant_node_id));
+                        });
+                } else {
+                    self.add_vote_actions(node_id, node, timed_out, actions);
+                }
+            }
+            TransactionState::PreCommitted => {
+                // The two-phase commit protocol does not pre-commit transactions.
+            }
+            TransactionState::Committed if node.read_only => {
+                // Reply read-only to the parent until it removes the participant.
+                let parent = &state.node_map[self.parent_node_id(node_id)];
+                if parent.transaction.has_participant(node_id) && !parent.delegated {
+                    actions.push(Action::AckReadOnly(node_id));
+                }
+            }
+            TransactionState::Committed => self.add_commit_actions(node_id, node, actions),
+            TransactionState::RolledBack => {
+                // Send `Rollback` messages to all the participants.
+                let mut has_participants = false;
+                node.transaction
+                    .for_each_participant(|participant_node_id| {
+                        has_participants = true;
+                        actions.push(Action::Rollback(participant_node_id));
+                    });
+
+                if (has_participants || node.disk_full) && !self.is_coordinator(node_id) {
+                    // An intermediate node that has rolled back, or a participant that has failed
+                    // to log the prepare record, votes against commit.
+                    actions.push(Action::AckPrepareFail(node_id));
+                }
+            }
+        }
+
+        if self.one_phase || self.last_agent {
+            self.add_one_phase_actions(state, node_id, node, actions);
+        }
+
+        self.add_log_actions(node_id, node, actions);
+        self.add_crash_actions(state, node_id, actions);
+    }
+
+    fn add_log_actions(&self, node_id: usize, node: &Node, actions: &mut Vec<Action>) {
+        if self.checkpointing && !node.persistency.is_empty() {
+            actions.push(Action::Checkpoint(node_id));
+        }
+
+        // The log of an active participant may fill up before it logs the prepare record.
+        if self.log_write_failures
+            && Self::is_affected(self.log_write_failure_nodes.as_ref(), node_id)
+            && !self.is_coordinator(node_id)
+            && !node.disk_full
+            && node.transaction.state() == TransactionState::Active
+        {
+            actions.push(Action::LogWriteFail(node_id));
+        }
+    }
+
+    /// Adds the actions of the prepared coordinator: it decides once the participants have voted
+    /// for commit, and requests the missing votes otherwise, as far as its policy allows.
+    fn add_decision_actions(
+        &self,
+        state: &System,
+        node_id: usize,
+        node: &Node,
+        actions: &mut Vec<Action>,
+    ) {
+        // The coordinator that has been asked to retry gives up only once the retry budget is
+        // exhausted.
+        let retrying = self.is_within_retry_budget(state);
+        if node
+            .transaction
+            .is_quorum_prepared(self.quorum.unwrap_or(usize::MAX))
+        {
+            actions.push(Action::Commit(
+                self.coordinator_node_id(),
+                Self::commit_timestamp_of(state, node_id),
+            ));
+            // This model emulates the coordinator voting against the unanimous decision of the
+            // participants as sending `Rollback` to all the participants, if its policy allows it.
+            if !retrying && self.coordinator_policy.may_abort_despite_yes_votes() {
+                actions.push(Action::Rollback(self.coordinator_node_id()));
+            }
+        } else {
+            // Send `RequestPrepare` repeatedly until it gets ACKs from all the participants.
+            let (mut acked, mut participants) = (0, 0);
+            node.transaction
+                .for_each_participant(|participant_node_id| {
+                    actions.push(Action::RequestPrepare(participant_node_id));
+                    participants += 1;
+                    if node.transaction.is_prepared(participant_node_id) {
+                        acked += 1;
+                    }
+                });
+            if let Some(retry_budget) = self.retry_budget {
+                Self::add_flow_control_actions(state, node, retry_budget, actions);
+            }
+            if !retrying
+                && self
+                    .coordinator_policy
+                    .may_abort_on_partial_acks(acked, participants)
+            {
+                actions.push(Action::Rollback(self.coordinator_node_id()));
+            }
+        }
+    }
+
+    fn add_crash_actions(&self, state: &System, node_id: usize, actions: &mut Vec<Action>) {
+        // Any node can crash any time unless the system is fault-free or the crash budget is
+        // exhausted; it recovers the transaction from its log when it restarts.
+        if !self.decision_latency
+            && self.is_crash_allowed(state)
+            && Self::is_affected(self.crash_nodes.as_ref(), node_id)
+        {
+            actions.push(Action::Crash(node_id));
+            if self.is_amnesia_possible(node_id, &state.node_map[node_id]) {
+                actions.push(Action::Amnesia(node_id));
+            }
+        }
+    }
+
+    /// Returns `true` if the coordinator may lose its log before its participants are durable.
+    ///
+    /// The participants are durable once the coordinator has decided, or, in the presumed-commit
+    /// variant, once it has logged them before sending the prepare requests.
+    fn is_amnesia_possible(&self, node_id: usize, node: &Node) -> bool {
+        self.coordinator_amnesia
+            && self.failure_model == FailureModel::CrashRecovery
+            && self.is_coordinator(node_id)
+            && !node.amnesiac
+            && match node.transaction.state() {
+                TransactionState::Active => true,
+                TransactionState::Prepared => !self.presumed_commit,
+                _ => false,
+            }
+    }
+
+    fn add_vote_actions(
+        &self,
+        node_id: usize,
+        node: &Node,
+        timed_out: bool,
+        actions: &mut Vec<Action>,
+    ) {
+        // This model emulates voting against commit as sending `AckPrepareFail` to the parent; the
+        // participant votes only once, and for commit only after its replicas have the prepare
+        // record and unless it has lost its prepare record in a crash.
+        let behavior = self.behavior_of(node_id);
+        if node.vote.is_none() {
+            if node.is_replicated() && !node.crashed_unprepared && behavior.may_vote(true) {
+                actions.push(Action::AckPrepare(node_id));
+            }
+            if behavior.may_vote(false) {
+                actions.push(Action::AckPrepareFail(node_id));
+            }
+        }
+
+        if self.presumed_commit && timed_out {
+            // Inquire the coordinator about the decision in case its message is lost.
+            actions.push(Action::Inquire(node_id));
+        }
+        if timed_out && node.vote.is_some() && node.violation.is_none() {
+            // Check the transaction status with the parent unless it has contradicted the log.
+            match node.status_reply {
+                None | Some(Decision::Pending) => actions.push(Action::CheckStatus(node_id)),
+                Some(decision) => actions.push(Action::StatusReply(node_id, decision)),
+            }
+        }
+    }
+
+    fn add_commit_actions(&self, node_id: usize, node: &Node, actions: &mut Vec<Action>) {
+        // Send `Commit` messages to all the participants.
+        let timestamp = node.commit_timestamp.unwrap_or_default();
+        node.transaction
+            .for_each_participant(|participant_node_id| {
+                actions.push(Action::Commit(participant_node_id, timestamp));
+                if self.quorum.is_some()
+                    && self.is_coordinator(node_id)
+                    && !node.transaction.is_prepared(participant_node_id)
+                {
+                    // Let the straggler start the transaction, and request its vote for it to
+                    // catch up.
+                    actions.push(Action::AckJoin(participant_node_id));
+                    actions.push(Action::RequestPrepare(participant_node_id));
+                }
+            });
+
+        if self.is_coordinator(node_id) && self.presumed_commit {
+            // The coordinator may forget the transaction without waiting for acknowledgements; the
+            // messages that are not yet sent are lost.
+            actions.push(Action::Forget(node_id));
+        }
+    }
+
+    /// Adds the actions of the node, sending the messages to other nodes to the network instead of
+    /// having them received at once.
+    fn add_send_actions(
+        &self,
+        state: &System,
+        node_id: usize,
+        node: &Node,
+        actions: &mut Vec<Action>,
+    ) {
+        let mut node_actions = Vec::new();
+        self.add_actions_per_node(state, node_id, node, &mut node_actions);
+        actions.extend(node_actions.into_iter().map(|action| {
+            if self.destination(&action) == node_id {
+                action
+            } else {
+                Action::Send(Box::new(Message {
+                    action,
+                    sequence: node.transaction.sequence(),
+                    sender: node_id,
+                }))
+            }
+        }));
+    }
+
+    fn add_link_failure_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        // Either direction of the link between a participant and its parent may fail.
+        for node_id in 0..state.node_map.len() {
+            if self.is_coordinator(node_id)
+                || !Self::is_affected(self.link_failure_nodes.as_ref(), node_id)
+            {
+                continue;
+            }
+            let parent_node_id = self.parent_node_id(node_id);
+            for link in [(node_id, parent_node_id), (parent_node_id, node_id)] {
+                if state.failed_links.binary_search(&link).is_err() {
+                    actions.push(Action::FailLink(link.0, link.1));
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if a participant has received the prepare request and voted for commit, but
+    /// its vote cannot reach its parent over the failed link.
+    fn is_ack_lost(&self, state: &System) -> bool {
+        state.node_map.iter().any(|node| {
+            let parent_node_id = self.parent_node_id(node.id);
+            !self.is_coordinator(node.id)
+                && node.vote == Some(true)
+                && node.transaction.state() == TransactionState::Prepared
+                && !state.node_map[parent_node_id]
+                    .transaction
+                    .is_prepared(node.id)
+                && state
+                    .failed_links
+                    .binary_search(&(node.id, parent_node_id))
+                    .is_ok()
+        })
+    }
+
+    /// Returns `true` unless the transaction performs a workload on the nodes that have not joined
+    /// it yet.
+    fn is_all_joined(&self, state: &System) -> bool {
+        self.workload.is_none() || self.joined_participants(state) == self.parents.len() - 1
+    }
+
+    /// Returns the number of participant nodes that have joined the transaction of their parents.
+    fn joined_participants(&self, state: &System) -> usize {
+        (0..self.parents.len())
+            .filter(|node_id| !self.is_coordinator(*node_id))
+            .filter(|node_id| {
+                state.node_map[self.parent_node_id(*node_id)]
+                    .transaction
+                    .has_participant(*node_id)
+            })
+            .count()
+    }
+
+    /// Returns the participant that the coordinator delegates the decision to, if the coordinator
+    /// has exactly one participant and it is a leaf of the commit tree.
+    fn delegate_to(&self, coordinator: &Node) -> Option<usize> {
+        if !self.one_phase {
+            return None;
+        }
+        let mut participants = Vec::new();
+        coordinator
+            .transaction
+            .for_each_participant(|participant_node_id| participants.push(participant_node_id));
+        match participants[..] {
+            [participant_node_id] if !self.parents.contains(&Some(participant_node_id)) => {
+                Some(participant_node_id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the last participant of the coordinator that is a leaf of the commit tree, if the
+    /// last-agent optimization is enabled.
+    fn last_agent_of(&self, node_id: usize, coordinator: &Node) -> Option<usize> {
+        if !self.last_agent || !self.is_coordinator(node_id) {
+            return None;
+        }
+        let mut last_agent = None;
+        coordinator
+            .transaction
+            .for_each_participant(|participant_node_id| {
+                if !self.parents.contains(&Some(participant_node_id)) {
+                    last_agent = last_agent.max(Some(participant_node_id));
+                }
+            });
+        last_agent
+    }
+
+    fn add_last_agent_actions(coordinator: &Node, last_agent: usize, actions: &mut Vec<Action>) {
+        // Send `RequestPrepare` repeatedly to all but the last agent, and delegate the decision to
+        // the last agent once the others have voted for commit.
+        let mut all_prepared = true;
+        coordinator
+            .transaction
+            .for_each_participant(|participant_node_id| {
+                if participant_node_id != last_agent {
+                    all_prepared &= coordinator.transaction.is_prepared(participant_node_id);
+                    actions.push(Action::RequestPrepare(participant_node_id));
+                }
+            });
+        if all_prepared {
+            actions.push(Action::Delegate(last_agent));
+        }
+    }
+
+    fn add_one_phase_actions(
+        &self,
+        state: &System,
+        node_id: usize,
+        node: &Node,
+        actions: &mut Vec<Action>,
+    ) {
+        let parent = &state.node_map[self.parent_node_id(node_id)];
+        if self.is_coordinator(node_id)
+            || !parent.delegated
+            || parent.transaction.state() != TransactionState::Prepared
+        {
+            return;
+        }
+        match node.transaction.state() {
+            TransactionState::Active => {
+                // The participant decides by itself, and may only roll back once it has lost its
+                // prepare record in a crash; a timed-out participant may already roll back.
+                if !node.crashed_unprepared && self.behavior_of(node_id).may_vote(true) {
+                    actions.push(Action::OnePhaseCommit(node_id));
+                }
+                if !self.is_timed_out(state, node) {
+                    actions.push(Action::Rollback(node_id));
+                }
+            }
+            TransactionState::Committed | TransactionState::RolledBack => {
+                // Report the decision repeatedly until the coordinator learns it.
+                actions.push(Action::AckDecision(node_id));
+            }
+            _ => (),
+        }
+    }
+
+    fn add_flow_control_actions(
+        state: &System,
+        coordinator: &Node,
+        retry_budget: usize,
+        actions: &mut Vec<Action>,
+    ) {
+        coordinator
+            .transaction
+            .for_each_participant(|participant_node_id| {
+                let participant = &state.node_map[participant_node_id];
+                if coordinator.transaction.is_prepared(participant_node_id)
+                    || participant.transaction.state() != TransactionState::Active
+                {
+                    return;
+                }
+                if participant.retries > retry_budget {
+                    // The participant has exhausted the retry budget.
+                    actions.push(Action::GiveUp(participant_node_id));
+                } else if participant.is_running() {
+                    // The participant is overloaded, and defers its vote.
+                    actions.push(Action::Retry(participant_node_id));
+                }
+            });
+    }
+
+    /// Returns `true` if a participant has deferred its vote, and none has exhausted the retry
+    /// budget.
+    fn is_within_retry_budget(&self, state: &System) -> bool {
+        self.retry_budget.is_some_and(|retry_budget| {
+            state.node_map.iter().any(|node| node.retries > 0)
+                && state
+                    .node_map
+                    .iter()
+                    .all(|node| node.retries <= retry_budget)
+        })
+    }
+
+    fn add_replication_actions(state: &System, actions: &mut Vec<Action>) {
+        for (node_id, node) in state.node_map.iter().enumerate() {
+            if !node.is_running() {
+                continue;
+            }
+            let num_lost = node
+                .replicas
+                .iter()
+                .filter(|replica| replica.is_none())
+                .count();
+            for (replica, replica_state) in node.replicas.iter().enumerate() {
+                let Some(replica_state) = replica_state else {
+                    continue;
+                };
+                if *replica_state != node.transaction.state() {
+                    actions.push(Action::Replicate(node_id, replica));
+                }
+                if num_lost + 1 < node.replicas.len() {
+                    // Up to all but one of the replicas may be lost.
+                    actions.push(Action::LoseReplica(node_id, replica));
+                }
+            }
+        }
+    }
+
+    fn replicate(node: &Node, replica: usize) -> Option<Node> {
+        let mut new_node_state = node.clone();
+        let replica_state = new_node_state.replicas.get_mut(replica)?.as_mut()?;
+        if *replica_state == node.transaction.state() {
+            return None;
+        }
+        *replica_state = node.transaction.state();
+        Some(new_node_state)
+    }
+
+    fn lose_replica(node: &Node, replica: usize) -> Option<Node> {
+        let mut new_node_state = node.clone();
+        new_node_state.replicas.get_mut(replica)?.take()?;
+        if node.transaction.state().is_terminal() {
+            // A decided participant keeps the decision that it has logged, instead of failing over
+            // to a replica that has not caught up with it.
+            return Some(new_node_state);
+        }
+
+        // Fail over to the most advanced state among the surviving replicas.
+        let restored_state = new_node_state
+            .replicas
+            .iter()
+            .flatten()
+            .copied()
+            .max_by_key(|state| match state {
+                TransactionState::Inactive => 0,
+                TransactionState::Active => 1,
+                TransactionState::Prepared | TransactionState::PreCommitted => 2,
+                TransactionState::Committed | TransactionState::RolledBack => 3,
+            })?;
+        if restored_state != node.transaction.state() {
+            let mut transaction = node.new_transaction();
+            match restored_state {
+                TransactionState::Inactive => (),
+                TransactionState::Active => {
+                    transaction.start();
+                }
+                TransactionState::Prepared | TransactionState::PreCommitted => {
+                    transaction.start();
+                    transaction.prepare();
+                }
+                TransactionState::Committed => {
+                    transaction.start();
+                    transaction.prepare();
+                    transaction.commit();
+                }
+                TransactionState::RolledBack => {
+                    transaction.rollback();
+                }
+            }
+            new_node_state.transaction = transaction;
+        }
+        Some(new_node_state)
+    }
+
+    /// Returns `true` unless a participant that has voted for commit has lost its prepare record
+    /// along with its replicas.
+    fn is_vote_durable(state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            node.replicas.is_empty()
+                || node.vote != Some(true)
+                || !matches!(
+                    node.transaction.state(),
+                    TransactionState::Inactive | TransactionState::Active
+                )
+        })
+    }
+
+    /// Returns `true` if the node has waited in its current state long enough to time out.
+    fn is_timed_out(&self, state: &System, node: &Node) -> bool {
+        if self.timers {
+            return node.timer_expired;
+        }
+        self.timeout_ticks
+            .is_none_or(|timeout_ticks| state.clock - node.since >= timeout_ticks)
+    }
+
+    /// Returns `true` if the node waits for a message: the prepare request of an active
+    /// transaction, or the decision on its vote.
+    fn is_waiting(&self, node_id: usize, node: &Node) -> bool {
+        match node.transaction.state() {
+            TransactionState::Active => true,
+            TransactionState::Prepared => {
+                !self.is_coordinator(node_id) && node.vote.is_some() && node.violation.is_none()
+            }
+            _ => false,
+        }
+    }
+
+    fn add_shutdown_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        state
+            .node_map
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_running())
+            .for_each(|(node_id, node)| {
+                let can_shut_down = if self.is_coordinator(node_id) {
+                    // The coordinator has to wait until all the participants learn the decision.
+                    let mut drained = !matches!(
+                        node.transaction.state(),
+                        TransactionState::Active | TransactionState::Prepared
+                    );
+                    coordinator
+                        .transaction
+                        .for_each_participant(|participant_node_id| {
+                            let participant = &state.node_map[participant_node_id];
+                            if participant.status != NodeStatus::ShutDown
+                                && !participant.transaction.state().is_terminal()
+                            {
+                                drained = false;
+                            }
+                        });
+                    drained
+                } else {
+                    // An in-doubt participant has to wait for the decision of the coordinator.
+                    node.transaction.state() != TransactionState::Prepared
+                };
+                if can_shut_down {
+                    actions.push(Action::Shutdown(node_id));
+                }
+            });
+    }
+
+    fn add_replacement_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        state
+            .node_map
+            .iter()
+            .enumerate()
+            .filter(|(node_id, _)| !self.is_coordinator(*node_id))
+            .for_each(|(node_id, node)| match node.status {
+                NodeStatus::Running if node.incarnation == 0 => {
+                    // A participant node may permanently fail once.
+                    actions.push(Action::Fail(node_id));
+                }
+                NodeStatus::Failed => actions.push(Action::Replace(node_id)),
+                _ => (),
+            });
+    }
+
+    fn add_termination_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        for (node_id, node) in state.node_map.iter().enumerate() {
+            if node.is_running() && (!self.replacement || self.is_coordinator(node_id)) {
+                // Any node may fail; participants fail through replacement if it is enabled.
+                actions.push(Action::Fail(node_id));
+            }
+            if !self.is_in_doubt(state, node_id, node) {
+                continue;
+            }
+            for peer_node_id in &node.peers {
+                let peer = &state.node_map[*peer_node_id];
+                if !peer.is_running() {
+                    continue;
+                }
+                match peer.transaction.state() {
+                    TransactionState::Inactive | TransactionState::Active => {
+                        actions.push(Action::QueryPeer(node_id, *peer_node_id));
+                    }
+                    TransactionState::Committed | TransactionState::RolledBack => {
+                        actions.push(Action::PeerAnswer(*peer_node_id, node_id));
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    fn add_election_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        if coordinator.is_running() {
+            if !self.cooperative_termination {
+                // The coordinator may fail; it fails through cooperative termination otherwise.
+                actions.push(Action::Fail(self.coordinator_node_id()));
+            }
+            return;
+        }
+        let mut survivors = state
+            .node_map
+            .iter()
+            .enumerate()
+            .filter(|(node_id, node)| !self.is_coordinator(*node_id) && node.is_running());
+        if !state
+            .acting_coordinator
+            .is_some_and(|node_id| state.node_map[node_id].is_running())
+        {
+            // Elect the running participant with the lowest identifier.
+            if let Some((node_id, _)) = survivors.next() {
+                actions.push(Action::Elect(node_id));
+            }
+            return;
+        }
+        match self.recovered_decision(state) {
+            Some(TransactionState::Committed) => {
+                // The commit timestamp is recovered from a participant that has committed.
+                let timestamp = survivors
+                    .clone()
+                    .find_map(|(_, node)| node.commit_timestamp)
+                    .unwrap_or_default();
+                survivors
+                    .filter(|(_, node)| node.transaction.state() == TransactionState::Prepared)
+                    .for_each(|(node_id, _)| actions.push(Action::Commit(node_id, timestamp)));
+            }
+            Some(_) => survivors
+                .filter(|(_, node)| {
+                    matches!(
+                        node.transaction.state(),
+                        TransactionState::Active | TransactionState::Prepared
+                    )
+                })
+                .for_each(|(node_id, _)| actions.push(Action::Rollback(node_id))),
+            None => (),
+        }
+    }
+
+    fn add_heuristic_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        if !self.cooperative_termination
+            && !self.coordinator_election
+            && state.node_map[self.coordinator_node_id()].is_running()
+        {
+            // The coordinator may fail; it fails through the other recovery protocols otherwise.
+            actions.push(Action::Fail(self.coordinator_node_id()));
+        }
+        for (node_id, node) in state.node_map.iter().enumerate() {
+            if self.is_in_doubt(state, node_id, node) {
+                // A participant that has lost its prepare record in a crash may only roll back.
+                if !node.crashed_unprepared {
+                    actions.push(Action::HeuristicCommit(node_id));
+                }
+                actions.push(Action::HeuristicRollback(node_id));
+            }
+        }
+    }
+
+    fn add_dead_participant_actions(&self, state: &System, dead: usize, actions: &mut Vec<Action>) {
+        // The participant and the coordinator may die at any point of the protocol.
+        for node_id in [dead, self.coordinator_node_id()] {
+            if state.node_map[node_id].is_running() {
+                actions.push(Action::Fail(node_id));
+            }
+        }
+    }
+
+    /// Returns `true` if a prepared participant has lost its parent for good, and no node is left
+    /// that can ever tell it the decision.
+    fn is_blocking(&self, state: &System) -> bool {
+        state.node_map.iter().any(|node| {
+            let parent = &state.node_map[self.parent_node_id(node.id)];
+            self.is_in_doubt(state, node.id, node)
+                && node.heuristic.is_none()
+                && self.is_permanently_down(parent)
+                && !self.is_resolvable(state, node)
+        })
+    }
+
+    /// Returns `true` if the node is down, and never restarts or is replaced.
+    fn is_permanently_down(&self, node: &Node) -> bool {
+        match node.status {
+            NodeStatus::Running | NodeStatus::ShutDown => false,
+            NodeStatus::Failed => !self.replacement || self.is_coordinator(node.id),
+            NodeStatus::Crashed => true,
+        }
+    }
+
+    /// Returns `true` if the in-doubt participant may still learn the decision from the standby,
+    /// from a peer, or through an election.
+    fn is_resolvable(&self, state: &System, node: &Node) -> bool {
+        let is_undecided_peer = |peer: &Node| {
+            peer.id != node.id
+                && !self.is_coordinator(peer.id)
+                && peer.is_running()
+                && peer.transaction.state() != TransactionState::Prepared
+        };
+        let parent_is_coordinator = self.parent_node_id(node.id) == self.coordinator_node_id();
+        (parent_is_coordinator
+            && state
+                .standby
+                .as_ref()
+                .is_some_and(|standby| standby.is_running() || standby.active))
+            || (self.cooperative_termination
+                && node
+                    .peers
+                    .iter()
+                    .any(|peer_node_id| is_undecided_peer(&state.node_map[*peer_node_id])))
+            || (self.coordinator_election
+                && parent_is_coordinator
+                && state.node_map.iter().any(is_undecided_peer))
+    }
+
+    /// Returns `true` if the coordinator and the dead participant are down, and all the running
+    /// participants are prepared, so that none of them can terminate the transaction without the
+    /// risk of contradicting the decision that the dead nodes may have learned.
+    fn is_blocked(&self, state: &System) -> bool {
+        let mut participants = state
+            .node_map
+            .iter()
+            .filter(|node| !self.is_coordinator(node.id) && node.is_running())
+            .peekable();
+        self.dead_participant
+            .is_some_and(|dead| !state.node_map[dead].is_running())
+            && !state.node_map[self.coordinator_node_id()].is_running()
+            && participants.peek().is_some()
+            && participants.all(|node| node.transaction.state() == TransactionState::Prepared)
+    }
+
+    fn add_byzantine_actions(&self, state: &System, byzantine: usize, actions: &mut Vec<Action>) {
+        if !state.node_map[byzantine].is_running() {
+            return;
+        }
+
+        // Vote for commit regardless of the transaction state, and tell the other participants
+        // that the transaction has been committed.
+        actions.push(Action::Forge(Box::new(Action::AckPrepare(byzantine))));
+        let timestamp = Self::next_timestamp(state);
+        for node_id in 0..state.node_map.len() {
+            if node_id != byzantine && !self.is_coordinator(node_id) {
+                actions.push(Action::Forge(Box::new(Action::Commit(node_id, timestamp))));
+            }
+        }
+    }
+
+    /// Returns `true` unless a node that follows the protocol has committed the transaction while
+    /// another one has rolled it back.
+    fn is_honest_agreement(&self, state: &System) -> bool {
+        let mut decided = state
+            .node_map
+            .iter()
+            .filter(|node| Some(node.id) != self.byzantine && !node.read_only)
+            .map(|node| node.transaction.state())
+            .filter(|state| state.is_terminal());
+        decided
+            .next()
+            .is_none_or(|first| decided.all(|decision| decision == first))
+    }
+
+    fn add_standby_actions(&self, state: &System, actions: &mut Vec<Action>) {
+        let Some(standby) = state
+            .standby
+            .as_ref()
+            .filter(|standby| standby.is_running())
+        else {
+            return;
+        };
+        actions.push(Action::FailStandby);
+        if state.node_map[self.coordinator_node_id()].is_running() {
+            if !self.cooperative_termination && !self.coordinator_election && !self.heuristics {
+                // The coordinator may fail; it fails through the other recovery protocols
+                // otherwise.
+                actions.push(Action::Fail(self.coordinator_node_id()));
+            }
+            return;
+        }
+        if !standby.active {
+            actions.push(Action::Takeover);
+            return;
+        }
+        match standby.transaction.state() {
+            TransactionState::Active => actions.push(Action::StandbyRollback),
+            TransactionState::Prepared => {
+                if standby.transaction.is_all_prepared() {
+                    actions.push(Action::StandbyCommit);
+                }
+                actions.push(Action::StandbyRollback);
+            }
+            TransactionState::Committed => {
+                let timestamp = standby.commit_timestamp.unwrap_or_default();
+                standby.transaction.for_each_participant(|node_id| {
+                    actions.push(Action::Commit(node_id, timestamp));
+                });
+            }
+            TransactionState::RolledBack => standby
+                .transaction
+                .for_each_participant(|node_id| actions.push(Action::Rollback(node_id))),
+            TransactionState::Inactive | TransactionState::PreCommitted => (),
+        }
+    }
+
+    fn take_over(&self, last_state: &System) -> Option<System> {
+        let standby = last_state.standby.as_ref()?;
+        if !standby.is_running()
+            || standby.active
+            || last_state.node_map[self.coordinator_node_id()].is_running()
+        {
+            return None;
+        }
+        Some(System {
+            standby: Some(Standby {
+                active: true,
+                ..standby.clone()
+            }),
+            ..last_state.clone()
+        })
+    }
+
+    fn decide_standby(last_state: &System, commit: bool) -> Option<System> {
+        let mut standby = last_state.standby.clone()?;
+        let decided = standby.active
+            && if commit {
+                standby.commit_timestamp = Some(Self::next_timestamp(last_state));
+                standby.transaction.commit()
+            } else {
+                standby.transaction.rollback()
+            };
+        decided.then(|| System {
+            standby: Some(standby),
+            ..last_state.clone()
+        })
+    }
+
+    fn fail_standby(last_state: &System) -> Option<System> {
+        let standby = last_state.standby.as_ref()?;
+        standby.is_running().then(|| System {
+            standby: Some(Standby {
+                status: NodeStatus::Failed,
+                ..standby.clone()
+            }),
+            ..last_state.clone()
+        })
+    }
+
+    /// Copies the prepare and decision records of the running coordinator to the standby.
+    fn replicate_to_standby(&self, next_state: &mut System) {
+        let coordinator = &next_state.node_map[self.coordinator_node_id()];
+        if let Some(standby) = next_state.standby.as_mut() {
+            if coordinator.is_running() && standby.is_running() && !standby.active {
+                standby.transaction = coordinator.transaction.clone();
+                standby.commit_timestamp = coordinator.commit_timestamp;
+            }
+        }
+    }
+
+    /// Returns `true` unless a participant is in doubt while the standby is running and cannot
+    /// make progress.
+    fn is_takeover_enabled(&self, state: &System) -> bool {
+        let in_doubt = state
+            .node_map
+            .iter()
+            .enumerate()
+            .any(|(node_id, node)| self.is_in_doubt(state, node_id, node));
+        !in_doubt
+            || !state.standby.as_ref().is_some_and(Standby::is_running)
+            || self.next_steps(state).iter().any(|(action, next_state)| {
+                matches!(
+                    action,
+                    Action::Takeover
+                        | Action::StandbyCommit
+                        | Action::StandbyRollback
+                        | Action::Commit(..)
+                        | Action::Rollback(_)
+                ) && next_state != state
+            })
+    }
+
+    /// Returns the decision that the acting coordinator derives from the states of the running
+    /// participants, or `None` if the decision of the failed coordinator is unknown.
+    fn recovered_decision(&self, state: &System) -> Option<TransactionState> {
+        let mut states = state
+            .node_map
+            .iter()
+            .enumerate()
+            .filter(|(node_id, node)| !self.is_coordinator(*node_id) && node.is_running())
+            .map(|(_, node)| node.transaction.state());
+        if states
+            .clone()
+            .any(|state| state == TransactionState::Committed)
+        {
+            // The failed coordinator has committed the transaction.
+            Some(TransactionState::Committed)
+        } else if states.any(|state| {
+            matches!(
+                state,
+                TransactionState::Active | TransactionState::RolledBack
+            )
+        }) {
+            // The failed coordinator cannot have committed the transaction.
+            Some(TransactionState::RolledBack)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the transaction makes progress after the coordinator failed unless the
+    /// decision of the coordinator is unknown.
+    fn is_recovery_enabled(&self, state: &System) -> bool {
+        let undecided = state.node_map.iter().enumerate().any(|(node_id, node)| {
+            !self.is_coordinator(node_id)
+                && node.is_running()
+                && matches!(
+                    node.transaction.state(),
+                    TransactionState::Active | TransactionState::Prepared
+                )
+        });
+        state.node_map[self.coordinator_node_id()].is_running()
+            || !undecided
+            || self.recovered_decision(state).is_none()
+            || self.next_steps(state).iter().any(|(action, next_state)| {
+                matches!(
+                    action,
+                    Action::Elect(_) | Action::Commit(..) | Action::Rollback(_)
+                ) && next_state != state
+            })
+    }
+
+    /// Returns `true` if the participant node is prepared, and its parent is down.
+    fn is_in_doubt(&self, state: &System, node_id: usize, node: &Node) -> bool {
+        !self.is_coordinator(node_id)
+            && node.is_running()
+            && node.transaction.state() == TransactionState::Prepared
+            && !state.node_map[self.parent_node_id(node_id)].is_running()
+    }
+
+    fn query_peer(last_state: &System, peer_node_id: usize) -> Option<System> {
+        let peer = last_state.node_map.get(peer_node_id)?;
+        if !matches!(
+            peer.transaction.state(),
+            TransactionState::Inactive | TransactionState::Active
+        ) {
+            return None;
+        }
+
+        // The peer has not voted yet, and gives up the transaction to answer the query.
+        let next_peer_state = Self::rollback_distributed_transaction(peer);
+        Some(Self::next_system_state(
+            last_state,
+            peer_node_id,
+            next_peer_state,
+        ))
+    }
+
+    fn answer_peer(last_state: &System, peer_node_id: usize, node_id: usize) -> Option<System> {
+        let node = last_state.node_map.get(node_id)?;
+        let peer = last_state.node_map.get(peer_node_id)?;
+        let next_node_state = match peer.transaction.state() {
+            TransactionState::Committed => Self::commit_distributed_transaction(
+                node,
+                peer.commit_timestamp.unwrap_or_default(),
+            ),
+            TransactionState::RolledBack => Self::rollback_distributed_transaction(node),
+            _ => return None,
+        };
+        Some(Self::next_system_state(
+            last_state,
+            node_id,
+            next_node_state,
+        ))
+    }
+
+    fn next_system_state(last_state: &System, node_id: usize, mut next_node_state: Node) -> System {
+        if next_node_state.transaction.state() != last_state.node_map[node_id].transaction.state() {
+            // Restart the timer of the node.
+            next_node_state.since = last_state.clock;
+            next_node_state.timer_expired = false;
+        }
+        let mut next_node_map: Vec<Node> = last_state.node_map.clone();
+        next_node_map[node_id] = next_node_state;
+        System {
+            node_map: next_node_map,
+            latency: last_state.latency,
+            clock: last_state.clock,
+            acting_coordinator: last_state.acting_coordinator,
+            standby: last_state.standby.clone(),
+            network: last_state.network.clone(),
+            delivered: last_state.delivered.clone(),
+            duplicates: last_state.duplicates,
+            failed_links: last_state.failed_links.clone(),
+            crashes: last_state.crashes,
+            unvoted_commit: last_state.unvoted_commit,
+            faulty: last_state.faulty,
+            decision_flipped: last_state.decision_flipped,
+            depth: last_state.depth,
+        }
+    }
+
+    fn start_transaction(node: &Node) -> Option<Node> {
+        if node.transaction.state() == TransactionState::Inactive {
+            let mut new_node_state = node.clone();
+            new_node_state.transaction.start();
+
+            // Record the transaction state change: the coordinator started the transaction.
+            new_node_state.log(Action::Start(new_node_state.id));
+            Some(new_node_state)
+        } else {
+            None
+        }
+    }
+
+    fn add_participant(&self, node: &Node, participant_node_id: usize) -> Node {
+        let mut new_node_state = node.clone();
+        if node.forgotten {
+            // The transaction has been committed and forgotten.
+            return new_node_state;
+        }
+
+        // Make sure that the transaction is active; an intermediate node accepts participants only
+        // after it has joined the transaction of its parent.
+        if self.is_coordinator(node.id) {
+            new_node_state.transaction.start();
+        }
+        if new_node_state.transaction.state() == TransactionState::Active
+            && new_node_state
+                .transaction
+                .add_participant(participant_node_id)
+        {
+            // Record the transaction state change: a new participant added.
+            new_node_state.log(Action::RequestJoin(participant_node_id));
+        }
+
+        new_node_state
+    }
+
+    fn start_distributed_transaction(node: &Node) -> Node {
+        let mut new_node_state = node.clone();
+
+        // The coordinator knows that this node participates in the distributed transaction.
+        if new_node_state.transaction.start() {
+            // Record the transaction state change: a transaction started in the node.
+            new_node_state.log(Action::Start(new_node_state.id));
+        }
+
+        new_node_state
+    }
 
     fn prepare_distributed_transaction(node: &Node) -> Node {
         let mut new_node_state = node.clone();
 
-        // Prepare the transaction for commit.
-        if new_node_state.transaction.prepare() {
-            // Record the transaction state change: the transaction is prepared for commit.
-            new_node_state
-                .persistency
-                .push(Action::RequestPrepare(new_node_state.id));
+        if node.disk_full && !node.read_only && node.transaction.state() == TransactionState::Active
+        {
+            // The prepare record cannot be logged: abort rather than prepare.
+            new_node_state.transaction.rollback();
+            return new_node_state;
+        }
+
+        // Prepare the transaction for commit.
+        if new_node_state.transaction.prepare() {
+            // Record the transaction state change: the transaction is prepared for commit.
+            new_node_state.log(Action::RequestPrepare(new_node_state.id));
+        } else if node.read_only {
+            // A read-only participant releases its resources without logging anything.
+            new_node_state.transaction.commit();
+        }
+
+        new_node_state
+    }
+
+    fn remove_read_only(node: &Node, participant_node_id: usize) -> Option<Node> {
+        if node.transaction.state() != TransactionState::Prepared {
+            return None;
+        }
+        let mut new_node_state = node.clone();
+        new_node_state
+            .transaction
+            .remove_participant(participant_node_id)
+            .then_some(new_node_state)
+    }
+
+    fn delegate(node: &Node) -> Option<Node> {
+        let mut new_node_state = Self::prepare_distributed_transaction(node);
+        if new_node_state.transaction.state() != TransactionState::Prepared {
+            return None;
+        }
+        new_node_state.delegated = true;
+        Some(new_node_state)
+    }
+
+    fn commit_one_phase(node: &Node, timestamp: usize) -> Node {
+        let mut new_node_state = node.clone();
+
+        // Prepare and commit the transaction at once; a read-only transaction commits directly.
+        new_node_state.transaction.prepare();
+        Self::commit_distributed_transaction(&new_node_state, timestamp)
+    }
+
+    fn learn_decision(&self, last_state: &System, node_id: usize) -> Option<System> {
+        let coordinator = &last_state.node_map[self.parent_node_id(node_id)];
+        if !coordinator.delegated {
+            return None;
+        }
+        let participant = last_state.node_map.get(node_id)?;
+        let next_coordinator_state = match participant.transaction.state() {
+            TransactionState::Committed => Self::commit_distributed_transaction(
+                coordinator,
+                participant.commit_timestamp.unwrap_or_default(),
+            ),
+            TransactionState::RolledBack => Self::rollback_distributed_transaction(coordinator),
+            _ => return None,
+        };
+        Some(Self::next_system_state(
+            last_state,
+            self.parent_node_id(node_id),
+            next_coordinator_state,
+        ))
+    }
+
+    fn mark_prepared(node: &Node, participant_node_id: usize) -> Node {
+        let mut new_node_state = node.clone();
+        new_node_state
+            .transaction
+            .report_prepared(participant_node_id);
+        new_node_state
+    }
+
+    fn commit_distributed_transaction(node: &Node, timestamp: usize) -> Node {
+        let mut new_node_state = node.clone();
+
+        // Commit the transaction.
+        if new_node_state.transaction.commit() {
+            new_node_state.apply_decision();
+
+            // Record the transaction state change along with the commit timestamp: the
+            // transaction is committed.
+            new_node_state.commit_timestamp = Some(timestamp);
+            new_node_state.log(Action::Commit(new_node_state.id, timestamp));
+        }
+
+        new_node_state
+    }
+
+    fn rollback_distributed_transaction(node: &Node) -> Node {
+        let mut new_node_state = node.clone();
+
+        // Rollback the transaction unless it has been committed.
+        if new_node_state.transaction.state() != TransactionState::Committed
+            && new_node_state.transaction.rollback()
+        {
+            new_node_state.apply_decision();
+
+            // Record the transaction state change: the transaction is rolled back.
+            new_node_state.log(Action::Rollback(new_node_state.id));
+        }
+
+        new_node_state
+    }
+
+    /// Commits or rolls back the prepared transaction without the decision of the parent.
+    ///
+    /// A heuristic commit is recorded with a commit timestamp that the participant generates.
+    fn decide_heuristically(node: &Node, commit: bool, timestamp: usize) -> Option<Node> {
+        if node.transaction.state() != TransactionState::Prepared {
+            return None;
+        }
+        let mut new_node_state = if commit {
+            Self::commit_distributed_transaction(node, timestamp)
+        } else {
+            Self::rollback_distributed_transaction(node)
+        };
+        new_node_state.heuristic = Some(commit);
+        Some(new_node_state)
+    }
+
+    /// Returns `true` if some participants have committed and others have rolled back the
+    /// transaction after a heuristic decision.
+    fn is_heuristic_mixed(state: &System) -> bool {
+        let mut decided = state.node_map.iter().filter(|node| !node.read_only);
+        decided.clone().any(|node| node.heuristic.is_some())
+            && decided
+                .clone()
+                .any(|node| node.transaction.state() == TransactionState::Committed)
+            && decided.any(|node| node.transaction.state() == TransactionState::RolledBack)
+    }
+
+    /// Returns a logical commit timestamp greater than any commit timestamp known to the nodes.
+    fn next_timestamp(state: &System) -> usize {
+        state
+            .node_map
+            .iter()
+            .filter_map(|node| node.commit_timestamp)
+            .chain(
+                state
+                    .standby
+                    .as_ref()
+                    .and_then(|standby| standby.commit_timestamp),
+            )
+            .max()
+            .unwrap_or_default()
+            + 1
+    }
+
+    /// Returns the commit timestamp that the node has logged for the transaction, or a new one if
+    /// it has never committed the transaction.
+    fn commit_timestamp_of(state: &System, node_id: usize) -> usize {
+        state.node_map[node_id]
+            .commit_timestamp
+            .unwrap_or_else(|| Self::next_timestamp(state))
+    }
+
+    /// Records the first vote of the participant in its log.
+    fn log_vote(mut state: System, node_id: usize, commit: bool) -> System {
+        let node = &mut state.node_map[node_id];
+        if node.vote.is_none() {
+            node.vote = Some(commit);
+            node.log(if commit {
+                Action::AckPrepare(node_id)
+            } else {
+                Action::AckPrepareFail(node_id)
+            });
+        }
+        state
+    }
+
+    /// Flags the decision that contradicts the log of the participant without applying it.
+    fn flag_violation(node: &Node, decision: Action) -> Node {
+        let mut new_node_state = node.clone();
+        new_node_state.violation.get_or_insert(decision);
+        new_node_state
+    }
+
+    fn shutdown(&self, last_state: &System, node_id: usize) -> Option<System> {
+        let node = last_state.node_map.get(node_id)?;
+        let mut new_node_state = node.clone();
+
+        // Abort the unfinished work.
+        let aborted = matches!(
+            node.transaction.state(),
+            TransactionState::Inactive | TransactionState::Active
+        ) && new_node_state.transaction.rollback();
+        if aborted {
+            new_node_state.log(Action::Rollback(new_node_state.id));
+        }
+
+        // Flush the log with a shutdown record, and stop the node.
+        new_node_state.log(Action::Shutdown(node_id));
+        new_node_state.sync();
+        new_node_state.status = NodeStatus::ShutDown;
+        let mut next_state = Self::next_system_state(last_state, node_id, new_node_state);
+
+        // Notify the parent that the participant will no longer take part in the transaction.
+        let parent = &next_state.node_map[self.parent_node_id(node_id)];
+        if aborted && !self.is_coordinator(node_id) && parent.transaction.has_participant(node_id) {
+            let next_parent_state = Self::rollback_distributed_transaction(parent);
+            next_state = Self::next_system_state(
+                &next_state,
+                self.parent_node_id(node_id),
+                next_parent_state,
+            );
+        }
+
+        Some(next_state)
+    }
+
+    fn fail_permanently(node: &Node) -> Node {
+        let mut new_node_state = node.clone();
+        new_node_state.status = NodeStatus::Failed;
+        new_node_state
+    }
+
+    fn replace(&self, last_state: &System, node_id: usize) -> Option<System> {
+        let node = last_state.node_map.get(node_id)?;
+        if node.status != NodeStatus::Failed {
+            return None;
+        }
+
+        // The replacement starts with an empty log, and restores the data from its peers.
+        let mut new_node_state = if node.read_only {
+            Node::read_only(node_id)
+        } else {
+            Node::with_id(node_id)
+        };
+        new_node_state.incarnation = node.incarnation + 1;
+        new_node_state.applied.clone_from(&node.applied);
+        new_node_state.data.clone_from(&node.data);
+        new_node_state.operations.clone_from(&node.operations);
+        new_node_state.log(Action::Replace(node_id));
+
+        // Restore the transaction state from the restored data if the decision was applied to it,
+        // otherwise from the parent.
+        let parent = &last_state.node_map[self.parent_node_id(node_id)];
+        let restored_state = node
+            .applied
+            .first()
+            .map(|(_, decision)| *decision)
+            .or_else(|| {
+                parent
+                    .transaction
+                    .has_participant(node_id)
+                    .then(|| parent.transaction.state())
+            });
+        if let Some(restored_state) = restored_state {
+            let transaction = &mut new_node_state.transaction;
+            match restored_state {
+                TransactionState::Inactive => (),
+                TransactionState::Active => {
+                    transaction.start();
+                }
+                TransactionState::Prepared | TransactionState::PreCommitted => {
+                    transaction.start();
+                    if parent.transaction.is_prepared(node_id) {
+                        transaction.prepare();
+                    }
+                }
+                TransactionState::Committed => {
+                    transaction.start();
+                    transaction.prepare();
+                    transaction.commit();
+                }
+                TransactionState::RolledBack => {
+                    transaction.rollback();
+                }
+            }
+
+            // The decision is applied only if the restored data does not reflect it yet.
+            if transaction.state().is_terminal() && node.applied.is_empty() {
+                new_node_state.apply_decision();
+            }
+
+            // Log the restored transaction state for the replacement to recover it after a crash.
+            let restored_state = new_node_state.transaction.state();
+            if !matches!(
+                restored_state,
+                TransactionState::Inactive | TransactionState::RolledBack
+            ) {
+                new_node_state.log(Action::Start(node_id));
+            }
+            if matches!(
+                restored_state,
+                TransactionState::Prepared | TransactionState::Committed
+            ) {
+                new_node_state.log(Action::RequestPrepare(node_id));
+            }
+            match restored_state {
+                TransactionState::Committed => new_node_state.log(Action::Commit(
+                    node_id,
+                    parent.commit_timestamp.unwrap_or_default(),
+                )),
+                TransactionState::RolledBack => {
+                    new_node_state.log(Action::Rollback(node_id));
+                }
+                _ => (),
+            }
+        }
+
+        // The replacement writes the restored state to the disk before it takes part in the
+        // transaction.
+        new_node_state.sync();
+
+        Some(Self::next_system_state(last_state, node_id, new_node_state))
+    }
+
+    /// Returns `true` if the transaction on every running node has been committed or rolled back,
+    /// or forgotten after the commit, unless the node has not joined the transaction.
+    fn is_terminated(state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            !node.is_running()
+                || node.forgotten
+                || node.transaction.state() == TransactionState::Inactive
+                || node.transaction.state().is_terminal()
+        })
+    }
+
+    /// Returns `true` if the transaction is atomic and consistent across all the nodes.
+    #[must_use]
+    pub fn is_acid(&self, state: &System) -> bool {
+        Self::is_agreement(state) && self.is_commit_valid(state)
+    }
+
+    /// Returns `true` if all the nodes that have decided agree on the decision.
+    fn is_agreement(state: &System) -> bool {
+        // If a transaction on a node has decided to commit or roll back, all the participant
+        // transactions should agree on the decision.
+        let mut commit_decided: Option<bool> = None;
+        //
+        // A read-only participant is excluded as it has no effect on the data whichever decision
+        // it learns, and so is a heuristic decision that is not taken by the protocol; the
+        // decision of the standby that has taken over is included.
+        let standby_decision = state
+            .standby
+            .as_ref()
+            .filter(|standby| standby.active)
+            .map(|standby| standby.transaction.state());
+        !state
+            .node_map
+            .iter()
+            .filter(|node| !node.read_only && node.heuristic.is_none())
+            .map(|node| node.transaction.state())
+            .chain(standby_decision)
+            .any(|state| {
+                let final_state = match state {
+                    TransactionState::Committed => Some(true),
+                    TransactionState::RolledBack => Some(false),
+                    _ => None,
+                };
+                match (final_state, commit_decided) {
+                    (None, _) => false,
+                    (Some(_), None) => {
+                        commit_decided = final_state;
+                        false
+                    }
+                    (Some(final_state), Some(other_state)) => {
+                        // If they do not match, it is a consistency error.
+                        final_state != other_state
+                    }
+                }
+            })
+    }
+
+    /// Returns `true` if the action injects a fault rather than following the protocol.
+    fn is_fault(action: &Action) -> bool {
+        matches!(
+            action,
+            Action::Crash(_)
+                | Action::Amnesia(_)
+                | Action::LogWriteFail(_)
+                | Action::Fail(_)
+                | Action::LoseReplica(..)
+                | Action::FailStandby
+                | Action::Duplicate(_)
+                | Action::FailLink(..)
+                | Action::Forge(_)
+        )
+    }
+
+    /// Records whether the coordinator decides to commit before all the participants have voted
+    /// for commit.
+    ///
+    /// A coordinator that has delegated the decision to a participant learns the commit from it.
+    fn record_commit_decision(&self, last_state: &System, next_state: &mut System) {
+        let coordinator = &last_state.node_map[self.coordinator_node_id()];
+        if !coordinator.delegated
+            && coordinator.transaction.state() != TransactionState::Committed
+            && next_state.node_map[self.coordinator_node_id()]
+                .transaction
+                .state()
+                == TransactionState::Committed
+            && !coordinator
+                .transaction
+                .is_quorum_prepared(self.quorum.unwrap_or(usize::MAX))
+        {
+            next_state.unvoted_commit = true;
+        }
+    }
+
+    /// Records whether a node changes its decision on the transaction in the transition.
+    fn record_decision_flip(last_state: &System, next_state: &mut System) {
+        let flipped =
+            last_state
+                .node_map
+                .iter()
+                .zip(&next_state.node_map)
+                .any(|(last_node, next_node)| {
+                    let (last, next) =
+                        (last_node.transaction.state(), next_node.transaction.state());
+                    last.is_terminal() && next.is_terminal() && last != next
+                });
+        if flipped {
+            next_state.decision_flipped = true;
+        }
+    }
+
+    /// Returns `true` unless the coordinator has committed the transaction without the votes for
+    /// commit of all the participants.
+    fn is_commit_valid(&self, state: &System) -> bool {
+        if state.unvoted_commit {
+            // A participant that has prepared may not have voted yet when the coordinator
+            // commits, which the states of the participants alone do not tell.
+            return false;
+        }
+
+        // If the coordinator has decided to commit, all the participants in the commit tree
+        // also should commit or be prepared for commit, except for the read-only participants
+        // and the stragglers that have not voted in the quorum variant.
+        let mut not_unanimous = false;
+        if state.node_map[self.coordinator_node_id()]
+            .transaction
+            .state()
+            == TransactionState::Committed
+        {
+            let mut nodes = vec![self.coordinator_node_id()];
+            while let Some(node_id) = nodes.pop() {
+                state.node_map[node_id]
+                    .transaction
+                    .for_each_participant(|participant_node_id| {
+                        let participant = &state.node_map[participant_node_id];
+                        let state = participant.transaction.state();
+                        if !participant.read_only
+                            && participant.heuristic.is_none()
+                            && (self.quorum.is_none() || state == TransactionState::RolledBack)
+                            && state != TransactionState::Prepared
+                            && state != TransactionState::Committed
+                        {
+                            not_unanimous = true;
+                        }
+                        nodes.push(participant_node_id);
+                    });
+            }
+        }
+
+        !not_unanimous
+    }
+
+    /// Returns `true` if all the nodes that write data have rolled back the transaction.
+    fn is_rolled_back_everywhere(state: &System) -> bool {
+        state
+            .node_map
+            .iter()
+            .all(|node| node.read_only || node.transaction.state() == TransactionState::RolledBack)
+    }
+
+    /// Returns `true` unless a participant that has voted for commit has rolled back the
+    /// transaction while its running parent has not rolled it back.
+    ///
+    /// A parent that does not know the transaction presumes that it was rolled back.
+    fn is_abort_valid(&self, state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            let parent = &state.node_map[self.parent_node_id(node.id)];
+            self.is_coordinator(node.id)
+                || node.vote != Some(true)
+                || node.heuristic.is_some()
+                || node.transaction.state() != TransactionState::RolledBack
+                || !parent.is_running()
+                || matches!(
+                    parent.transaction.state(),
+                    TransactionState::RolledBack | TransactionState::Inactive
+                )
+        })
+    }
+
+    /// Returns `true` if all the nodes have committed the transaction.
+    fn is_committed_everywhere(state: &System) -> bool {
+        state
+            .node_map
+            .iter()
+            .all(|node| node.transaction.state() == TransactionState::Committed)
+    }
+
+    /// Returns `true` if every participant has learned the commit decision of the coordinator, or
+    /// the coordinator has rolled back the transaction.
+    ///
+    /// A read-only participant is excluded as it never learns the decision, and so is a node that
+    /// has not joined the transaction.
+    fn is_commit_propagated(&self, state: &System) -> bool {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        match coordinator.transaction.state() {
+            TransactionState::RolledBack => true,
+            TransactionState::Committed => state.node_map.iter().all(|node| {
+                node.read_only
+                    || matches!(
+                        node.transaction.state(),
+                        TransactionState::Inactive | TransactionState::Committed
+                    )
+            }),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` unless a participant of the committed transaction has rolled it back.
+    ///
+    /// A straggler that has not voted when the coordinator commits may still be active or
+    /// prepared, but it never rolls back the transaction.
+    fn is_straggler_consistent(&self, state: &System) -> bool {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        let mut consistent = true;
+        if coordinator.transaction.state() == TransactionState::Committed {
+            coordinator
+                .transaction
+                .for_each_participant(|participant_node_id| {
+                    if state.node_map[participant_node_id].transaction.state()
+                        == TransactionState::RolledBack
+                    {
+                        consistent = false;
+                    }
+                });
+        }
+        consistent
+    }
+
+    /// Returns `true` unless a node that has been shut down has left an unfinished transaction
+    /// behind.
+    fn is_shutdown_clean(&self, state: &System) -> bool {
+        // A node that has been shut down never leaves an unfinished transaction behind.
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        let mut in_doubt = state.node_map.iter().any(|node| {
+            node.status == NodeStatus::ShutDown
+                && matches!(
+                    node.transaction.state(),
+                    TransactionState::Active | TransactionState::Prepared
+                )
+        });
+
+        // Participants are never left waiting for a coordinator that has been shut down.
+        if coordinator.status == NodeStatus::ShutDown {
+            coordinator
+                .transaction
+                .for_each_participant(|participant_node_id| {
+                    if state.node_map[participant_node_id].transaction.state()
+                        == TransactionState::Prepared
+                    {
+                        in_doubt = true;
+                    }
+                });
+        }
+
+        !in_doubt
+    }
+
+    /// Returns `true` if the data satisfies the invariant of the workload once every in-doubt node
+    /// applies the decision.
+    fn is_data_consistent(workload: &dyn Workload, state: &System) -> bool {
+        let committed = state.node_map.iter().any(|node| {
+            node.forgotten
+                || (!node.read_only && node.transaction.state() == TransactionState::Committed)
+        });
+        let data = state
+            .node_map
+            .iter()
+            .map(|node| {
+                let mut data = node.data.clone();
+                if committed && node.transaction.state() == TransactionState::Prepared {
+                    for operation in &node.operations {
+                        operation.apply(&mut data);
+                    }
+                }
+                data
+            })
+            .collect::<Vec<_>>();
+        workload.is_consistent(&data)
+    }
+
+    /// Returns `true` if every in-doubt participant either queries a peer, or has no peer that is
+    /// running and not in doubt.
+    fn is_blocking_unavoidable(&self, state: &System) -> bool {
+        let mut actions = Vec::new();
+        self.add_termination_actions(state, &mut actions);
+        state
+            .node_map
+            .iter()
+            .enumerate()
+            .filter(|(node_id, node)| self.is_in_doubt(state, *node_id, node))
+            .all(|(node_id, node)| {
+                actions.iter().any(|action| {
+                    matches!(
+                        action,
+                        Action::QueryPeer(querier, _) | Action::PeerAnswer(_, querier)
+                            if *querier == node_id
+                    )
+                }) || node.peers.iter().all(|peer_node_id| {
+                    let peer = &state.node_map[*peer_node_id];
+                    !peer.is_running() || peer.transaction.state() == TransactionState::Prepared
+                })
+            })
+    }
+
+    fn track_latency(&self, last_state: &System, next_state: &mut System) {
+        if next_state.node_map == last_state.node_map {
+            // Retransmissions that do not change the state are not counted.
+            return;
+        }
+        next_state.latency = match last_state.latency {
+            Some(latency) if !last_state.is_all_decided(self.coordinator) => Some(latency + 1),
+            None if next_state.is_all_voted(self.coordinator) => Some(0),
+            latency => latency,
+        };
+    }
+
+    fn inquire(&self, last_state: &System, node_id: usize) -> Option<System> {
+        let parent = &last_state.node_map[self.parent_node_id(node_id)];
+        let node = last_state.node_map.get(node_id)?;
+        let next_node_state = if parent.forgotten {
+            // The coordinator does not know the transaction: presume commit with the last commit
+            // timestamp that it has generated.
+            Self::commit_distributed_transaction(node, parent.commit_timestamp.unwrap_or_default())
+        } else {
+            match parent.transaction.state() {
+                TransactionState::Committed => Self::commit_distributed_transaction(
+                    node,
+                    parent.commit_timestamp.unwrap_or_default(),
+                ),
+                TransactionState::RolledBack => Self::rollback_distributed_transaction(node),
+                _ => return None,
+            }
+        };
+        Some(Self::next_system_state(
+            last_state,
+            node_id,
+            next_node_state,
+        ))
+    }
+
+    fn check_status(&self, last_state: &System, node_id: usize) -> Option<System> {
+        let node = last_state.node_map.get(node_id)?;
+        let parent = &last_state.node_map[self.parent_node_id(node_id)];
+        if node
+            .status_reply
+            .is_some_and(|decision| decision != Decision::Pending)
+            || !parent.is_running()
+            || !parent.pending.is_empty()
+        {
+            // A parent that is down, or has not synced its decision yet, does not reply.
+            return None;
+        }
+        let decision = match parent.transaction.state() {
+            TransactionState::Committed => {
+                Decision::Commit(parent.commit_timestamp.unwrap_or_default())
+            }
+            TransactionState::Inactive if parent.forgotten => {
+                // The parent has forgotten the committed transaction: presume commit.
+                Decision::Commit(parent.commit_timestamp.unwrap_or_default())
+            }
+            TransactionState::Inactive | TransactionState::RolledBack => {
+                // A parent that does not know the transaction presumes that it was rolled back.
+                Decision::Rollback
+            }
+            TransactionState::Active
+            | TransactionState::Prepared
+            | TransactionState::PreCommitted => Decision::Pending,
+        };
+        let mut next_node_state = node.clone();
+        next_node_state.status_reply = Some(decision);
+        Some(Self::next_system_state(
+            last_state,
+            node_id,
+            next_node_state,
+        ))
+    }
+
+    fn receive_status(last_state: &System, node_id: usize, decision: Decision) -> Option<System> {
+        let node = last_state.node_map.get(node_id)?;
+        if node.status_reply != Some(decision) {
+            return None;
+        }
+
+        // The reply is validated against the log of the participant like a decision message.
+        let mut next_node_state = match decision {
+            Decision::Commit(timestamp) if node.vote != Some(false) => {
+                Self::commit_distributed_transaction(node, timestamp)
+            }
+            Decision::Commit(timestamp) => {
+                Self::flag_violation(node, Action::Commit(node_id, timestamp))
+            }
+            Decision::Rollback
+                if node.read_only || node.transaction.state() != TransactionState::Committed =>
+            {
+                Self::rollback_distributed_transaction(node)
+            }
+            Decision::Rollback => Self::flag_violation(node, Action::Rollback(node_id)),
+            Decision::Pending => {
+                // The participant keeps the pending reply, and checks again later.
+                return Some(last_state.clone());
+            }
+        };
+        next_node_state.status_reply = None;
+        Some(Self::next_system_state(
+            last_state,
+            node_id,
+            next_node_state,
+        ))
+    }
+
+    fn send(last_state: &System, message: Message) -> Option<System> {
+        let Err(index) = last_state.network.binary_search(&message) else {
+            // The message is already in flight.
+            return None;
+        };
+        let mut next_state = match message.action {
+            // The participant logs its vote when it sends the vote.
+            Action::AckPrepare(node_id) => Self::log_vote(last_state.clone(), node_id, true),
+            Action::AckPrepareFail(node_id) => Self::log_vote(last_state.clone(), node_id, false),
+            _ => last_state.clone(),
+        };
+        next_state.network.insert(index, message);
+        Some(next_state)
+    }
+
+    fn deliver(&self, last_state: &System, message: &Message) -> Option<System> {
+        let index = last_state.network.binary_search(message).ok()?;
+        let mut state = last_state.clone();
+        state.network.remove(index);
+        let link = (message.sender, self.destination(&message.action));
+        if state.failed_links.binary_search(&link).is_ok() {
+            // A message sent over a failed link is lost.
+            return Some(state);
+        }
+        if self.max_duplicates > 0 {
+            if let Err(index) = state.delivered.binary_search(message) {
+                state.delivered.insert(index, message.clone());
+            }
+        }
+
+        // A message that the destination cannot process is lost.
+        Some(
+            self.next_state(&state, message.action.clone())
+                .unwrap_or(state),
+        )
+    }
+
+    fn fail_link(last_state: &System, link: (usize, usize)) -> Option<System> {
+        let Err(index) = last_state.failed_links.binary_search(&link) else {
+            return None;
+        };
+        let mut state = last_state.clone();
+        state.failed_links.insert(index, link);
+        Some(state)
+    }
+
+    fn duplicate(last_state: &System, message: Message) -> Option<System> {
+        last_state.delivered.binary_search(&message).ok()?;
+        let Err(index) = last_state.network.binary_search(&message) else {
+            // The message is already in flight.
+            return None;
+        };
+        let mut state = last_state.clone();
+        state.network.insert(index, message);
+        state.duplicates += 1;
+        Some(state)
+    }
+
+    fn forget(node: &Node) -> Option<Node> {
+        if node.transaction.state() != TransactionState::Committed {
+            // A rolled back transaction is never forgotten.
+            return None;
+        }
+        let mut new_node_state = node.clone();
+        new_node_state.transaction = Transaction::default();
+        new_node_state.forgotten = true;
+        new_node_state.persistency.clear();
+        new_node_state.pending.clear();
+        new_node_state.checkpoint = None;
+        new_node_state.truncated.clear();
+        Some(new_node_state)
+    }
+
+    fn checkpoint(node: &Node) -> Option<Node> {
+        if node.persistency.is_empty() {
+            return None;
+        }
+        let mut new_node_state = node.clone();
+
+        // The snapshot is the transaction recovered from the log, so that it holds only the
+        // durable state changes.
+        new_node_state.checkpoint = Some(Self::recover_transaction(node));
+        new_node_state
+            .truncated
+            .append(&mut new_node_state.persistency);
+        Some(new_node_state)
+    }
+
+    /// Returns the behavior of the participant node.
+    fn behavior_of(&self, node_id: usize) -> &dyn ParticipantBehavior {
+        self.behaviors
+            .iter()
+            .find(|(behaving_node_id, _)| *behaving_node_id == node_id)
+            .map_or(&Cooperative, |(_, behavior)| behavior.as_ref())
+    }
+
+    /// Returns `true` if the fault restricted to the nodes, if any, may affect the node.
+    fn is_affected(nodes: Option<&Vec<usize>>, node_id: usize) -> bool {
+        nodes.is_none_or(|nodes| nodes.contains(&node_id))
+    }
+
+    /// Returns `true` unless the nodes have crashed as many times as the crash budget allows.
+    fn is_crash_allowed(&self, state: &System) -> bool {
+        self.max_crashes
+            .is_none_or(|max_crashes| state.crashes < max_crashes)
+    }
+
+    fn crash(&self, last_state: &System, node_id: usize) -> Option<System> {
+        if !self.is_crash_allowed(last_state) {
+            return None;
+        }
+        let node = last_state.node_map.get(node_id)?;
+        let failure_model = if self.behavior_of(node_id).recovers_after_crash() {
+            self.failure_model
+        } else {
+            FailureModel::FailStop
+        };
+        let next_node_state = match failure_model {
+            FailureModel::CrashRecovery => {
+                let mut next_node_state = Self::crash_restart(node);
+                if !self.is_coordinator(node_id)
+                    && !node.read_only
+                    && matches!(
+                        node.transaction.state(),
+                        TransactionState::Active | TransactionState::Prepared
+                    )
+                    && next_node_state.transaction.state() == TransactionState::Active
+                {
+                    // The participant has lost its prepare record, if it had written one.
+                    next_node_state.crashed_unprepared = true;
+                }
+                next_node_state
+            }
+            FailureModel::FailStop => {
+                let mut next_node_state = node.clone();
+                next_node_state.status = NodeStatus::Crashed;
+                next_node_state
+            }
+        };
+        let mut state = Self::next_system_state(last_state, node_id, next_node_state);
+        if self.max_crashes.is_some() {
+            state.crashes += 1;
+        }
+        Some(state)
+    }
+
+    fn amnesia(&self, last_state: &System, node_id: usize) -> Option<System> {
+        let node = last_state.node_map.get(node_id)?;
+        if !self.is_crash_allowed(last_state) || !self.is_amnesia_possible(node_id, node) {
+            return None;
+        }
+        let mut new_node_state = node.clone();
+
+        // The log is lost along with the in-memory state; the restarted coordinator presumes that
+        // the unknown transaction was rolled back, and logs the rollback so that it never starts
+        // the transaction again.
+        new_node_state.pending.clear();
+        new_node_state.persistency.clear();
+        new_node_state.truncated.clear();
+        new_node_state.checkpoint = None;
+        new_node_state.commit_timestamp = None;
+        new_node_state.transaction = node.new_transaction();
+        new_node_state.transaction.rollback();
+        new_node_state.log(Action::Rollback(node_id));
+        new_node_state.amnesiac = true;
+
+        let mut state = Self::next_system_state(last_state, node_id, new_node_state);
+        if self.max_crashes.is_some() {
+            state.crashes += 1;
+        }
+        Some(state)
+    }
+
+    fn crash_restart(node: &Node) -> Node {
+        let mut new_node_state = node.clone();
+
+        // The in-memory transaction state and the log records that are not synced are lost, and
+        // the transaction is rebuilt from the log.
+        new_node_state.pending.clear();
+        new_node_state.transaction = Self::recover_transaction(node);
+        if node.transaction.state() == TransactionState::Committed
+            && new_node_state.transaction.state() != TransactionState::Committed
+            && !node.read_only
+            && !node.forgotten
+            && !node
+                .pending
+                .iter()
+                .any(|record| matches!(record, Action::Commit(..)))
+        {
+            new_node_state.lost_commit = true;
+        }
+        if Self::last_decision(node)
+            .is_some_and(|decision| decision != new_node_state.transaction.state())
+        {
+            new_node_state.misrecovered = true;
+        }
+        if new_node_state.transaction.state().is_terminal() {
+            new_node_state.recovered = true;
+        }
+
+        new_node_state
+    }
+
+    /// Returns the decision of the last decision record in the durable log of the node, including
+    /// the records truncated by a checkpoint.
+    fn last_decision(node: &Node) -> Option<TransactionState> {
+        node.truncated
+            .iter()
+            .chain(&node.persistency)
+            .rev()
+            .find_map(|record| match record {
+                Action::Commit(..) => Some(TransactionState::Committed),
+                Action::Rollback(_) => Some(TransactionState::RolledBack),
+                _ => None,
+            })
+    }
+
+    /// Rebuilds the transaction of the node by replaying its log on top of the last checkpoint.
+    ///
+    /// Every state change of the transaction is logged in the same transition that makes it, so
+    /// that it is logged before the node acts on it; the coordinator learns the votes of the
+    /// participants again as they are not logged.
+    fn recover_transaction(node: &Node) -> Transaction {
+        let checkpoint = node
+            .checkpoint
+            .clone()
+            .unwrap_or_else(|| node.new_transaction());
+        Self::replay(checkpoint, &node.persistency)
+    }
+
+    /// Applies the log records to the transaction.
+    fn replay(mut transaction: Transaction, records: &[Action]) -> Transaction {
+        for record in records {
+            match record {
+                Action::Start(_) => {
+                    transaction.start();
+                }
+                Action::RequestJoin(participant_node_id) => {
+                    // The coordinator starts the transaction when the first participant joins.
+                    transaction.start();
+                    transaction.add_participant(*participant_node_id);
+                }
+                Action::RequestPrepare(_) => {
+                    transaction.prepare();
+                }
+                Action::Commit(..) => {
+                    // A one-phase commit is logged without a prepare record.
+                    transaction.prepare();
+                    transaction.commit();
+                }
+                Action::Rollback(_) => {
+                    transaction.rollback();
+                }
+                // The vote and the commit timestamp are kept in the node, and the other records do
+                // not change the transaction state.
+                _ => (),
+            }
+        }
+        transaction
+    }
+
+    /// Returns `true` if the coordinator that has decided can send the decision to every
+    /// participant that is prepared, even after it has recovered the decision from its log.
+    fn is_decision_resendable(&self, state: &System) -> bool {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        let decision = coordinator.transaction.state();
+        if !coordinator.is_running()
+            || coordinator.forgotten
+            || coordinator.amnesiac
+            || !decision.is_terminal()
+        {
+            return true;
+        }
+        (decision == TransactionState::RolledBack || coordinator.commit_timestamp.is_some())
+            && state.node_map.iter().all(|node| {
+                self.parent_node_id(node.id) != self.coordinator_node_id()
+                    || self.is_coordinator(node.id)
+                    || node.transaction.state() != TransactionState::Prepared
+                    || coordinator.transaction.has_participant(node.id)
+            })
+    }
+
+    /// Returns `true` if the coordinator that has lost its log has rolled back the transaction,
+    /// and no participant of the coordinator has committed it, so that the prepared participants
+    /// terminate by checking the status with the coordinator.
+    fn is_amnesia_safe(&self, state: &System) -> bool {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        !coordinator.amnesiac
+            || (coordinator.transaction.state() == TransactionState::RolledBack
+                && state.node_map.iter().all(|node| {
+                    self.parent_node_id(node.id) != self.coordinator_node_id()
+                        || node.heuristic.is_some()
+                        || node.transaction.state() != TransactionState::Committed
+                }))
+    }
+
+    /// Returns `true` if the coordinator has crashed after committing the transaction, and
+    /// recovered the decision before sending it to any participant.
+    fn is_in_doubt_window(&self, state: &System) -> bool {
+        let coordinator = &state.node_map[self.coordinator_node_id()];
+        coordinator.recovered
+            && coordinator.transaction.state() == TransactionState::Committed
+            && state
+                .node_map
+                .iter()
+                .any(|node| !self.is_coordinator(node.id))
+            && state.node_map.iter().all(|node| {
+                self.is_coordinator(node.id)
+                    || node.transaction.state() != TransactionState::Committed
+            })
+    }
+
+    /// Returns `true` if every participant that its parent counts as prepared has synced the
+    /// prepare record.
+    ///
+    /// Replicated, replaced and read-only participants are excluded as they restore the
+    /// transaction from elsewhere.
+    fn is_vote_synced(&self, state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            self.is_coordinator(node.id)
+                || !node.replicas.is_empty()
+                || node.incarnation > 0
+                || node.read_only
+                || !state.node_map[self.parent_node_id(node.id)]
+                    .transaction
+                    .is_prepared(node.id)
+                || node
+                    .truncated
+                    .iter()
+                    .chain(&node.persistency)
+                    .any(|record| *record == Action::RequestPrepare(node.id))
+        })
+    }
+
+    /// Returns `true` if every node recovers the same transaction from its checkpoint and the
+    /// rest of the log as from the full log.
+    fn is_checkpoint_consistent(state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            let full_log = [node.truncated.as_slice(), node.persistency.as_slice()].concat();
+            Self::recover_transaction(node) == Self::replay(node.new_transaction(), &full_log)
+        })
+    }
+
+    /// Returns `true` unless a participant that has crashed before its prepare record was durable
+    /// has committed the transaction.
+    fn is_unprepared_crash_aborted(state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            !node.crashed_unprepared || node.transaction.state() != TransactionState::Committed
+        })
+    }
+
+    /// Returns `true` unless a node has recovered the transaction in a state that its durable log
+    /// does not imply.
+    fn is_recovery_correct(state: &System) -> bool {
+        state.node_map.iter().all(|node| !node.misrecovered)
+    }
+
+    /// Returns `true` unless a node that had committed the transaction before a crash has
+    /// recovered it in another state.
+    fn is_commit_durable(state: &System) -> bool {
+        state.node_map.iter().all(|node| !node.lost_commit)
+    }
+
+    /// Returns `true` unless a node that has logged the prepare record has lost the prepared
+    /// transaction.
+    ///
+    /// Replicated participants are excluded as they fail over to the state of their replicas.
+    fn is_prepare_durable(state: &System) -> bool {
+        state.node_map.iter().all(|node| {
+            !node.replicas.is_empty()
+                || !node
+                    .truncated
+                    .iter()
+                    .chain(&node.persistency)
+                    .any(|record| *record == Action::RequestPrepare(node.id))
+                || !matches!(
+                    node.transaction.state(),
+                    TransactionState::Inactive | TransactionState::Active
+                )
+        })
+    }
+
+    /// Applies the action to the state, or returns `None` if the action is not enabled.
+    #[allow(clippy::too_many_lines)]
+    fn apply(&self, last_state: &System, action: Action) -> Option<System> {
+        let fault = Self::is_fault(&action);
+        let status_query = matches!(action, Action::CheckStatus(_) | Action::Inquire(_));
+        match action {
+            Action::Send(message) => return Self::send(last_state, *message),
+            Action::Deliver(message) => return self.deliver(last_state, &message),
+            Action::Duplicate(message) => {
+                return Self::duplicate(last_state, *message).map(System::with_fault);
+            }
+            Action::FailLink(sender, destination) => {
+                return Self::fail_link(last_state, (sender, destination)).map(System::with_fault);
+            }
+            // A forged message is processed like the genuine one.
+            Action::Forge(forged) => {
+                return self.apply(last_state, *forged).map(System::with_fault);
+            }
+            _ => (),
+        }
+        if !matches!(
+            action,
+            Action::Replace(_)
+                | Action::Tick
+                | Action::Takeover
+                | Action::StandbyCommit
+                | Action::StandbyRollback
+                | Action::FailStandby
+        ) && !last_state
+            .node_map
+            .get(self.destination(&action))
+            .is_some_and(Node::is_running)
+        {
+            // Messages sent to a node that has been shut down or failed are lost.
+            return None;
+        }
+
+        let mut next_state =
+            match action {
+                Action::Start(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(Self::start_transaction)
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::RequestJoin(participant_node_id) => last_state
+                    .node_map
+                    .get(self.parent_node_id(participant_node_id))
+                    .map(|node| self.add_participant(node, participant_node_id))
+                    .map(|next_node_state| {
+                        Self::next_system_state(
+                            last_state,
+                            self.parent_node_id(participant_node_id),
+                            next_node_state,
+                        )
+                    }),
+                Action::AckJoin(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .map(Self::start_distributed_transaction)
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+
+                Action::RequestPrepare(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    // A participant may leave the prepare request unanswered for now.
+                    .filter(|node| {
+                        self.is_coordinator(node_id)
+                            || node.transaction.state() != TransactionState::Active
+                            || self
+                                .behavior_of(node_id)
+                                .responds_to_prepare(self.is_timed_out(last_state, node))
+                    })
+                    .map(Self::prepare_distributed_transaction)
+                    .map(|mut next_node_state| {
+                        if self.cooperative_termination && !self.is_coordinator(node_id) {
+                            // The prepare request carries the identifiers of the peers.
+                            next_node_state.peers.clear();
+                            last_state.node_map[self.parent_node_id(node_id)]
+                                .transaction
+                                .for_each_participant(|peer_node_id| {
+                                    if peer_node_id != node_id {
+                                        next_node_state.peers.push(peer_node_id);
+                                    }
+                                });
+                            next_node_state.peers.sort_unstable();
+                        }
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::AckPrepare(participant_node_id) => last_state
+                    .node_map
+                    .get(self.parent_node_id(participant_node_id))
+                    .map(|node| Self::mark_prepared(node, participant_node_id))
+                    .map(|next_node_state| {
+                        Self::next_system_state(
+                            last_state,
+                            self.parent_node_id(participant_node_id),
+                            next_node_state,
+                        )
+                    })
+                    .map(|next_state| Self::log_vote(next_state, participant_node_id, true)),
+                Action::AckPrepareFail(participant_node_id) => last_state
+                    .node_map
+                    .get(self.parent_node_id(participant_node_id))
+                    // A vote for a forgotten, delegated or decided transaction is ignored, and an
+                    // intermediate node that has counted the yes-vote of the participant may have
+                    // voted for commit.
+                    .filter(|node| {
+                        !node.forgotten
+                            && !node.delegated
+                            && !node.transaction.state().is_terminal()
+                            && (self.is_coordinator(node.id)
+                                || !node.transaction.is_prepared(participant_node_id))
+                    })
+                    .map(Self::rollback_distributed_transaction)
+                    .map(|next_node_state| {
+                        Self::next_system_state(
+                            last_state,
+                            self.parent_node_id(participant_node_id),
+                            next_node_state,
+                        )
+                    })
+                    .map(|next_state| Self::log_vote(next_state, participant_node_id, false)),
+                Action::AckReadOnly(participant_node_id) => last_state
+                    .node_map
+                    .get(self.parent_node_id(participant_node_id))
+                    .and_then(|node| Self::remove_read_only(node, participant_node_id))
+                    .map(|next_node_state| {
+                        Self::next_system_state(
+                            last_state,
+                            self.parent_node_id(participant_node_id),
+                            next_node_state,
+                        )
+                    }),
+                Action::Delegate(participant_node_id) => last_state
+                    .node_map
+                    .get(self.parent_node_id(participant_node_id))
+                    .and_then(Self::delegate)
+                    .map(|next_node_state| {
+                        Self::next_system_state(
+                            last_state,
+                            self.parent_node_id(participant_node_id),
+                            next_node_state,
+                        )
+                    }),
+                Action::OnePhaseCommit(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .map(|node| {
+                        Self::commit_one_phase(node, Self::commit_timestamp_of(last_state, node_id))
+                    })
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::AckDecision(node_id) => self.learn_decision(last_state, node_id),
+                Action::Commit(node_id, timestamp) => last_state
+                    .node_map
+                    .get(node_id)
+                    .map(|node| {
+                        if self.is_coordinator(node_id) || node.vote != Some(false) {
+                            Self::commit_distributed_transaction(node, timestamp)
+                        } else {
+                            // The participant has voted against commit.
+                            Self::flag_violation(node, Action::Commit(node_id, timestamp))
+                        }
+                    })
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Rollback(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .map(|node| {
+                        if self.is_coordinator(node_id)
+                            || node.read_only
+                            || node.transaction.state() != TransactionState::Committed
+                        {
+                            Self::rollback_distributed_transaction(node)
+                        } else {
+                            // The participant has committed the transaction.
+                            Self::flag_violation(node, Action::Rollback(node_id))
+                        }
+                    })
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Crash(node_id) => self.crash(last_state, node_id),
+                Action::Amnesia(node_id) => self.amnesia(last_state, node_id),
+                Action::Fsync(node_id) => last_state.node_map.get(node_id).and_then(|node| {
+                    let mut next_node_state = node.clone();
+                    (!node.pending.is_empty()).then(|| {
+                        next_node_state.sync();
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    })
+                }),
+                Action::Checkpoint(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(Self::checkpoint)
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::LogWriteFail(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .filter(|node| !node.disk_full)
+                    .map(|node| {
+                        let mut next_node_state = node.clone();
+                        next_node_state.disk_full = true;
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Shutdown(node_id) => self.shutdown(last_state, node_id),
+                Action::Fail(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .map(Self::fail_permanently)
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Replace(node_id) => self.replace(last_state, node_id),
+                Action::Inquire(node_id) => self.inquire(last_state, node_id),
+                Action::CheckStatus(node_id) => self.check_status(last_state, node_id),
+                Action::StatusReply(node_id, decision) => {
+                    Self::receive_status(last_state, node_id, decision)
+                }
+                Action::QueryPeer(_, peer_node_id) => Self::query_peer(last_state, peer_node_id),
+                Action::PeerAnswer(peer_node_id, node_id) => {
+                    Self::answer_peer(last_state, peer_node_id, node_id)
+                }
+                Action::Elect(node_id) => (!last_state.node_map[self.coordinator_node_id()]
+                    .is_running())
+                .then(|| System {
+                    acting_coordinator: Some(node_id),
+                    ..last_state.clone()
+                }),
+                Action::HeuristicCommit(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(|node| {
+                        Self::decide_heuristically(
+                            node,
+                            true,
+                            Self::commit_timestamp_of(last_state, node_id),
+                        )
+                    })
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::HeuristicRollback(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(|node| Self::decide_heuristically(node, false, 0))
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Forget(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(Self::forget)
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Retry(node_id) => last_state.node_map.get(node_id).map(|node| {
+                    // The coordinator backs off, and requests the vote again later.
+                    let mut next_node_state = node.clone();
+                    next_node_state.retries += 1;
+                    Self::next_system_state(last_state, node_id, next_node_state)
+                }),
+                Action::GiveUp(node_id) => {
+                    let coordinator = &last_state.node_map[self.parent_node_id(node_id)];
+                    let next_node_state = Self::rollback_distributed_transaction(coordinator);
+                    Some(Self::next_system_state(
+                        last_state,
+                        self.parent_node_id(node_id),
+                        next_node_state,
+                    ))
+                }
+                Action::Timeout(node_id) => last_state
+                    .node_map
+                    .get(node_id)
+                    .filter(|node| !node.timer_expired)
+                    .map(|node| {
+                        let mut next_node_state = node.clone();
+                        next_node_state.timer_expired = true;
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Tick => Some(System {
+                    clock: last_state.clock + 1,
+                    ..last_state.clone()
+                }),
+                Action::Replicate(node_id, replica) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(|node| Self::replicate(node, replica))
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::LoseReplica(node_id, replica) => last_state
+                    .node_map
+                    .get(node_id)
+                    .and_then(|node| Self::lose_replica(node, replica))
+                    .map(|next_node_state| {
+                        Self::next_system_state(last_state, node_id, next_node_state)
+                    }),
+                Action::Takeover => self.take_over(last_state),
+                Action::StandbyCommit => Self::decide_standby(last_state, true),
+                Action::StandbyRollback => Self::decide_standby(last_state, false),
+                Action::FailStandby => Self::fail_standby(last_state),
+                // The messages in the network, the link failures and the forged messages have been handled
+                // above.
+                Action::Send(_)
+                | Action::Deliver(_)
+                | Action::Duplicate(_)
+                | Action::FailLink(..)
+                | Action::Forge(_) => None,
+            }?;
+
+        if !self.torn_writes {
+            // Log records are synced as soon as they are written.
+            next_state.node_map.iter_mut().for_each(Node::sync);
+        }
+
+        if self.backup_coordinator {
+            self.replicate_to_standby(&mut next_state);
+        }
+
+        if self.decision_latency && !status_query {
+            self.track_latency(last_state, &mut next_state);
+        }
+
+        self.record_commit_decision(last_state, &mut next_state);
+        Self::record_decision_flip(last_state, &mut next_state);
+        if fault {
+            next_state = next_state.with_fault();
+        }
+
+        if self.fairness && next_state == *last_state {
+            // A stuttering step cannot make progress.
+            return None;
+        }
+
+        if self.max_depth.is_some() {
+            next_state.depth = last_state.depth + 1;
+        }
+
+        Some(next_state)
+    }
+}
+
+impl Model for TransactionModel {
+    type State = System;
+    type Action = Action;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let mut node_map: Vec<Node> = Vec::with_capacity(self.parents.len());
+        for id in 0..self.parents.len() {
+            let mut node = if self.read_only.contains(&id) {
+                Node::read_only(id)
+            } else {
+                Node::with_id(id)
+            };
+            if let Some(workload) = &self.workload {
+                // A read-only participant performs only the reads of the workload.
+                node.data = workload.initial_data(id, self.parents.len());
+                node.operations = workload
+                    .operations(id, self.parents.len())
+                    .into_iter()
+                    .filter(|operation| !node.read_only || *operation == Operation::Read)
+                    .collect();
+            }
+            if self.replicas > 1 && !self.is_coordinator(id) {
+                node.replicas = vec![Some(TransactionState::Inactive); self.replicas];
+            }
+            node_map.push(node);
+        }
+        vec![System {
+            latency: None,
+            clock: 0,
+            acting_coordinator: None,
+            standby: self.backup_coordinator.then(|| Standby {
+                transaction: node_map[self.coordinator_node_id()].transaction.clone(),
+                commit_timestamp: None,
+                status: NodeStatus::Running,
+                active: false,
+            }),
+            node_map,
+            network: Vec::new(),
+            delivered: Vec::new(),
+            duplicates: 0,
+            failed_links: Vec::new(),
+            crashes: 0,
+            unvoted_commit: false,
+            faulty: false,
+            decision_flipped: false,
+            depth: 0,
+        }]
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        state
+            .node_map
+            .iter()
+            .enumerate()
+            .for_each(|(node_id, node)| {
+                if self.asynchronous_network {
+                    self.add_send_actions(state, node_id, node, actions);
+                } else {
+                    self.add_actions_per_node(state, node_id, node, actions);
+                }
+            });
+        actions.extend(
+            state
+                .network
+                .iter()
+                .map(|message| Action::Deliver(Box::new(message.clone()))),
+        );
+        if self.asynchronous_network && state.failed_links.len() < self.max_link_failures {
+            self.add_link_failure_actions(state, actions);
+        }
+        if state.duplicates < self.max_duplicates {
+            actions.extend(
+                state
+                    .delivered
+                    .iter()
+                    .filter(|message| state.network.binary_search(message).is_err())
+                    .map(|message| Action::Duplicate(Box::new(message.clone()))),
+            );
+        }
+        if self.graceful_shutdown {
+            self.add_shutdown_actions(state, actions);
+        }
+        if self.replacement {
+            self.add_replacement_actions(state, actions);
+        }
+        if self.cooperative_termination {
+            self.add_termination_actions(state, actions);
+        }
+        if self.coordinator_election {
+            self.add_election_actions(state, actions);
+        }
+        if self.heuristics {
+            self.add_heuristic_actions(state, actions);
+        }
+        if self.backup_coordinator {
+            self.add_standby_actions(state, actions);
+        }
+        if self.replicas > 1 {
+            Self::add_replication_actions(state, actions);
+        }
+        if self.timeout_ticks.is_some() && state.clock < self.max_ticks {
+            actions.push(Action::Tick);
+        }
+        if let Some(byzantine) = self.byzantine {
+            self.add_byzantine_actions(state, byzantine, actions);
+        }
+        if let Some(dead) = self.dead_participant {
+            self.add_dead_participant_actions(state, dead, actions);
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        if !log_enabled!(Level::Trace) {
+            return self.apply(last_state, action);
+        }
+        let description = format!("{action:?}");
+        let next_state = self.apply(last_state, action);
+        match &next_state {
+            Some(next_state) => trace!(
+                "{:016x} --{description}--> {:016x}",
+                fingerprint(last_state),
+                fingerprint(next_state)
+            ),
+            None => trace!(
+                "{:016x} --{description}--> disabled",
+                fingerprint(last_state)
+            ),
+        }
+        next_state
+    }
+
+    fn within_boundary(&self, state: &Self::State) -> bool {
+        self.max_depth
+            .is_none_or(|max_depth| state.depth <= max_depth)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn properties(&self) -> Vec<Property<Self>> {
+        let mut properties = vec![
+            // The atomic commitment properties AC1 to AC4; termination, AC5, is checked without
+            // crashes.
+            Property::<Self>::always("agreement", |_, state| Self::is_agreement(state)),
+            Property::<Self>::always("commit validity", Self::is_commit_valid),
+            Property::<Self>::always("abort validity", Self::is_abort_valid),
+            // A fault may force a rollback, but the transaction has to be able to commit in an
+            // execution without faults.
+            Property::<Self>::sometimes("non-triviality", |_, state| {
+                !state.faulty && Self::is_committed_everywhere(state)
+            }),
+            // Either decision has to be reachable, so that the always-properties do not hold
+            // vacuously.
+            Property::<Self>::sometimes("global rollback", |_, state| {
+                Self::is_rolled_back_everywhere(state)
+            }),
+            // A decision is never reversed, even if the nodes agree on the reversed decision.
+            Property::<Self>::always("decision stability", |_, state| !state.decision_flipped),
+            Property::<Self>::always("no protocol violation", |_, state| {
+                state.is_violation_free()
+            }),
+            Property::<Self>::always("same commit timestamp", |_, state| {
+                state.is_commit_timestamp_agreed()
+            }),
+            Property::<Self>::always("prepared survives crash", |_, state| {
+                Self::is_prepare_durable(state)
+            }),
+            Property::<Self>::always("committed survives crash", |_, state| {
+                Self::is_commit_durable(state)
+            }),
+            Property::<Self>::always("recovery correctness", |_, state| {
+                Self::is_recovery_correct(state)
+            }),
+            Property::<Self>::always(
+                "decision resent after recovery",
+                Self::is_decision_resendable,
+            ),
+        ];
+
+        if self.quorum.is_none() {
+            // A straggler of the quorum variant catches up with the commit even if it has lost
+            // its prepare record in a crash.
+            properties.push(Property::<Self>::always(
+                "unprepared crash aborts",
+                |_, state| Self::is_unprepared_crash_aborted(state),
+            ));
+        }
+
+        if !self.decision_latency
+            && self.max_crashes != Some(0)
+            && self.failure_model == FailureModel::CrashRecovery
+        {
+            properties.push(Property::<Self>::sometimes(
+                "crash before fan-out",
+                Self::is_in_doubt_window,
+            ));
+        }
+
+        if self.max_crashes == Some(0) {
+            // Without crashes, the protocol must not deadlock before every node has decided.
+            properties.push(Property::<Self>::eventually("termination", |_, state| {
+                Self::is_terminated(state)
+            }));
+        }
+
+        if self.max_crashes.is_some() {
+            // Once the coordinator commits, every participant must eventually commit rather than
+            // stay prepared; the property is only checked when the execution ends, and so needs
+            // a bounded number of crashes.
+            properties.push(Property::<Self>::eventually(
+                "commit reaches participants",
+                Self::is_commit_propagated,
+            ));
+        }
+
+        if self.checkpointing {
+            properties.push(Property::<Self>::always(
+                "recovery from checkpoint",
+                |_, state| Self::is_checkpoint_consistent(state),
+            ));
+        }
+
+        if self.max_link_failures > 0 {
+            properties.push(Property::<Self>::sometimes(
+                "one-way link failure",
+                Self::is_ack_lost,
+            ));
+        }
+
+        if self.dead_participant.is_some() {
+            // Expected to be discovered: the two-phase commit protocol blocks when the coordinator
+            // and a participant die at the wrong time.
+            properties.push(Property::<Self>::sometimes(
+                "blocking after failures",
+                Self::is_blocked,
+            ));
+        }
+
+        if self.byzantine.is_some() {
+            // Expected to fail: the two-phase commit protocol does not tolerate Byzantine
+            // participants.
+            properties.push(Property::<Self>::always(
+                "honest agreement",
+                Self::is_honest_agreement,
+            ));
+        }
+
+        if self.coordinator_amnesia {
+            properties.push(Property::<Self>::always(
+                "termination after amnesia",
+                Self::is_amnesia_safe,
+            ));
+        }
+
+        if self.log_write_failures {
+            properties.push(Property::<Self>::always(
+                "no ack without prepare record",
+                Self::is_vote_synced,
+            ));
+        }
+
+        if self.torn_writes {
+            properties.push(Property::<Self>::always(
+                "vote synced before sent",
+                Self::is_vote_synced,
+            ));
+        }
+
+        if self.graceful_shutdown {
+            properties.push(Property::<Self>::always(
+                "no in-doubt shutdown",
+                Self::is_shutdown_clean,
+            ));
+        }
+
+        if self.decision_latency {
+            properties.push(Property::<Self>::always(
+                "decision latency bound",
+                |model, state| {
+                    // All the participants learn the decision within the bounded number of
+                    // transitions after the last yes-vote.
+                    state
+                        .latency
+                        .is_none_or(|latency| latency <= model.decision_latency_bound())
+                },
+            ));
+        }
+
+        if self.workload.is_some() {
+            properties.push(Property::<Self>::always(
+                "data consistency",
+                |model, state| {
+                    // The data of the nodes satisfies the invariant of the workload.
+                    model
+                        .workload
+                        .as_deref()
+                        .is_none_or(|workload| Self::is_data_consistent(workload, state))
+                },
+            ));
+        }
+
+        if self.coordinator_election {
+            properties.push(Property::<Self>::always(
+                "progress after coordinator failure",
+                |model, state| {
+                    // Under weak fairness, the transaction is eventually decided after the
+                    // coordinator fails unless all the running participants are in doubt.
+                    model.is_recovery_enabled(state)
+                },
+            ));
+        }
+
+        if self.replicas > 1 {
+            properties.push(Property::<Self>::always(
+                "vote survives replica loss",
+                |_, state| Self::is_vote_durable(state),
+            ));
+        }
+
+        if self.quorum.is_some() {
+            properties.push(Property::<Self>::always(
+                "stragglers catch up",
+                Self::is_straggler_consistent,
+            ));
+        }
+
+        if self.backup_coordinator {
+            // Under weak fairness, no participant stays prepared forever unless both replicas of
+            // the coordinator have failed.
+            properties.push(Property::<Self>::always(
+                "progress while a coordinator replica is alive",
+                Self::is_takeover_enabled,
+            ));
+        }
+
+        if self.heuristics {
+            // Expected to fail: participants deciding heuristically may contradict each other or
+            // the coordinator.
+            properties.push(Property::<Self>::always(
+                "no heuristic-mixed outcome",
+                |_, state| !Self::is_heuristic_mixed(state),
+            ));
+        }
+
+        if self.cooperative_termination {
+            properties.push(Property::<Self>::always(
+                "blocking only when peers are down",
+                |model, state| {
+                    // An in-doubt participant queries a peer unless every peer is down or in
+                    // doubt as well.
+                    model.is_blocking_unavoidable(state)
+                },
+            ));
+        }
+
+        if self.retry_budget.is_some() {
+            properties.push(Property::<Self>::always(
+                "no abort within retry budget",
+                |model, state| {
+                    // Without a fault, the coordinator that has been asked to retry never rolls back
+                    // unless a participant has voted against commit or exhausted the retry budget.
+                    state.faulty
+                        || state.node_map[model.coordinator_node_id()]
+                            .transaction
+                            .state()
+                            != TransactionState::RolledBack
+                        || state.node_map.iter().any(|node| node.vote == Some(false))
+                        || !model.is_within_retry_budget(state)
+                },
+            ));
+        }
+
+        if self.replacement {
+            properties.push(Property::<Self>::always(
+                "no double apply after replacement",
+                |_, state| {
+                    // A replacement never applies a decision that its predecessor already applied.
+                    state.node_map.iter().all(|node| node.applied.len() <= 1)
+                },
+            ));
+        }
+
+        if !self.invariants.is_empty() {
+            properties.push(Property::<Self>::always(
+                "custom invariants",
+                |model, state| {
+                    let states = state
+                        .node_map
+                        .iter()
+                        .map(|node| node.transaction.state())
+                        .collect::<Vec<_>>();
+                    model
+                        .invariants
+                        .iter()
+                        .all(|invariant| invariant.holds(&states))
+                },
+            ));
+        }
+
+        if self.max_depth.is_some() {
+            // An execution cut at the depth has not ended.
+            properties.retain(|property| !matches!(property.expectation, Expectation::Eventually));
+        }
+
+        if self
+            .selected_properties
+            .as_ref()
+            .is_some_and(|selected| selected.iter().any(|name| name == "no blocking"))
+        {
+            // Expected to fail whenever the coordinator may stay down: the two-phase commit
+            // protocol blocks the prepared participants, so it is only checked when selected.
+            properties.push(Property::<Self>::always("no blocking", |model, state| {
+                !model.is_blocking(state)
+            }));
+        }
+
+        if let Some(selected) = &self.selected_properties {
+            properties.retain(|property| selected.iter().any(|name| name == property.name));
+        }
+        properties
+    }
+}
+
+impl Coverage for TransactionModel {
+    fn action_variants(&self) -> &'static [&'static str] {
+        &[
+            "Start",
+            "RequestJoin",
+            "AckJoin",
+            "RequestPrepare",
+            "AckPrepare",
+            "AckPrepareFail",
+            "AckReadOnly",
+            "Delegate",
+            "OnePhaseCommit",
+            "AckDecision",
+            "QueryPeer",
+            "PeerAnswer",
+            "Elect",
+            "HeuristicCommit",
+            "HeuristicRollback",
+            "Commit",
+            "Rollback",
+            "Crash",
+            "Amnesia",
+            "Checkpoint",
+            "LogWriteFail",
+            "Fsync",
+            "Shutdown",
+            "Fail",
+            "Replace",
+            "Inquire",
+            "CheckStatus",
+            "StatusReply",
+            "Forget",
+            "Tick",
+            "Timeout",
+            "Replicate",
+            "LoseReplica",
+            "Takeover",
+            "StandbyCommit",
+            "StandbyRollback",
+            "FailStandby",
+            "Retry",
+            "GiveUp",
+            "Send",
+            "Deliver",
+            "Duplicate",
+            "FailLink",
+            "Forge",
+        ]
+    }
+
+    fn node_states(&self, state: &Self::State) -> Vec<TransactionState> {
+        state
+            .node_map
+            .iter()
+            .map(|node| node.transaction.state())
+            .collect()
+    }
+}
+
+impl MessageCount for TransactionModel {
+    fn messages(&self, state: &Self::State, action: &Self::Action) -> Option<usize> {
+        // Messages joining the transaction belong to its execution rather than to the commit
+        // protocol, decisions of the coordinator are local, and a message in the asynchronous
+        // network is counted when it is sent.
+        match action {
+            Action::Start(_)
+            | Action::RequestJoin(_)
+            | Action::AckJoin(_)
+            | Action::Tick
+            | Action::Timeout(_)
+            | Action::GiveUp(_)
+            | Action::OnePhaseCommit(_)
+            | Action::Deliver(_) => Some(0),
+            Action::RequestPrepare(node_id)
+            | Action::Commit(node_id, _)
+            | Action::Rollback(node_id)
+                if self.is_coordinator(*node_id) =>
+            {
+                Some(0)
+            }
+            Action::Rollback(node_id) => {
+                // A participant may roll back the active transaction by itself.
+                let parent = &state.node_map[self.parent_node_id(*node_id)];
+                Some(usize::from(
+                    parent.transaction.state() == TransactionState::RolledBack,
+                ))
+            }
+            Action::RequestPrepare(_)
+            | Action::AckPrepare(_)
+            | Action::AckPrepareFail(_)
+            | Action::AckReadOnly(_)
+            | Action::Delegate(_)
+            | Action::AckDecision(_)
+            | Action::Commit(..) => Some(1),
+            Action::Retry(_) => {
+                // The prepare request that is deferred, and the retry hint.
+                Some(2)
+            }
+            Action::Forget(_) => {
+                // Forgetting the transaction before all the participants commit it loses messages.
+                let coordinator = &state.node_map[self.coordinator_node_id()];
+                let mut delivered = true;
+                coordinator
+                    .transaction
+                    .for_each_participant(|participant_node_id| {
+                        if state.node_map[participant_node_id].transaction.state()
+                            != TransactionState::Committed
+                        {
+                            delivered = false;
+                        }
+                    });
+                delivered.then_some(0)
+            }
+            Action::Inquire(_) | Action::CheckStatus(_) | Action::StatusReply(..) => {
+                // A participant inquires or checks the status only after its coordinator message
+                // is lost.
+                None
+            }
+            Action::Replicate(..) | Action::Checkpoint(_) | Action::Fsync(_) => {
+                // Replication, checkpoints and syncs are internal to the node.
+                Some(0)
+            }
+            Action::Send(message) => self.messages(state, &message.action),
+            Action::Crash(_)
+            | Action::Amnesia(_)
+            | Action::LogWriteFail(_)
+            | Action::Shutdown(_)
+            | Action::Fail(_)
+            | Action::Replace(_)
+            | Action::LoseReplica(..)
+            | Action::Duplicate(_)
+            | Action::FailLink(..)
+            | Action::Forge(_) => None,
+            Action::QueryPeer(_, _)
+            | Action::PeerAnswer(_, _)
+            | Action::Elect(_)
+            | Action::HeuristicCommit(_)
+            | Action::HeuristicRollback(_)
+            | Action::Takeover
+            | Action::StandbyCommit
+            | Action::StandbyRollback
+            | Action::FailStandby => {
+                // Peers are queried, a coordinator is elected or replaced by the standby, and
+                // participants decide heuristically only after the coordinator fails.
+                None
+            }
+        }
+    }
+
+    fn is_committed(&self, state: &Self::State) -> bool {
+        state
+            .node_map
+            .iter()
+            .all(|node| node.transaction.state() == TransactionState::Committed)
+    }
+
+    fn expected_messages(&self) -> usize {
+        // Prepare requests, votes, and commit messages along each edge of the commit tree; a
+        // read-only participant receives no commit message.
+        if self.one_phase && self.parents.len() == 2 {
+            // The delegation of the decision, and the decision.
+            return 2;
+        }
+        if self.last_agent {
+            // The last agent receives no prepare request, and no commit message.
+            return 3 * (self.parents.len() - 2) + 2;
+        }
+        3 * (self.parents.len() - 1) - self.read_only.len()
+    }
+}
+
+#[cfg(test)]
+mod model_checker {
+    use super::{
+        Action, Decision, FailureModel, Message, Model, NodeStatus, System, TransactionModel,
+        TransactionState,
+    };
+    use crate::coordinator_policy;
+    use crate::invariant::Invariant;
+    use crate::model_config::ModelConfig;
+    use crate::participant_behavior;
+    use crate::scenario::Scenario;
+    use crate::workload;
+
+    use std::num::NonZeroUsize;
+    use std::thread::available_parallelism;
+
+    use stateright::{Checker, Property};
+
+    /// [`Mutation`] is a bug injected into the coordinator.
+    #[derive(Clone, Copy)]
+    enum Mutation {
+        /// The coordinator counts a vote against commit as a vote for commit.
+        IgnoreNoVote,
+
+        /// The coordinator sends rollback to the participants after it has committed.
+        RollbackAfterCommit,
+
+        /// The coordinator commits once the participants have prepared, before their votes
+        /// arrive.
+        CommitBeforeVotes,
+
+        /// The coordinator commits only after a fault has been injected.
+        CommitOnlyAfterFault,
+    }
+
+    /// [`Mutant`] is a [`TransactionModel`] with a bug injected into the coordinator.
+    struct Mutant {
+        model: TransactionModel,
+        mutation: Mutation,
+    }
+
+    impl Model for Mutant {
+        type State = System;
+        type Action = Action;
+
+        fn init_states(&self) -> Vec<Self::State> {
+            self.model.init_states()
+        }
+
+        fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+            self.model.actions(state, actions);
+            let coordinator = &state.node_map[self.model.coordinator_node_id()];
+            if matches!(self.mutation, Mutation::RollbackAfterCommit)
+                && coordinator.transaction.state() == TransactionState::Committed
+            {
+                coordinator
+                    .transaction
+                    .for_each_participant(|node_id| actions.push(Action::Rollback(node_id)));
+            }
+            let mut all_prepared = true;
+            coordinator.transaction.for_each_participant(|node_id| {
+                all_prepared &=
+                    state.node_map[node_id].transaction.state() == TransactionState::Prepared;
+            });
+            if matches!(self.mutation, Mutation::CommitOnlyAfterFault) && !state.faulty {
+                let coordinator_node_id = self.model.coordinator_node_id();
+                actions.retain(|action| {
+                    !matches!(action, Action::Commit(node_id, _) if *node_id == coordinator_node_id)
+                });
+            }
+            if matches!(self.mutation, Mutation::CommitBeforeVotes)
+                && coordinator.transaction.state() == TransactionState::Prepared
+                && all_prepared
+            {
+                actions.push(Action::Commit(
+                    self.model.coordinator_node_id(),
+                    TransactionModel::commit_timestamp_of(state, self.model.coordinator_node_id()),
+                ));
+            }
+        }
+
+        fn next_state(
+            &self,
+            last_state: &Self::State,
+            action: Self::Action,
+        ) -> Option<Self::State> {
+            match (self.mutation, action) {
+                (Mutation::IgnoreNoVote, Action::AckPrepareFail(node_id)) => {
+                    let state = TransactionModel::log_vote(last_state.clone(), node_id, false);
+                    self.model.next_state(&state, Action::AckPrepare(node_id))
+                }
+                (_, action) => self.model.next_state(last_state, action),
+            }
+        }
+
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![
+                Property::<Self>::always("no protocol violation", |_, state| {
+                    state.is_violation_free()
+                }),
+                Property::<Self>::always("commit validity", |mutant, state| {
+                    mutant.model.is_commit_valid(state)
+                }),
+                Property::<Self>::sometimes("non-triviality", |_, state| {
+                    !state.faulty && TransactionModel::is_committed_everywhere(state)
+                }),
+            ]
+        }
+    }
+
+    #[test]
+    fn two_phase_commit() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn presumed_commit() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn graceful_shutdown() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .graceful_shutdown(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("no in-doubt shutdown");
+    }
+
+    #[test]
+    fn replacement() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .replacement(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("no double apply after replacement");
+    }
+
+    #[test]
+    fn decision_latency() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .decision_latency(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("decision latency bound");
+    }
+
+    #[test]
+    fn flow_control() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .flow_control(1)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("no abort within retry budget");
+    }
+
+    #[test]
+    fn commit_tree() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::with_topology(vec![None, Some(0), Some(1)], false)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn read_only_participant() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .read_only_participants(vec![2])
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn one_phase() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        for num_nodes in 2..=3 {
+            let checker = TransactionModel::new(num_nodes, false)
+                .one_phase(true)
+                .checker()
+                .threads(num_cpus)
+                .spawn_dfs()
+                .join();
+            checker.assert_properties();
+        }
+    }
+
+    #[test]
+    fn workloads() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        for name in ["transfer", "append", "counter"] {
+            let checker = TransactionModel::new(3, false)
+                .workload(workload::by_name(name).unwrap())
+                .checker()
+                .threads(num_cpus)
+                .spawn_dfs()
+                .join();
+            checker.assert_properties();
+        }
+    }
+
+    #[test]
+    fn cooperative_termination() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .cooperative_termination(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn coordinator_election() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .coordinator_election(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn backup_coordinator() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .backup_coordinator(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn quorum() {
+        // A single straggler is enough to exercise the catch-up, and four nodes are too many for
+        // an exhaustive check.
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .quorum(1)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn replicated_participants() {
+        // The replicas multiply the states of the participants, so that three nodes are too many
+        // for an exhaustive check.
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(2, false)
+            .replicated_participants(2)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn heuristics() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .heuristics(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("agreement");
+        checker.assert_no_discovery("commit validity");
+        checker.assert_no_discovery("unprepared crash aborts");
+        checker.assert_any_discovery("no heuristic-mixed outcome");
+    }
+
+    #[test]
+    fn configurable_coordinator() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .coordinator(2)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+        assert_eq!(
+            checker.unique_state_count(),
+            TransactionModel::new(3, false)
+                .checker()
+                .spawn_dfs()
+                .join()
+                .unique_state_count()
+        );
+    }
+
+    #[test]
+    fn last_agent() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .last_agent(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn asynchronous_network() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(2, false)
+            .decision_latency(true)
+            .asynchronous_network(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("agreement");
+        checker.assert_no_discovery("commit validity");
+        checker.assert_no_discovery("no protocol violation");
+    }
+
+    #[test]
+    fn message_duplication() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(2, false)
+            .decision_latency(true)
+            .asynchronous_network(true)
+            .message_duplication(1)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("agreement");
+        checker.assert_no_discovery("commit validity");
+        checker.assert_no_discovery("no protocol violation");
+    }
+
+    #[test]
+    fn crash_budget() {
+        let model = TransactionModel::new(2, false).crash_budget(1);
+        let mut state = model.init_states().remove(0);
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::Crash(0)));
+        assert!(actions.contains(&Action::Crash(1)));
+
+        // No node crashes once the budget is exhausted.
+        state = model.next_state(&state, Action::Crash(1)).unwrap();
+        actions.clear();
+        model.actions(&state, &mut actions);
+        assert!(!actions
+            .iter()
+            .any(|action| matches!(action, Action::Crash(_))));
+        assert!(model.next_state(&state, Action::Crash(0)).is_none());
+    }
+
+    #[test]
+    fn crash_recovery() {
+        let model = TransactionModel::new(2, false);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // Both nodes rebuild the transaction from their logs after a crash.
+        for node_id in [0, 1] {
+            state = model.next_state(&state, Action::Crash(node_id)).unwrap();
+            assert_eq!(
+                state.node_map[node_id].transaction.state(),
+                TransactionState::Prepared
+            );
+        }
+        assert!(state.node_map[0].transaction.has_participant(1));
+        assert!(!state.node_map[0].transaction.is_prepared(1));
+    }
+
+    #[test]
+    fn durability() {
+        let model = TransactionModel::new(2, false).checkpointing(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Commit(0, 1),
+            Action::Commit(1, 1),
+            Action::Checkpoint(1),
+            Action::Crash(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The participant recovers the commit from its checkpoint.
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::Committed
+        );
+        assert!(TransactionModel::is_commit_durable(&state));
+    }
+
+    #[test]
+    fn unprepared_crash() {
+        let model = TransactionModel::new(2, false);
+        let mut state = model.init_states().remove(0);
+        for action in [Action::Start(0), Action::RequestJoin(1), Action::AckJoin(1)] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The participant that crashes after it has logged the prepare record is not flagged.
+        let prepared = model
+            .next_state(&state, Action::RequestPrepare(1))
+            .and_then(|state| model.next_state(&state, Action::Crash(1)))
+            .unwrap();
+        assert!(!prepared.node_map[1].crashed_unprepared);
+
+        // The participant that crashes before it has prepared may only roll back.
+        state = model.next_state(&state, Action::Crash(1)).unwrap();
+        assert!(state.node_map[1].crashed_unprepared);
+        assert!(TransactionModel::is_unprepared_crash_aborted(&state));
+        let mut committed = state.clone();
+        committed.node_map[1].transaction.prepare();
+        committed.node_map[1].transaction.commit();
+        assert!(!TransactionModel::is_unprepared_crash_aborted(&committed));
+    }
+
+    #[test]
+    fn unprepared_crash_aborts() {
+        // The participant that crashes before it has prepared may not commit in one phase either.
+        let model = TransactionModel::new(2, false).one_phase(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::Crash(1),
+            Action::Delegate(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::OnePhaseCommit(1)));
+
+        for model in [model, TransactionModel::new(3, false)] {
+            let checker = model
+                .select_properties(vec!["unprepared crash aborts".to_string()])
+                .checker()
+                .spawn_dfs()
+                .join();
+            checker.assert_properties();
+        }
+    }
+
+    #[test]
+    fn recovery_correctness() {
+        let model = TransactionModel::new(2, false).checkpointing(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Commit(0, 1),
+            Action::Commit(1, 1),
+            Action::Checkpoint(1),
+            Action::Crash(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
         }
+        assert!(TransactionModel::is_recovery_correct(&state));
 
-        new_node_state
+        // A log that ends with a commit record, but replays to a rollback, is recovered in a state
+        // other than its last decision.
+        state.node_map[0].persistency = vec![
+            Action::RequestJoin(1),
+            Action::RequestPrepare(0),
+            Action::Rollback(0),
+            Action::Commit(0, 1),
+        ];
+        state = model.next_state(&state, Action::Crash(0)).unwrap();
+        assert_eq!(
+            state.node_map[0].transaction.state(),
+            TransactionState::RolledBack
+        );
+        assert!(!TransactionModel::is_recovery_correct(&state));
     }
 
-    fn mark_prepared(node: &Node, participant_node_id: usize) -> Node {
-        let mut new_node_state = node.clone();
-        new_node_state
-            .transaction
-            .report_prepared(participant_node_id);
-        new_node_state
+    #[test]
+    fn crash_before_fan_out() {
+        let model = TransactionModel::new(3, false);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::RequestJoin(2),
+            Action::AckJoin(1),
+            Action::AckJoin(2),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::RequestPrepare(2),
+            Action::AckPrepare(1),
+            Action::AckPrepare(2),
+            Action::Commit(0, 1),
+            Action::Crash(0),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The recovered coordinator sends the logged decision to the in-doubt participants.
+        assert!(model.is_in_doubt_window(&state));
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::Commit(1, 1)));
+        assert!(actions.contains(&Action::Commit(2, 1)));
     }
 
-    fn commit_distributed_transaction(node: &Node) -> Node {
-        let mut new_node_state = node.clone();
+    #[test]
+    fn coordinator_amnesia() {
+        let model = TransactionModel::new(3, false).coordinator_amnesia(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::RequestJoin(2),
+            Action::AckJoin(1),
+            Action::AckJoin(2),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::RequestPrepare(2),
+            Action::AckPrepare(1),
+            Action::Amnesia(0),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
 
-        // Commit the transaction.
-        if new_node_state.transaction.commit() {
-            // Record the transaction state change: the transaction is committed.
-            new_node_state
-                .persistency
-                .push(Action::Commit(new_node_state.id));
+        // The coordinator that knows nothing about the transaction never starts it again, and the
+        // prepared participant learns the presumed rollback by checking the status.
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::Start(0)));
+        assert!(!actions.contains(&Action::Amnesia(0)));
+        state = model.next_state(&state, Action::CheckStatus(1)).unwrap();
+        state = model
+            .next_state(&state, Action::StatusReply(1, Decision::Rollback))
+            .unwrap();
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::RolledBack
+        );
+        assert!(model.is_amnesia_safe(&state));
+        assert!(model.is_decision_resendable(&state));
+    }
+
+    #[test]
+    fn log_write_failure() {
+        let model = TransactionModel::new(2, false).log_write_failures(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::LogWriteFail(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
         }
 
-        new_node_state
+        // The participant that cannot log the prepare record votes against commit.
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::RolledBack
+        );
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::AckPrepareFail(1)));
+        assert!(!actions.contains(&Action::AckPrepare(1)));
+        state = model.next_state(&state, Action::AckPrepareFail(1)).unwrap();
+        assert_eq!(
+            state.node_map[0].transaction.state(),
+            TransactionState::RolledBack
+        );
+        assert!(model.is_vote_synced(&state));
     }
 
-    fn rollback_distributed_transaction(node: &Node) -> Node {
-        let mut new_node_state = node.clone();
+    #[test]
+    fn fail_stop() {
+        let model = TransactionModel::new(2, false).failure_model(FailureModel::FailStop);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Crash(0),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
 
-        // Rollback the transaction.
-        if new_node_state.transaction.rollback() {
-            // Record the transaction state change: the transaction is rolled back.
-            new_node_state
-                .persistency
-                .push(Action::Rollback(new_node_state.id));
+        // The crashed coordinator stays down, and the prepared participant gets no reply.
+        assert_eq!(state.node_map[0].status, NodeStatus::Crashed);
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::Crash(0)));
+        assert!(!actions.contains(&Action::Commit(0, 1)));
+        assert!(!actions.contains(&Action::Rollback(0)));
+        assert!(model.next_state(&state, Action::CheckStatus(1)).is_none());
+    }
+
+    #[test]
+    fn participant_behaviors() {
+        // A participant that always votes no never lets the transaction commit.
+        let checker = TransactionModel::new(3, false)
+            .crash_budget(0)
+            .participant_behavior(2, participant_behavior::by_name("no-voter").unwrap())
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("non-triviality");
+        checker.assert_any_discovery("global rollback");
+
+        // An unrecoverable participant stays down even if the other nodes recover.
+        let model = TransactionModel::new(2, false)
+            .participant_behavior(1, participant_behavior::by_name("unrecoverable").unwrap());
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::Crash(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
         }
+        assert_eq!(state.node_map[1].status, NodeStatus::Crashed);
+        state = model.next_state(&state, Action::Crash(0)).unwrap();
+        assert_ne!(state.node_map[0].status, NodeStatus::Crashed);
+    }
 
-        new_node_state
+    #[test]
+    fn coordinator_policies() {
+        let actions_after = |policy: &str, steps: &[Action]| {
+            let model = TransactionModel::new(3, false)
+                .coordinator_policy(coordinator_policy::by_name(policy).unwrap());
+            let mut state = model.init_states().remove(0);
+            for action in steps {
+                state = model.next_state(&state, action.clone()).unwrap();
+            }
+            let mut actions = Vec::new();
+            model.actions(&state, &mut actions);
+            actions
+        };
+        let mut steps = vec![Action::Start(0), Action::RequestJoin(1), Action::AckJoin(1)];
+
+        // Only the strict coordinator waits for all the participants to join before voting.
+        assert!(actions_after("permissive", &steps).contains(&Action::RequestPrepare(0)));
+        assert!(!actions_after("strict", &steps).contains(&Action::RequestPrepare(0)));
+
+        // Only the impatient coordinator gives up on a missing vote.
+        steps.extend([
+            Action::RequestJoin(2),
+            Action::AckJoin(2),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+        ]);
+        assert!(!actions_after("permissive", &steps).contains(&Action::Rollback(0)));
+        assert!(actions_after("impatient", &steps).contains(&Action::Rollback(0)));
+
+        // Only the permissive coordinator may roll back despite unanimous yes votes.
+        steps.extend([Action::RequestPrepare(2), Action::AckPrepare(2)]);
+        for (policy, may_abort) in [
+            ("permissive", true),
+            ("strict", false),
+            ("impatient", false),
+        ] {
+            let actions = actions_after(policy, &steps);
+            assert!(actions.contains(&Action::Commit(0, 1)));
+            assert_eq!(actions.contains(&Action::Rollback(0)), may_abort);
+        }
     }
 
-    fn crash_restart(node: &Node) -> Node {
-        let mut new_node_state = node.clone();
+    #[test]
+    fn checkpointing() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .checkpointing(true)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("recovery from checkpoint");
+        checker.assert_no_discovery("prepared survives crash");
+    }
 
-        // Reset the transaction.
-        new_node_state.transaction = Transaction::default();
+    #[test]
+    fn torn_writes() {
+        let model = TransactionModel::new(2, false).torn_writes(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::Fsync(0),
+            Action::RequestJoin(1),
+            Action::Fsync(0),
+            Action::AckJoin(1),
+            Action::Fsync(1),
+            Action::RequestPrepare(0),
+            Action::Fsync(0),
+            Action::RequestPrepare(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
 
-        // TODO: how to make it work??
+        // The participant votes only after syncing the prepare record, and loses the record that
+        // is not synced when it crashes.
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::AckPrepare(1)));
+        let crashed = model.next_state(&state, Action::Crash(1)).unwrap();
+        assert_eq!(
+            crashed.node_map[1].transaction.state(),
+            TransactionState::Active
+        );
+        state = model.next_state(&state, Action::Fsync(1)).unwrap();
+        state = model.next_state(&state, Action::Crash(1)).unwrap();
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::Prepared
+        );
 
-        new_node_state
+        let checker = model.checker().spawn_dfs().join();
+        checker.assert_no_discovery("vote synced before sent");
+        checker.assert_no_discovery("prepared survives crash");
     }
-}
 
-impl Model for TransactionModel {
-    type State = System;
-    type Action = Action;
+    #[test]
+    fn byzantine_participant() {
+        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
+        let checker = TransactionModel::new(3, false)
+            .byzantine_participant(2)
+            .checker()
+            .threads(num_cpus)
+            .spawn_dfs()
+            .join();
+        checker.assert_any_discovery("honest agreement");
+    }
 
-    fn init_states(&self) -> Vec<Self::State> {
-        let mut node_map: Vec<Node> = Vec::with_capacity(self.num_nodes);
-        for id in 0..self.num_nodes {
-            node_map.push(Node::with_id(id));
+    #[test]
+    fn dead_participant() {
+        let model = TransactionModel::new(3, false).dead_participant(2);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::RequestJoin(2),
+            Action::AckJoin(1),
+            Action::AckJoin(2),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::RequestPrepare(2),
+            Action::AckPrepare(1),
+            Action::AckPrepare(2),
+            Action::Commit(0, 1),
+            Action::Commit(2, 1),
+            Action::Fail(2),
+        ] {
+            state = model.next_state(&state, action).unwrap();
         }
-        vec![System { node_map }]
+        assert!(!model.is_blocked(&state));
+
+        // The prepared participant cannot learn the decision once the coordinator dies as well.
+        state = model.next_state(&state, Action::Fail(0)).unwrap();
+        assert!(model.is_blocked(&state));
+        assert!(model.is_blocking(&state));
+        assert!(model.next_state(&state, Action::CheckStatus(1)).is_none());
     }
 
-    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
-        state
-            .node_map
+    #[test]
+    fn termination() {
+        let checker = TransactionModel::new(3, false)
+            .crash_budget(0)
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("termination");
+    }
+
+    #[test]
+    fn fairness() {
+        let model = TransactionModel::new(2, false);
+        let fair_model = TransactionModel::new(2, false).fairness(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Commit(0, 1),
+            Action::Commit(1, 1),
+        ] {
+            state = fair_model.next_state(&state, action).unwrap();
+        }
+
+        // Retransmitting the commit to the participant that has committed does not change the
+        // state, and is pruned under fairness.
+        assert_eq!(
+            model.next_state(&state, Action::Commit(1, 1)),
+            Some(state.clone())
+        );
+        assert_eq!(fair_model.next_state(&state, Action::Commit(1, 1)), None);
+    }
+
+    #[test]
+    fn max_depth() {
+        let model = TransactionModel::new(2, false).crash_budget(0).max_depth(2);
+        // An execution cut at the depth is not a deadlock.
+        assert!(model
+            .properties()
             .iter()
-            .enumerate()
-            .for_each(|(node_id, node)| Self::add_actions_per_node(node_id, node, actions));
+            .all(|property| property.name != "termination"));
+
+        let mut state = model.init_states().remove(0);
+        for action in [Action::Start(0), Action::RequestJoin(1)] {
+            state = model.next_state(&state, action).unwrap();
+            assert!(model.within_boundary(&state));
+        }
+        let state = model.next_state(&state, Action::AckJoin(1)).unwrap();
+        assert!(!model.within_boundary(&state));
+
+        // The disagreement takes more actions than the depth allows.
+        TransactionModel::new(3, false)
+            .max_depth(6)
+            .checker()
+            .spawn_bfs()
+            .join()
+            .assert_no_discovery("agreement");
     }
 
-    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
-        match action {
-            Action::Start(node_id) => last_state
-                .node_map
-                .get(node_id)
-                .and_then(Self::start_transaction)
-                .map(|next_node_state| {
-                    Self::next_system_state(last_state, node_id, next_node_state)
-                }),
-            Action::RequestJoin(participant_node_id) => last_state
-                .node_map
-                .get(Self::coordinator_node_id())
-                .map(|node| Self::add_participant(node, participant_node_id))
-                .map(|next_node_state| {
-                    Self::next_system_state(
-                        last_state,
-                        Self::coordinator_node_id(),
-                        next_node_state,
-                    )
-                }),
-            Action::AckJoin(node_id) => last_state
-                .node_map
-                .get(node_id)
-                .map(Self::start_distributed_transaction)
-                .map(|next_node_state| {
-                    Self::next_system_state(last_state, node_id, next_node_state)
-                }),
+    #[test]
+    fn commit_propagation() {
+        let model = TransactionModel::new(2, false).crash_budget(0);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Commit(0, 1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
 
-            Action::RequestPrepare(node_id) => last_state
-                .node_map
-                .get(node_id)
-                .map(Self::prepare_distributed_transaction)
-                .map(|next_node_state| {
-                    Self::next_system_state(last_state, node_id, next_node_state)
-                }),
-            Action::AckPrepare(participant_node_id) => last_state
-                .node_map
-                .get(Self::coordinator_node_id())
-                .map(|node| Self::mark_prepared(node, participant_node_id))
-                .map(|next_node_state| {
-                    Self::next_system_state(
-                        last_state,
-                        Self::coordinator_node_id(),
-                        next_node_state,
-                    )
-                }),
-            Action::AckPrepareFail => last_state
-                .node_map
-                .get(Self::coordinator_node_id())
-                .map(Self::rollback_distributed_transaction)
-                .map(|next_node_state| {
-                    Self::next_system_state(
-                        last_state,
-                        Self::coordinator_node_id(),
-                        next_node_state,
-                    )
-                }),
-            Action::Commit(node_id) => last_state
-                .node_map
-                .get(node_id)
-                .map(Self::commit_distributed_transaction)
-                .map(|next_node_state| {
-                    Self::next_system_state(last_state, node_id, next_node_state)
-                }),
-            Action::Rollback(node_id) => last_state
-                .node_map
-                .get(node_id)
-                .map(Self::rollback_distributed_transaction)
-                .map(|next_node_state| {
-                    Self::next_system_state(last_state, node_id, next_node_state)
-                }),
-            Action::Crash(node_id) => last_state
-                .node_map
-                .get(node_id)
-                .map(Self::crash_restart)
-                .map(|next_node_state| {
-                    Self::next_system_state(last_state, node_id, next_node_state)
-                }),
+        // The coordinator has committed, but the participant is still prepared.
+        assert!(!model.is_commit_propagated(&state));
+        state = model.next_state(&state, Action::Commit(1, 1)).unwrap();
+        assert!(model.is_commit_propagated(&state));
+
+        let checker = model.checker().spawn_dfs().join();
+        checker.assert_no_discovery("commit reaches participants");
+    }
+
+    #[test]
+    fn blocking() {
+        let model = TransactionModel::new(3, false).cooperative_termination(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::RequestJoin(2),
+            Action::AckJoin(1),
+            Action::AckJoin(2),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Fail(0),
+        ] {
+            state = model.next_state(&state, action).unwrap();
         }
+
+        // The in-doubt participant can still learn the rollback from its undecided peer, but not
+        // once the peer has prepared as well.
+        assert!(!model.is_blocking(&state));
+        state = model.next_state(&state, Action::RequestPrepare(2)).unwrap();
+        assert!(model.is_blocking(&state));
     }
 
-    fn properties(&self) -> Vec<Property<Self>> {
-        vec![Property::<Self>::always("ACID", |_, state| {
-            // If a transaction on a node has decided to commit or roll back, all the participant
-            // transactions should agree on the decision.
-            let mut commit_decided: Option<bool> = None;
-            let mut not_unanimous = state.node_map.iter().any(|node| {
-                let final_state = match node.transaction.state() {
-                    TransactionState::Committed => Some(true),
-                    TransactionState::RolledBack => Some(false),
-                    _ => None,
-                };
-                match (final_state, commit_decided) {
-                    (None, _) => false,
-                    (Some(_), None) => {
-                        commit_decided = final_state;
-                        false
-                    }
-                    (Some(final_state), Some(other_state)) => {
-                        // If they do not match, it is a consistency error.
-                        final_state != other_state
-                    }
-                }
-            });
+    #[test]
+    fn reachable_decisions() {
+        let checker = TransactionModel::new(2, false)
+            .crash_budget(0)
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_any_discovery("non-triviality");
+        checker.assert_any_discovery("global rollback");
+    }
 
-            // If the coordinator has decided to commit, all the participants also should commit or
-            // be prepared for commit.
-            if !not_unanimous
-                && state.node_map[Self::coordinator_node_id()]
-                    .transaction
-                    .state()
-                    == TransactionState::Committed
-            {
-                state.node_map[Self::coordinator_node_id()]
-                    .transaction
-                    .for_each_participant(|participant_node_id| {
-                        let state = state.node_map[participant_node_id].transaction.state();
-                        if state != TransactionState::Prepared
-                            && state != TransactionState::Committed
-                        {
-                            not_unanimous = true;
-                        }
-                    });
-            }
+    #[test]
+    fn select_properties() {
+        let model = TransactionModel::new(2, false)
+            .crash_budget(0)
+            .select_properties(vec!["termination".to_string(), "agreement".to_string()]);
+        let names: Vec<_> = model
+            .properties()
+            .iter()
+            .map(|property| property.name)
+            .collect();
+        assert_eq!(names, ["agreement", "termination"]);
+    }
 
-            !not_unanimous
-        })]
+    #[test]
+    fn no_blocking_is_opt_in() {
+        let has_no_blocking = |model: TransactionModel| {
+            model
+                .properties()
+                .iter()
+                .any(|property| property.name == "no blocking")
+        };
+        assert!(!has_no_blocking(TransactionModel::new(3, false)));
+        assert!(has_no_blocking(
+            TransactionModel::new(3, false).select_properties(vec!["no blocking".to_string()])
+        ));
     }
-}
 
-#[cfg(test)]
-mod model_checker {
-    use super::{Model, TransactionModel};
+    #[test]
+    fn property_descriptions() {
+        // Every property of a model with all the optional properties enabled is described.
+        let model = TransactionModel::new(3, false)
+            .crash_budget(1)
+            .checkpointing(true)
+            .link_failures(1)
+            .dead_participant(2)
+            .byzantine_participant(2)
+            .coordinator_amnesia(true)
+            .log_write_failures(true)
+            .torn_writes(true)
+            .graceful_shutdown(true)
+            .decision_latency(true)
+            .workload(workload::by_name("counter").unwrap())
+            .coordinator_election(true)
+            .replicated_participants(2)
+            .quorum(1)
+            .backup_coordinator(true)
+            .heuristics(true)
+            .cooperative_termination(true)
+            .flow_control(1)
+            .replacement(true)
+            .invariants(
+                Invariant::load(
+                    r#"[{"name": "n", "condition": {"not": {"every_node": "Active"}}}]"#,
+                    3,
+                )
+                .unwrap(),
+            );
+        let properties = model
+            .properties()
+            .into_iter()
+            .chain(TransactionModel::new(3, false).crash_budget(0).properties());
+        for property in properties {
+            assert!(
+                TransactionModel::property_description(property.name).is_some(),
+                "{} is not described",
+                property.name
+            );
+        }
+        assert_eq!(TransactionModel::property_description("unknown"), None);
+    }
 
-    use std::num::NonZeroUsize;
-    use std::thread::available_parallelism;
+    #[test]
+    fn invariants() {
+        // A participant never commits while the coordinator is active, but the coordinator may
+        // commit.
+        let invariants = Invariant::load(
+            r#"[
+                {"name": "no early commit", "condition": {"not": {"all": [
+                    {"node": {"node": 0, "state": "Active"}},
+                    {"node": {"node": 1, "state": "Committed"}}
+                ]}}},
+                {"name": "never committed", "condition": {"not": {"any_node": "Committed"}}}
+            ]"#,
+            2,
+        )
+        .unwrap();
+        let checker = TransactionModel::new(2, false)
+            .invariants(invariants[..1].to_vec())
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("custom invariants");
+        let checker = TransactionModel::new(2, false)
+            .invariants(invariants)
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_any_discovery("custom invariants");
+    }
+
+    #[test]
+    fn protocol_violation() {
+        let checker = TransactionModel::new(3, false).checker().spawn_dfs().join();
+        checker.assert_no_discovery("no protocol violation");
+
+        for mutation in [Mutation::IgnoreNoVote, Mutation::RollbackAfterCommit] {
+            let checker = Mutant {
+                model: TransactionModel::new(3, false),
+                mutation,
+            }
+            .checker()
+            .spawn_dfs()
+            .join();
+            checker.assert_any_discovery("no protocol violation");
+        }
+    }
+
+    #[test]
+    fn commit_validity() {
+        let checker = TransactionModel::new(3, false)
+            .crash_budget(0)
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_no_discovery("commit validity");
 
-    use stateright::Checker;
+        // The participants are prepared when the coordinator commits, but their votes are missing.
+        let checker = Mutant {
+            model: TransactionModel::new(3, false).crash_budget(0),
+            mutation: Mutation::CommitBeforeVotes,
+        }
+        .checker()
+        .spawn_dfs()
+        .join();
+        checker.assert_any_discovery("commit validity");
+    }
 
     #[test]
-    fn two_phase_commit() {
-        let num_cpus = available_parallelism().map_or(1, NonZeroUsize::get);
-        let checker = TransactionModel::new(3)
+    fn decision_stability() {
+        let model = TransactionModel::new(2, false);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+            Action::Commit(0, 1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+        assert!(!state.decision_flipped);
+
+        // The coordinator does not roll back the transaction that it has committed.
+        let next_state = model.next_state(&state, Action::Rollback(0)).unwrap();
+        assert_eq!(
+            next_state.node_map[0].transaction.state(),
+            TransactionState::Committed
+        );
+        assert!(!next_state.decision_flipped);
+
+        // A node that reverses its decision is flagged even if the nodes agree.
+        let mut flipped = state.clone();
+        flipped.node_map[0].transaction.rollback();
+        flipped.node_map[1].transaction.rollback();
+        TransactionModel::record_decision_flip(&state, &mut flipped);
+        assert!(TransactionModel::is_agreement(&flipped));
+        assert!(flipped.decision_flipped);
+
+        let checker = TransactionModel::new(3, false)
+            .select_properties(vec!["decision stability".to_string()])
             .checker()
-            .threads(num_cpus)
             .spawn_dfs()
             .join();
         checker.assert_properties();
     }
+
+    #[test]
+    fn non_triviality() {
+        let checker = TransactionModel::new(2, false)
+            .crash_budget(1)
+            .checker()
+            .spawn_dfs()
+            .join();
+        checker.assert_any_discovery("non-triviality");
+
+        // The transaction commits only in the executions where a node has crashed.
+        let checker = Mutant {
+            model: TransactionModel::new(2, false).crash_budget(1),
+            mutation: Mutation::CommitOnlyAfterFault,
+        }
+        .checker()
+        .spawn_dfs()
+        .join();
+        checker.assert_no_discovery("non-triviality");
+    }
+
+    #[test]
+    fn out_of_order_delivery() {
+        let model = TransactionModel::new(2, false).asynchronous_network(true);
+        let message = |action, sequence, sender| {
+            Box::new(Message {
+                action,
+                sequence,
+                sender,
+            })
+        };
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::Send(message(Action::RequestJoin(1), 0, 1)),
+            Action::Deliver(message(Action::RequestJoin(1), 0, 1)),
+            Action::Send(message(Action::AckJoin(1), 1, 0)),
+            Action::Deliver(message(Action::AckJoin(1), 1, 0)),
+            Action::RequestPrepare(0),
+            Action::Send(message(Action::RequestPrepare(1), 2, 0)),
+            Action::Rollback(0),
+            Action::Send(message(Action::Rollback(1), 3, 0)),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The stale prepare request is delivered after the rollback, and is ignored.
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        let stale_prepare = Action::Deliver(message(Action::RequestPrepare(1), 2, 0));
+        assert!(actions.contains(&stale_prepare));
+        state = model
+            .next_state(&state, Action::Deliver(message(Action::Rollback(1), 3, 0)))
+            .unwrap();
+        state = model.next_state(&state, stale_prepare).unwrap();
+        assert!(state.network.is_empty());
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::RolledBack
+        );
+    }
+
+    #[test]
+    fn link_failures() {
+        let model = TransactionModel::new(2, false)
+            .asynchronous_network(true)
+            .link_failures(1);
+        let message = |action, sequence, sender| {
+            Box::new(Message {
+                action,
+                sequence,
+                sender,
+            })
+        };
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::Send(message(Action::RequestJoin(1), 0, 1)),
+            Action::Deliver(message(Action::RequestJoin(1), 0, 1)),
+            Action::Send(message(Action::AckJoin(1), 1, 0)),
+            Action::Deliver(message(Action::AckJoin(1), 1, 0)),
+            Action::FailLink(1, 0),
+            Action::RequestPrepare(0),
+            Action::Send(message(Action::RequestPrepare(1), 2, 0)),
+            Action::Deliver(message(Action::RequestPrepare(1), 2, 0)),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The prepare request gets through, but the vote is lost on the failed link, and no other
+        // link fails.
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::FailLink(0, 1)));
+        let vote = message(Action::AckPrepare(1), 1, 1);
+        state = model
+            .next_state(&state, Action::Send(vote.clone()))
+            .unwrap();
+        state = model.next_state(&state, Action::Deliver(vote)).unwrap();
+        assert!(state.network.is_empty());
+        assert!(model.is_ack_lost(&state));
+    }
+
+    #[test]
+    fn scenario() {
+        let scenario = Scenario::load(
+            r#"{"name": "participant crash", "crashes": {"budget": 1, "nodes": [1]}}"#,
+            3,
+        )
+        .unwrap();
+        let model = TransactionModel::new(3, false).scenario(&scenario);
+        let mut state = model.init_states().remove(0);
+
+        // Only the participant in the scenario crashes, and only once.
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::Crash(1)));
+        assert!(!actions.contains(&Action::Crash(0)));
+        assert!(!actions.contains(&Action::Crash(2)));
+        state = model.next_state(&state, Action::Crash(1)).unwrap();
+        actions.clear();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::Crash(1)));
+    }
+
+    #[test]
+    fn config() {
+        let config = ModelConfig::load(
+            r#"
+            coordinator = 1
+            crash_budget = 1
+            faults = ["fail-stop"]
+            properties = ["agreement"]
+            "#,
+        )
+        .unwrap();
+        let model = TransactionModel::new(3, false).config(&config);
+        assert_eq!(model.coordinator_node_id(), 1);
+        let properties = model.properties();
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].name, "agreement");
+
+        // A node crashes once, and stays down.
+        let state = model.init_states().remove(0);
+        let state = model.next_state(&state, Action::Crash(2)).unwrap();
+        assert_eq!(state.node_map[2].status, NodeStatus::Crashed);
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::Crash(0)));
+    }
+
+    #[test]
+    fn check_status() {
+        let model = TransactionModel::new(2, false);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The undecided coordinator replies that the transaction is pending.
+        state = model.next_state(&state, Action::CheckStatus(1)).unwrap();
+        assert!(model
+            .next_state(&state, Action::StatusReply(1, Decision::Rollback))
+            .is_none());
+        state = model
+            .next_state(&state, Action::StatusReply(1, Decision::Pending))
+            .unwrap();
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::Prepared
+        );
+
+        // The participant learns the commit timestamp along with the decision.
+        state = model.next_state(&state, Action::Commit(0, 1)).unwrap();
+        state = model.next_state(&state, Action::CheckStatus(1)).unwrap();
+        state = model
+            .next_state(&state, Action::StatusReply(1, Decision::Commit(1)))
+            .unwrap();
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::Committed
+        );
+        assert_eq!(state.node_map[1].commit_timestamp, Some(1));
+    }
+
+    #[test]
+    fn timers() {
+        let model = TransactionModel::new(2, false).timers(true);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::Start(0),
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::RequestPrepare(0),
+            Action::RequestPrepare(1),
+            Action::AckPrepare(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The prepared participant checks the status only after its timer expires, and the timer
+        // is disabled once the participant learns the decision.
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::Timeout(1)));
+        assert!(!actions.contains(&Action::CheckStatus(1)));
+        state = model.next_state(&state, Action::Timeout(1)).unwrap();
+        actions.clear();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::CheckStatus(1)));
+        state = model.next_state(&state, Action::Commit(0, 1)).unwrap();
+        state = model.next_state(&state, Action::Commit(1, 1)).unwrap();
+        actions.clear();
+        model.actions(&state, &mut actions);
+        assert!(!actions.contains(&Action::Timeout(1)));
+    }
+
+    #[test]
+    fn replica_loss_after_decision() {
+        let model = TransactionModel::new(2, false).replicated_participants(2);
+        let mut state = model.init_states().remove(0);
+        for action in [
+            Action::RequestJoin(1),
+            Action::AckJoin(1),
+            Action::Replicate(1, 0),
+            Action::Replicate(1, 1),
+            Action::Crash(1),
+            Action::Rollback(1),
+        ] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The participant that has rolled back does not fail over to its active replica.
+        state = model.next_state(&state, Action::LoseReplica(1, 1)).unwrap();
+        assert_eq!(
+            state.node_map[1].transaction.state(),
+            TransactionState::RolledBack
+        );
+    }
+
+    #[test]
+    fn simulated_clock() {
+        let model = TransactionModel::new(2, false).simulated_clock(2, 2);
+        let mut state = model.init_states().remove(0);
+        for action in [Action::Start(0), Action::RequestJoin(1), Action::AckJoin(1)] {
+            state = model.next_state(&state, action).unwrap();
+        }
+
+        // The participant times out only after two ticks, and the clock stops after two ticks.
+        for _ in 0..2 {
+            assert!(!model
+                .next_steps(&state)
+                .iter()
+                .any(|(action, _)| *action == Action::Rollback(1)));
+            state = model.next_state(&state, Action::Tick).unwrap();
+        }
+        let mut actions = Vec::new();
+        model.actions(&state, &mut actions);
+        assert!(actions.contains(&Action::Rollback(1)));
+        assert!(!actions.contains(&Action::Tick));
+    }
 }
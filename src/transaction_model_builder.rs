@@ -0,0 +1,280 @@
+//! This is a builder of the two-phase commit [`TransactionModel`].
+//!
+//! The builder collects the node count or the commit tree, the coordinator, the crash budget, the
+//! faults and the optimizations, and validates them together when the model is built; the other
+//! parameters are set on the built model.
+
+use super::model_config::{FaultType, ModelConfig};
+use super::transaction_model::TransactionModel;
+
+use std::fmt::{self, Display};
+
+/// [`TransactionModelBuilder`] builds a validated [`TransactionModel`].
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug)]
+pub struct TransactionModelBuilder {
+    /// The parent of each node in the commit tree; a node without a parent is a child of the
+    /// coordinator.
+    parents: Vec<Option<usize>>,
+
+    /// The node that coordinates the transaction.
+    coordinator: usize,
+
+    /// The maximum number of crashes of the nodes, or `None` if nodes crash any number of times.
+    crash_budget: Option<usize>,
+
+    /// The faults that are enabled.
+    faults: Vec<FaultType>,
+
+    /// The presumed-commit variant of the protocol.
+    presumed_commit: bool,
+
+    /// A coordinator with a single participant delegates the decision to it.
+    one_phase: bool,
+
+    /// The coordinator delegates the decision to its last participant after preparing the others.
+    last_agent: bool,
+
+    /// The participant nodes that only read data.
+    read_only: Vec<usize>,
+}
+
+/// [`BuildError`] describes why a [`TransactionModel`] cannot be built.
+#[derive(Debug, Eq, PartialEq)]
+pub enum BuildError {
+    /// The model has fewer than two nodes.
+    TooFewNodes(usize),
+
+    /// The coordinator is not one of the nodes.
+    UnknownCoordinator(usize),
+
+    /// A parent in the commit tree, or a read-only participant, is not one of the nodes.
+    UnknownNode(usize),
+
+    /// The node is its own ancestor in the commit tree.
+    CyclicTree(usize),
+
+    /// The coordinator is made a read-only participant.
+    ReadOnlyCoordinator(usize),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::TooFewNodes(nodes) => write!(f, "{nodes} nodes, while 2 are needed"),
+            BuildError::UnknownCoordinator(node_id) => write!(f, "unknown coordinator {node_id}"),
+            BuildError::UnknownNode(node_id) => write!(f, "unknown node {node_id}"),
+            BuildError::CyclicTree(node_id) => write!(f, "node {node_id} is its own ancestor"),
+            BuildError::ReadOnlyCoordinator(node_id) => {
+                write!(f, "read-only coordinator {node_id}")
+            }
+        }
+    }
+}
+
+impl Default for TransactionModelBuilder {
+    fn default() -> Self {
+        TransactionModelBuilder::new()
+    }
+}
+
+impl TransactionModelBuilder {
+    /// Creates a new [`TransactionModelBuilder`] of three nodes coordinated by node 0, without
+    /// faults or optimizations.
+    #[must_use]
+    pub fn new() -> TransactionModelBuilder {
+        TransactionModelBuilder {
+            parents: vec![None; 3],
+            coordinator: 0,
+            crash_budget: None,
+            faults: Vec::new(),
+            presumed_commit: false,
+            one_phase: false,
+            last_agent: false,
+            read_only: Vec::new(),
+        }
+    }
+
+    /// Sets the number of nodes including the coordinator, all the participants being children of
+    /// the coordinator.
+    #[must_use]
+    pub fn nodes(self, num_nodes: usize) -> TransactionModelBuilder {
+        TransactionModelBuilder {
+            parents: vec![None; num_nodes],
+            ..self
+        }
+    }
+
+    /// Forms a commit tree in which `parents[node_id]` is the parent of the node; a node without a
+    /// parent is a child of the coordinator.
+    #[must_use]
+    pub fn commit_tree(self, parents: Vec<Option<usize>>) -> TransactionModelBuilder {
+        TransactionModelBuilder { parents, ..self }
+    }
+
+    /// Sets the node that coordinates the transaction.
+    #[must_use]
+    pub fn coordinator(self, coordinator: usize) -> TransactionModelBuilder {
+        TransactionModelBuilder {
+            coordinator,
+            ..self
+        }
+    }
+
+    /// Lets the nodes crash at most `crash_budget` times in total.
+    #[must_use]
+    pub fn crash_budget(self, crash_budget: usize) -> TransactionModelBuilder {
+        TransactionModelBuilder {
+            crash_budget: Some(crash_budget),
+            ..self
+        }
+    }
+
+    /// Enables or disables the fault.
+    #[must_use]
+    pub fn fault(self, fault: FaultType, enabled: bool) -> TransactionModelBuilder {
+        let mut faults: Vec<FaultType> = self.faults.into_iter().filter(|f| *f != fault).collect();
+        if enabled {
+            faults.push(fault);
+        }
+        TransactionModelBuilder { faults, ..self }
+    }
+
+    /// Uses the presumed-commit variant of the protocol.
+    #[must_use]
+    pub fn presumed_commit(self, presumed_commit: bool) -> TransactionModelBuilder {
+        TransactionModelBuilder {
+            presumed_commit,
+            ..self
+        }
+    }
+
+    /// Lets a coordinator with a single participant skip the voting phase.
+    #[must_use]
+    pub fn one_phase(self, one_phase: bool) -> TransactionModelBuilder {
+        TransactionModelBuilder { one_phase, ..self }
+    }
+
+    /// Lets the coordinator delegate the decision to its last participant.
+    #[must_use]
+    pub fn last_agent(self, last_agent: bool) -> TransactionModelBuilder {
+        TransactionModelBuilder { last_agent, ..self }
+    }
+
+    /// Makes the participant nodes read-only.
+    #[must_use]
+    pub fn read_only_participants(self, read_only: Vec<usize>) -> TransactionModelBuilder {
+        TransactionModelBuilder { read_only, ..self }
+    }
+
+    /// Validates the parameters, and builds the [`TransactionModel`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than two nodes, if the coordinator, a parent or a
+    /// read-only participant is not one of the nodes, if the commit tree has a cycle, or if the
+    /// coordinator is read-only.
+    pub fn build(self) -> Result<TransactionModel, BuildError> {
+        let num_nodes = self.parents.len();
+        if num_nodes < 2 {
+            return Err(BuildError::TooFewNodes(num_nodes));
+        }
+        if self.coordinator >= num_nodes {
+            return Err(BuildError::UnknownCoordinator(self.coordinator));
+        }
+        if let Some(parent) = self.parents.iter().flatten().find(|p| **p >= num_nodes) {
+            return Err(BuildError::UnknownNode(*parent));
+        }
+        for node_id in 0..num_nodes {
+            // A node on a cycle reaches itself within as many steps as there are nodes.
+            let mut ancestor = self.parents[node_id];
+            for _ in 0..num_nodes {
+                match ancestor {
+                    Some(parent) if parent == node_id => {
+                        return Err(BuildError::CyclicTree(node_id))
+                    }
+                    Some(parent) => ancestor = self.parents[parent],
+                    None => break,
+                }
+            }
+        }
+        if let Some(node_id) = self.read_only.iter().find(|node_id| **node_id >= num_nodes) {
+            return Err(BuildError::UnknownNode(*node_id));
+        }
+        if self.read_only.contains(&self.coordinator) {
+            return Err(BuildError::ReadOnlyCoordinator(self.coordinator));
+        }
+
+        let config = ModelConfig {
+            nodes: Some(num_nodes),
+            coordinator: Some(self.coordinator),
+            crash_budget: self.crash_budget,
+            faults: self.faults,
+            properties: Vec::new(),
+        };
+        Ok(
+            TransactionModel::with_topology(self.parents, self.presumed_commit)
+                .one_phase(self.one_phase)
+                .last_agent(self.last_agent)
+                .read_only_participants(self.read_only)
+                .config(&config),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BuildError, TransactionModelBuilder};
+    use crate::model_config::FaultType;
+    use crate::transaction_model::TransactionModel;
+
+    use stateright::Model;
+
+    #[test]
+    fn build() {
+        let model = TransactionModelBuilder::new()
+            .nodes(2)
+            .crash_budget(0)
+            .fault(FaultType::FailStop, true)
+            .fault(FaultType::FailStop, false)
+            .build()
+            .unwrap();
+        let properties = model.properties();
+        let expected = TransactionModel::new(2, false).crash_budget(0).properties();
+        assert_eq!(
+            properties.iter().map(|p| p.name).collect::<Vec<_>>(),
+            expected.iter().map(|p| p.name).collect::<Vec<_>>()
+        );
+        assert!(TransactionModelBuilder::new()
+            .commit_tree(vec![None, None, Some(1)])
+            .coordinator(1)
+            .read_only_participants(vec![2])
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid() {
+        let build = |builder: TransactionModelBuilder| builder.build().err();
+        assert_eq!(
+            build(TransactionModelBuilder::new().nodes(1)),
+            Some(BuildError::TooFewNodes(1))
+        );
+        assert_eq!(
+            build(TransactionModelBuilder::new().coordinator(3)),
+            Some(BuildError::UnknownCoordinator(3))
+        );
+        assert_eq!(
+            build(TransactionModelBuilder::new().commit_tree(vec![None, Some(3), None])),
+            Some(BuildError::UnknownNode(3))
+        );
+        assert_eq!(
+            build(TransactionModelBuilder::new().commit_tree(vec![None, Some(2), Some(1)])),
+            Some(BuildError::CyclicTree(1))
+        );
+        assert_eq!(
+            build(TransactionModelBuilder::new().read_only_participants(vec![0])),
+            Some(BuildError::ReadOnlyCoordinator(0))
+        );
+    }
+}
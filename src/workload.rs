@@ -0,0 +1,158 @@
+//! This is a set of workloads that determine the data operations of a distributed transaction.
+//!
+//! # Operations
+//!
+//! Every node stores a list of values, and the transaction performs a sequence of read and write
+//! operations on the data of each node. The writes are buffered on the node until it applies the
+//! decision of the transaction.
+//!
+//! # Invariant
+//!
+//! Each workload defines an invariant over the data of all the nodes that holds as long as the
+//! transaction is atomic, so that the same model checks the data-level consequences of the commit
+//! protocol against several representative workloads.
+
+use std::hash::{Hash, Hasher};
+
+/// [`Operation`] is a data operation that a transaction performs on a node.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Operation {
+    /// Reads the data.
+    Read,
+
+    /// Adds the value to the first value of the data.
+    Add(i64),
+
+    /// Appends the value to the data.
+    Append(i64),
+}
+
+impl Operation {
+    /// Applies the operation to the data.
+    pub fn apply(self, data: &mut Vec<i64>) {
+        match self {
+            Operation::Read => (),
+            Operation::Add(value) => {
+                if let Some(first) = data.first_mut() {
+                    *first += value;
+                }
+            }
+            Operation::Append(value) => data.push(value),
+        }
+    }
+}
+
+/// [`Workload`] produces the data operations that a transaction performs on each node.
+pub trait Workload: Send + Sync {
+    /// Returns the name of the workload.
+    fn name(&self) -> &'static str;
+
+    /// Returns the initial data stored on the node.
+    fn initial_data(&self, node_id: usize, num_nodes: usize) -> Vec<i64>;
+
+    /// Returns the operations that the transaction performs on the node.
+    fn operations(&self, node_id: usize, num_nodes: usize) -> Vec<Operation>;
+
+    /// Returns `true` if the data of the nodes satisfies the invariant of the workload.
+    fn is_consistent(&self, data: &[Vec<i64>]) -> bool;
+}
+
+impl Hash for dyn Workload {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+    }
+}
+
+/// Returns the workload of the name.
+#[must_use]
+pub fn by_name(name: &str) -> Option<Box<dyn Workload>> {
+    match name {
+        "transfer" => Some(Box::new(Transfer { amount: 30 })),
+        "append" => Some(Box::new(Append)),
+        "counter" => Some(Box::new(CounterIncrement)),
+        _ => None,
+    }
+}
+
+/// The initial balance of each account in [`Transfer`].
+const INITIAL_BALANCE: i64 = 100;
+
+/// [`Transfer`] moves money from the account on the first node to the account on the last node;
+/// the other nodes read their accounts.
+///
+/// The total balance is conserved.
+pub struct Transfer {
+    /// The amount of money to move.
+    amount: i64,
+}
+
+impl Workload for Transfer {
+    fn name(&self) -> &'static str {
+        "transfer"
+    }
+
+    fn initial_data(&self, _node_id: usize, _num_nodes: usize) -> Vec<i64> {
+        vec![INITIAL_BALANCE]
+    }
+
+    fn operations(&self, node_id: usize, num_nodes: usize) -> Vec<Operation> {
+        if node_id == 0 {
+            vec![Operation::Read, Operation::Add(-self.amount)]
+        } else if node_id + 1 == num_nodes {
+            vec![Operation::Add(self.amount)]
+        } else {
+            vec![Operation::Read]
+        }
+    }
+
+    fn is_consistent(&self, data: &[Vec<i64>]) -> bool {
+        data.iter().map(|data| data[0]).sum::<i64>()
+            == data.iter().map(|_| INITIAL_BALANCE).sum::<i64>()
+    }
+}
+
+/// [`Append`] appends the same entry to the log on every node.
+///
+/// The logs on all the nodes are identical.
+pub struct Append;
+
+impl Workload for Append {
+    fn name(&self) -> &'static str {
+        "append"
+    }
+
+    fn initial_data(&self, _node_id: usize, _num_nodes: usize) -> Vec<i64> {
+        Vec::new()
+    }
+
+    fn operations(&self, _node_id: usize, _num_nodes: usize) -> Vec<Operation> {
+        vec![Operation::Append(1)]
+    }
+
+    fn is_consistent(&self, data: &[Vec<i64>]) -> bool {
+        data.windows(2).all(|pair| pair[0] == pair[1])
+    }
+}
+
+/// [`CounterIncrement`] reads and increments the counter on every node.
+///
+/// The counters on all the nodes are equal.
+pub struct CounterIncrement;
+
+impl Workload for CounterIncrement {
+    fn name(&self) -> &'static str {
+        "counter"
+    }
+
+    fn initial_data(&self, _node_id: usize, _num_nodes: usize) -> Vec<i64> {
+        vec![0]
+    }
+
+    fn operations(&self, _node_id: usize, _num_nodes: usize) -> Vec<Operation> {
+        vec![Operation::Read, Operation::Add(1)]
+    }
+
+    fn is_consistent(&self, data: &[Vec<i64>]) -> bool {
+        data.windows(2).all(|pair| pair[0] == pair[1])
+    }
+}
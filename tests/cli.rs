@@ -0,0 +1,52 @@
+//! Tests of the exit codes of the model checker binary.
+
+use std::process::{Command, Output};
+
+/// The exit code of invalid options, or of a file that cannot be loaded.
+const EXIT_ERROR: i32 = 2;
+
+/// Runs the model checker with the arguments.
+fn model_check(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_transaction-model-check"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn invalid_nodes() {
+    let output = model_check(&["check", "--nodes", "abc"]);
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Invalid --nodes"));
+
+    let output = model_check(&["check", "--nodes", "1"]);
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("At least 2 nodes are needed."));
+}
+
+#[test]
+fn invalid_timeout() {
+    let output = model_check(&["check", "--timeout", "10x"]);
+    assert_eq!(output.status.code(), Some(EXIT_ERROR));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Invalid --timeout"));
+}
+
+#[test]
+fn invalid_option_values() {
+    for args in [
+        &["check", "--threads", "0"][..],
+        &["check", "--max-states", "ten"],
+        &["check", "--crashes", "-1"],
+        &["check", "--seed", "abc"],
+        &["check", "--parents", "0,x"],
+        &["check", "--workload"],
+        &["simulate", "--runs", "1k"],
+    ] {
+        let output = model_check(args);
+        assert_eq!(output.status.code(), Some(EXIT_ERROR), "{args:?}");
+        assert!(
+            String::from_utf8_lossy(&output.stdout).contains(&format!("Invalid {}", args[1])),
+            "{args:?}"
+        );
+    }
+}